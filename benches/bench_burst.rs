@@ -0,0 +1,29 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use x86_strings_ops::backend::try_copy_with_config;
+use x86_strings_ops::Config;
+
+const LEN: usize = 64 << 20;
+
+fn bench_max_burst_bytes(c: &mut Criterion) {
+    let src = vec![1_u8; LEN];
+    let mut dst = vec![0_u8; LEN];
+    let mut group = c.benchmark_group("copy_64mib");
+    group
+        .throughput(Throughput::Bytes(LEN as u64))
+        .sample_size(20);
+    for (name, max_burst_bytes) in [
+        ("burst_4kib", 4 << 10),
+        ("burst_64kib", 64 << 10),
+        ("burst_1mib", 1 << 20),
+        ("unbounded", usize::MAX),
+    ] {
+        let config = Config::new().with_max_burst_bytes(max_burst_bytes);
+        group.bench_function(name, |b| {
+            b.iter(|| try_copy_with_config(&src, &mut dst, &config))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_max_burst_bytes);
+criterion_main!(benches);
@@ -11,7 +11,9 @@ const BATCH_SIZE: usize = 4096;
 fn bench_inline_copy(dst: &mut [u8], src: &[u8], ranges: &[Range<usize>]) {
     assert_eq!(dst.len(), src.len());
     for range in ranges {
-        dst[range.clone()].inline_copy_from(&src[range.clone()]);
+        dst[range.clone()]
+            .try_inline_copy_from(&src[range.clone()])
+            .unwrap();
     }
 }
 
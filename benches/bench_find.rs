@@ -0,0 +1,76 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use x86_strings_ops::{Finder, SharedFinder, SliceExt};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const BATCH_SIZE: usize = 1024;
+
+static PREBUILT: Finder<'static> = Finder::new(b"Content-Length:");
+
+#[inline(never)]
+fn bench_construct_per_call(haystacks: &[Vec<u8>], needle: &[u8]) -> usize {
+    haystacks
+        .iter()
+        .filter_map(|haystack| Finder::new(needle).find(haystack))
+        .sum()
+}
+
+#[inline(never)]
+fn bench_prebuilt(haystacks: &[Vec<u8>], finder: &Finder) -> usize {
+    haystacks
+        .iter()
+        .filter_map(|haystack| finder.find(haystack))
+        .sum()
+}
+
+#[inline(never)]
+fn bench_shared(haystacks: &[Vec<u8>], finder: &SharedFinder) -> usize {
+    haystacks
+        .iter()
+        .filter_map(|haystack| finder.find(haystack))
+        .sum()
+}
+
+#[inline(never)]
+fn bench_find_subslice(haystacks: &[Vec<u8>], needle: &[u8]) -> usize {
+    haystacks
+        .iter()
+        .filter_map(|haystack| haystack.inline_find_subslice(needle))
+        .sum()
+}
+
+fn bench_headers(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    let needle = PREBUILT.needle();
+    let haystacks = (0..BATCH_SIZE)
+        .map(|_| {
+            let mut headers = Vec::new();
+            for _ in 0..rng.gen_range(2..10) {
+                let len = rng.gen_range(5..40);
+                headers.extend((0..len).map(|_| rng.gen_range(b'a'..=b'z')));
+                headers.extend_from_slice(b": value\r\n");
+            }
+            headers.extend_from_slice(b"Content-Length: 42\r\n\r\n");
+            headers
+        })
+        .collect::<Vec<_>>();
+    let shared = SharedFinder::new(needle);
+
+    c.benchmark_group("find_header")
+        .bench_function("construct_per_call", |b| {
+            b.iter(|| bench_construct_per_call(&haystacks, black_box(needle)))
+        })
+        .bench_function("prebuilt_static", |b| {
+            b.iter(|| bench_prebuilt(&haystacks, black_box(&PREBUILT)))
+        })
+        .bench_function("shared", |b| {
+            b.iter(|| bench_shared(&haystacks, black_box(&shared)))
+        })
+        .bench_function("inline_find_subslice", |b| {
+            b.iter(|| bench_find_subslice(&haystacks, black_box(needle)))
+        });
+}
+
+criterion_group!(benches, bench_headers);
+criterion_main!(benches);
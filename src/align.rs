@@ -0,0 +1,55 @@
+use crate::Error;
+
+/// Return the number of bytes by which `ptr` is past the previous multiple of the alignment of
+/// `T`, which is zero for aligned pointers.
+#[inline]
+pub fn misalignment<T>(ptr: *const T) -> usize {
+    ptr.addr() & (core::mem::align_of::<T>() - 1)
+}
+
+/// Check that `ptr` is properly aligned for `T`, as required by the string instruction
+/// primitives, or return [`Error::Misaligned`] with the misalignment offset.
+///
+/// With the `checked` feature the primitives use this to panic on misaligned pointers instead of
+/// causing undefined behavior.
+#[inline]
+pub fn ensure_aligned<T>(ptr: *const T) -> Result<(), Error> {
+    match misalignment(ptr) {
+        0 => Ok(()),
+        offset => Err(Error::Misaligned {
+            align: core::mem::align_of::<T>(),
+            offset,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_aligned() {
+        let buf = [0_u64; 2];
+        let base = buf.as_ptr();
+        assert_eq!(ensure_aligned(base), Ok(()));
+        assert_eq!(ensure_aligned(base.cast::<u8>().wrapping_add(3)), Ok(()));
+        let unaligned = base.cast::<u8>().wrapping_add(6).cast::<u32>();
+        assert_eq!(misalignment(unaligned), 2);
+        assert_eq!(
+            ensure_aligned(unaligned),
+            Err(Error::Misaligned {
+                align: 4,
+                offset: 2
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "rep_stos: `dst` pointer")]
+    fn test_checked_primitive() {
+        let mut buf = [0_u32; 4];
+        let dst = buf.as_mut_ptr().cast::<u8>().wrapping_add(1).cast::<u32>();
+        unsafe { crate::rep_stos(0_u32, dst, 0) };
+    }
+}
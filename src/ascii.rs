@@ -0,0 +1,227 @@
+use crate::{position_any, rep_cmps, ByteSet, SliceExt};
+use core::cmp::Ordering;
+use std::borrow::Cow;
+
+const ASCII_UPPERCASE: ByteSet = ByteSet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+
+/// Return the index of the first byte that differs between `a` and `b` when ignoring ASCII case.
+///
+/// The exactly equal prefix is skipped using a single `rep cmps`. From the first byte where it
+/// stops, both inputs are case folded by setting bit 5 of the upper case letters, 16 bytes at a
+/// time using SSE2 on x86_64 and 8 bytes at a time otherwise, without restarting `rep cmps`.
+#[inline]
+fn mismatch_ignore_ascii_case(a: &[u8], b: &[u8]) -> Option<usize> {
+    let len = a.len();
+    debug_assert_eq!(len, b.len());
+    let start = unsafe { rep_cmps(a.as_ptr(), b.as_ptr(), len) }?;
+    mismatch_folded(&a[start..], &b[start..]).map(|index| start + index)
+}
+
+/// Return the index of the first byte that differs between the ASCII lower case versions of the
+/// equally long `a` and `b`.
+#[inline]
+fn mismatch_folded(a: &[u8], b: &[u8]) -> Option<usize> {
+    let len = a.len().min(b.len());
+    let mut offset = 0;
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    unsafe {
+        use core::arch::x86_64::*;
+
+        let fold = |x: __m128i| {
+            let upper = _mm_and_si128(
+                _mm_cmpgt_epi8(x, _mm_set1_epi8(b'A' as i8 - 1)),
+                _mm_cmpgt_epi8(_mm_set1_epi8(b'Z' as i8 + 1), x),
+            );
+            _mm_or_si128(x, _mm_and_si128(upper, _mm_set1_epi8(0x20)))
+        };
+        while offset + 16 <= len {
+            let x = _mm_loadu_si128(a.as_ptr().add(offset).cast::<__m128i>());
+            let y = _mm_loadu_si128(b.as_ptr().add(offset).cast::<__m128i>());
+            let equal = _mm_movemask_epi8(_mm_cmpeq_epi8(fold(x), fold(y))) as u32;
+            if equal != 0xFFFF {
+                return Some(offset + (!equal).trailing_zeros() as usize);
+            }
+            offset += 16;
+        }
+    }
+    while offset + 8 <= len {
+        let word = |src: &[u8]| u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
+        let diff = fold_word(word(a)) ^ fold_word(word(b));
+        if diff != 0 {
+            return Some(offset + diff.trailing_zeros() as usize / 8);
+        }
+        offset += 8;
+    }
+    (offset..len).find(|&index| !a[index].eq_ignore_ascii_case(&b[index]))
+}
+
+/// Convert the upper case ASCII letters in the 8 bytes of `word` to lower case.
+#[inline]
+fn fold_word(word: u64) -> u64 {
+    const LANES: u64 = 0x0101_0101_0101_0101;
+    const HIGH: u64 = 0x80 * LANES;
+    // the low 7 bits of every byte, so that the additions below can not carry into the next byte
+    let low = word & !HIGH;
+    let at_least_a = low + (0x80 - u64::from(b'A')) * LANES;
+    let above_z = low + (0x80 - u64::from(b'Z') - 1) * LANES;
+    let upper = at_least_a & !above_z & !word & HIGH;
+    word | (upper >> 2)
+}
+
+/// Check whether `a` and `b` are equal when ignoring ASCII case.
+///
+/// Same as [`<[u8]>::eq_ignore_ascii_case`](slice::eq_ignore_ascii_case), but skips over exactly
+/// equal runs of bytes using `rep cmps` instructions.
+pub fn eq_ignore_ascii_case_inline(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && mismatch_ignore_ascii_case(a, b).is_none()
+}
+
+/// Lexicographically compare `a` and `b` as if both were converted to ASCII lower case.
+pub fn cmp_ignore_ascii_case(a: &[u8], b: &[u8]) -> Ordering {
+    let len = a.len().min(b.len());
+    match mismatch_ignore_ascii_case(&a[..len], &b[..len]) {
+        Some(index) => a[index]
+            .to_ascii_lowercase()
+            .cmp(&b[index].to_ascii_lowercase()),
+        None => a.len().cmp(&b.len()),
+    }
+}
+
+/// Return the start index of the first occurrence of `needle` in `haystack` when ignoring
+/// ASCII case.
+///
+/// Candidate positions are located by scanning for both cases of the first byte of `needle`
+/// using `repne scas`. An empty `needle` matches at index 0.
+pub fn find_ignore_ascii_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let Some(&first) = needle.first() else {
+        return Some(0);
+    };
+    let candidates = &haystack[..haystack.len().checked_sub(needle.len())? + 1];
+    let scan = |byte: u8, from: usize| {
+        candidates[from..]
+            .inline_position(byte)
+            .map(|index| from + index)
+    };
+    let (lower, upper) = (first.to_ascii_lowercase(), first.to_ascii_uppercase());
+    let mut next_lower = scan(lower, 0);
+    let mut next_upper = if lower != upper { scan(upper, 0) } else { None };
+    loop {
+        let index = match (next_lower, next_upper) {
+            (Some(l), Some(u)) => l.min(u),
+            (Some(l), None) => l,
+            (None, Some(u)) => u,
+            (None, None) => return None,
+        };
+        let window = &haystack[index..index + needle.len()];
+        if mismatch_ignore_ascii_case(window, needle).is_none() {
+            return Some(index);
+        }
+        if next_lower == Some(index) {
+            next_lower = scan(lower, index + 1);
+        } else {
+            next_upper = scan(upper, index + 1);
+        }
+    }
+}
+
+/// Convert `src` to ASCII lower case, borrowing it if it contains no upper case letters.
+///
+/// The input is first scanned for upper case letters using [`position_any`], only if one is
+/// found the input is copied and the part starting at that letter is converted.
+pub fn normalize_ascii_lowercase(src: &[u8]) -> Cow<'_, [u8]> {
+    match position_any(src, &ASCII_UPPERCASE) {
+        None => Cow::Borrowed(src),
+        Some(first) => {
+            let mut owned = src.to_vec();
+            owned[first..].make_ascii_lowercase();
+            Cow::Owned(owned)
+        }
+    }
+}
+
+/// Same as [`normalize_ascii_lowercase`], but first removes leading and trailing ASCII
+/// whitespace, which only borrows a shorter part of `src`.
+pub fn normalize_ascii_lowercase_trimmed(src: &[u8]) -> Cow<'_, [u8]> {
+    normalize_ascii_lowercase(src.trim_ascii())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eq_ignore_ascii_case() {
+        assert!(eq_ignore_ascii_case_inline(b"", b""));
+        assert!(eq_ignore_ascii_case_inline(
+            b"Content-Length",
+            b"content-length"
+        ));
+        assert!(eq_ignore_ascii_case_inline(b"HOST", b"host"));
+        assert!(!eq_ignore_ascii_case_inline(b"host", b"hosts"));
+        assert!(!eq_ignore_ascii_case_inline(b"host", b"hast"));
+        assert!(!eq_ignore_ascii_case_inline(b"@", b"`"));
+        assert!(!eq_ignore_ascii_case_inline(b"[", b"{"));
+    }
+
+    #[test]
+    fn test_mismatch_folded() {
+        for x in 0..=255_u8 {
+            for y in [x, x ^ 0x20, x.to_ascii_lowercase(), b'a', b'Z', 0x80] {
+                for position in [0, 7, 8, 15, 16, 20, 36] {
+                    let mut a = b"Content-Length: 42, Transfer-Encoding".to_vec();
+                    let mut b = a.to_ascii_uppercase();
+                    a[position] = x;
+                    b[position] = y;
+                    let expected = (0..a.len()).find(|&i| !a[i].eq_ignore_ascii_case(&b[i]));
+                    assert_eq!(mismatch_ignore_ascii_case(&a, &b), expected, "{x} {y}");
+                    assert_eq!(mismatch_folded(&a, &b), expected, "{x} {y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cmp_ignore_ascii_case() {
+        assert_eq!(cmp_ignore_ascii_case(b"abc", b"ABC"), Ordering::Equal);
+        assert_eq!(cmp_ignore_ascii_case(b"abc", b"ABD"), Ordering::Less);
+        assert_eq!(cmp_ignore_ascii_case(b"Abd", b"abc"), Ordering::Greater);
+        assert_eq!(cmp_ignore_ascii_case(b"ab", b"ABC"), Ordering::Less);
+        assert_eq!(cmp_ignore_ascii_case(b"abc", b"AB"), Ordering::Greater);
+        assert_eq!(cmp_ignore_ascii_case(b"_", b"A"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_find_ignore_ascii_case() {
+        let haystack = b"Accept: text/html; Charset=UTF-8";
+        assert_eq!(find_ignore_ascii_case(haystack, b"charset"), Some(19));
+        assert_eq!(find_ignore_ascii_case(haystack, b"ACCEPT"), Some(0));
+        assert_eq!(find_ignore_ascii_case(haystack, b"utf-8"), Some(27));
+        assert_eq!(find_ignore_ascii_case(haystack, b"utf-16"), None);
+        assert_eq!(find_ignore_ascii_case(haystack, b": "), Some(6));
+        assert_eq!(find_ignore_ascii_case(haystack, b""), Some(0));
+        assert_eq!(find_ignore_ascii_case(b"ab", b"abc"), None);
+        assert_eq!(find_ignore_ascii_case(b"aAaAb", b"AAB"), Some(2));
+    }
+
+    #[test]
+    fn test_normalize_ascii_lowercase() {
+        let normalized = normalize_ascii_lowercase(b"content-type");
+        assert!(matches!(normalized, Cow::Borrowed(b"content-type")));
+        let normalized = normalize_ascii_lowercase(b"content-Type");
+        assert!(matches!(normalized, Cow::Owned(ref v) if v == b"content-type"));
+        assert_eq!(&*normalize_ascii_lowercase(b"X-\xC4Z"), b"x-\xC4z");
+        assert!(matches!(normalize_ascii_lowercase(b""), Cow::Borrowed(b"")));
+    }
+
+    #[test]
+    fn test_normalize_ascii_lowercase_trimmed() {
+        let normalized = normalize_ascii_lowercase_trimmed(b"  host\t");
+        assert!(matches!(normalized, Cow::Borrowed(b"host")));
+        let normalized = normalize_ascii_lowercase_trimmed(b" Host ");
+        assert!(matches!(normalized, Cow::Owned(ref v) if v == b"host"));
+        assert!(matches!(
+            normalize_ascii_lowercase_trimmed(b" \r\n"),
+            Cow::Borrowed(b"")
+        ));
+    }
+}
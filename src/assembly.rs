@@ -14,13 +14,49 @@
 
 use crate::RegisterType;
 
+/// Checks for the documented preconditions of the string operations.
+///
+/// The fallback implementations used under Miri would otherwise silently accept some misuse,
+/// for example overlapping regions passed to [`rep_movs`] or lengths that do not fit into an
+/// allocation, which would be undefined behavior with the inline assembly. With the `checked`
+/// feature the inline assembly implementations check the alignment of their pointers.
+#[cfg(any(miri, feature = "checked"))]
+mod preconditions {
+    #[track_caller]
+    pub(super) fn check_aligned<T>(op: &str, name: &str, ptr: *const T) {
+        if let Err(error) = crate::ensure_aligned(ptr) {
+            panic!("{op}: `{name}` pointer {ptr:?}: {error}");
+        }
+    }
+
+    #[cfg(miri)]
+    #[track_caller]
+    pub(super) fn check_region<T>(op: &str, name: &str, ptr: *const T, len: usize) {
+        check_aligned(op, name, ptr);
+        assert!(
+            len.checked_mul(core::mem::size_of::<T>())
+                .is_some_and(|bytes| bytes <= isize::MAX as usize),
+            "{op}: length {len} of `{name}` exceeds the maximum allocation size"
+        );
+    }
+
+    #[cfg(miri)]
+    #[track_caller]
+    pub(super) fn check_nonoverlapping<T>(op: &str, src: *const T, dst: *const T, len: usize) {
+        assert!(
+            crate::ranges_relation(src, dst, len) == crate::RangesRelation::Disjoint,
+            "{op}: `src` {src:?} and `dst` {dst:?} overlap for length {len}"
+        );
+    }
+}
+
 /// Copy `len` elements from `src` to `dst`.
 ///
 /// On x86_64 this implementation will use inline `rep movs` instructions.
 ///
 /// On other architectures this will fall back to `copy_nonoverlapping`.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// The same safety considerations as for [`core::ptr::copy_nonoverlapping`] apply:
 ///
@@ -28,30 +64,164 @@ use crate::RegisterType;
 ///  - `src` and `dst` memory regions must not overlap
 ///  - pointers need to be properly aligned
 #[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
 pub unsafe fn rep_movs<T: Copy>(src: *const T, dst: *mut T, len: usize) {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        #[cfg(feature = "checked")]
+        {
+            preconditions::check_aligned("rep_movs", "src", src);
+            preconditions::check_aligned("rep_movs", "dst", dst);
+        }
+
+        rep_movs_forward(src, dst, len)
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        #[cfg(miri)]
+        {
+            preconditions::check_region("rep_movs", "src", src, len);
+            preconditions::check_region("rep_movs", "dst", dst, len);
+            preconditions::check_nonoverlapping("rep_movs", src, dst, len);
+        }
+        core::ptr::copy_nonoverlapping(src, dst, len)
+    }
+}
+
+/// Copy `len` elements from `src` to `dst` using inline `rep movs` instructions, starting with
+/// the first element.
+///
+/// The instructions behave as if the elements were copied one at a time with increasing
+/// addresses, so unlike for [`rep_movs`] the regions may overlap if `dst` starts before `src`.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::copy`] apply, and `dst` must not start
+/// after `src` if the regions overlap:
+///
+///  - `src` and `dst` need to be valid for the given `len`
+///  - pointers need to be properly aligned
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+#[inline(always)]
+unsafe fn rep_movs_forward<T: Copy>(src: *const T, dst: *mut T, len: usize) {
+    use core::arch::asm;
+
+    let size = core::mem::size_of::<T>();
+    match size {
+        8 => {
+            asm!("rep movsq", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+        }
+        4 => {
+            asm!("rep movsd", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+        }
+        2 => {
+            asm!("rep movsw", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+        }
+        _ => {
+            asm!("rep movsb", inout("rcx") len * size => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+        }
+    }
+}
+
+/// Copy `len` elements from `src` to `dst`, starting with the last element.
+///
+/// On x86_64 this implementation will use inline `rep movs` instructions with the direction flag
+/// set, which is cleared again in the same assembly block as required by the ABI. Copying
+/// backwards is correct for overlapping regions where `dst` starts after `src`, for example to
+/// shift the elements of a buffer towards its end.
+///
+/// On other architectures this will fall back to `copy`.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::copy`] apply, and `dst` must not start
+/// before `src` if the regions overlap:
+///
+///  - `src` and `dst` need to be valid for the given `len`
+///  - pointers need to be properly aligned
+#[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
+pub unsafe fn rep_movs_backward<T: Copy>(src: *const T, dst: *mut T, len: usize) {
     #[cfg(all(target_arch = "x86_64", not(miri)))]
     {
         use core::arch::asm;
 
+        #[cfg(feature = "checked")]
+        {
+            preconditions::check_aligned("rep_movs_backward", "src", src);
+            preconditions::check_aligned("rep_movs_backward", "dst", dst);
+        }
+
+        // the instructions start at the last element, or the last byte for the byte form
         let size = core::mem::size_of::<T>();
+        let (src, dst) = (src.cast::<u8>(), dst.cast::<u8>());
         match size {
-            8 => {
-                asm!("rep movsq", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
-            }
-            4 => {
-                asm!("rep movsd", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
-            }
-            2 => {
-                asm!("rep movsw", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+            8 | 4 | 2 => {
+                let offset = (len * size).wrapping_sub(size);
+                let (src, dst) = (src.wrapping_add(offset), dst.wrapping_add(offset));
+                match size {
+                    8 => {
+                        asm!("std", "rep movsq", "cld", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+                    }
+                    4 => {
+                        asm!("std", "rep movsd", "cld", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+                    }
+                    _ => {
+                        asm!("std", "rep movsw", "cld", inout("rcx") len => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+                    }
+                }
             }
             _ => {
-                asm!("rep movsb", inout("rcx") len * size => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
+                let offset = (len * size).wrapping_sub(1);
+                let (src, dst) = (src.wrapping_add(offset), dst.wrapping_add(offset));
+                asm!("std", "rep movsb", "cld", inout("rcx") len * size => _, inout("rsi") src => _, inout("rdi") dst => _, options(nostack))
             }
         }
     }
     #[cfg(not(all(target_arch = "x86_64", not(miri))))]
     {
-        core::ptr::copy_nonoverlapping(src, dst, len)
+        #[cfg(miri)]
+        {
+            preconditions::check_region("rep_movs_backward", "src", src, len);
+            preconditions::check_region("rep_movs_backward", "dst", dst, len);
+        }
+        core::ptr::copy(src, dst, len)
+    }
+}
+
+/// Copy `len` elements from `src` to `dst`, which may overlap, like `memmove`.
+///
+/// Uses [`rep_movs_backward`] if `dst` starts inside of `src`, a forward `rep movs` if `src`
+/// starts inside of `dst`, and [`rep_movs`] for disjoint regions.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::copy`] apply:
+///
+///  - `src` and `dst` need to be valid for the given `len`
+///  - pointers need to be properly aligned
+#[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
+pub unsafe fn rep_movs_overlapping<T: Copy>(src: *const T, dst: *mut T, len: usize) {
+    use crate::{ranges_relation, RangesRelation};
+
+    match ranges_relation(src, dst, len) {
+        RangesRelation::ForwardOverlap => rep_movs_backward(src, dst, len),
+        RangesRelation::Identical => {}
+        RangesRelation::BackwardOverlap => {
+            #[cfg(all(target_arch = "x86_64", not(miri)))]
+            {
+                #[cfg(feature = "checked")]
+                {
+                    preconditions::check_aligned("rep_movs_overlapping", "src", src);
+                    preconditions::check_aligned("rep_movs_overlapping", "dst", dst);
+                }
+                rep_movs_forward(src, dst, len);
+            }
+            #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+            core::ptr::copy(src, dst, len);
+        }
+        RangesRelation::Disjoint => rep_movs(src, dst, len),
     }
 }
 
@@ -61,18 +231,22 @@ pub unsafe fn rep_movs<T: Copy>(src: *const T, dst: *mut T, len: usize) {
 ///
 /// On other architectures this will fall back to `slice::fill`.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// The same safety considerations as for [`core::ptr::write`] apply:
 ///
 ///  - dst must be valid for writes
 ///  - dst must be properly aligned
 #[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
 pub unsafe fn rep_stos<T: Copy>(src: T, dst: *mut T, len: usize) {
     #[cfg(all(target_arch = "x86_64", not(miri)))]
     {
         use core::arch::asm;
 
+        #[cfg(feature = "checked")]
+        preconditions::check_aligned("rep_stos", "dst", dst);
+
         let size = core::mem::size_of::<T>();
         match size {
             8 => {
@@ -95,6 +269,8 @@ pub unsafe fn rep_stos<T: Copy>(src: T, dst: *mut T, len: usize) {
     }
     #[cfg(not(all(target_arch = "x86_64", not(miri))))]
     {
+        #[cfg(miri)]
+        preconditions::check_region("rep_stos", "dst", dst, len);
         core::slice::from_raw_parts_mut(dst, len).fill(src)
     }
 }
@@ -103,25 +279,32 @@ pub unsafe fn rep_stos<T: Copy>(src: T, dst: *mut T, len: usize) {
 ///
 /// On x86_64 this implementation will use inline `rep cmps` instructions.
 ///
-/// On other architectures this will fall back to `slice::iter::position`.
+/// On other architectures this will fall back to a word at a time scalar loop.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// The same safety considerations as for [`core::ptr::write`] apply:
 ///
 ///  - `a` and `b` need to be valid for the given `len`
 ///  - pointers need to be properly aligned
 #[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
 pub unsafe fn rep_cmps<T: RegisterType>(a: *const T, b: *const T, len: usize) -> Option<usize> {
     #[cfg(all(target_arch = "x86_64", not(miri)))]
     {
+        use crate::AsmArm;
         use core::arch::asm;
 
-        let size = core::mem::size_of::<T>();
+        #[cfg(feature = "checked")]
+        {
+            preconditions::check_aligned("rep_cmps", "a", a);
+            preconditions::check_aligned("rep_cmps", "b", b);
+        }
+
         let mut eq: u8;
         let mut p: *const T;
-        match size {
-            8 => {
+        match T::ASM_ARM {
+            Some(AsmArm::Qword) => {
                 asm!(
                 "test rcx, rcx",
                 "repe cmpsq",
@@ -130,7 +313,7 @@ pub unsafe fn rep_cmps<T: RegisterType>(a: *const T, b: *const T, len: usize) ->
                 options(nostack, readonly)
                 );
             }
-            4 => {
+            Some(AsmArm::Dword) => {
                 asm! {
                 "test rcx, rcx",
                 "repe cmpsd",
@@ -139,7 +322,7 @@ pub unsafe fn rep_cmps<T: RegisterType>(a: *const T, b: *const T, len: usize) ->
                 options(nostack, readonly)
                 };
             }
-            2 => {
+            Some(AsmArm::Word) => {
                 asm!(
                 "test rcx, rcx",
                 "repe cmpsw",
@@ -148,7 +331,7 @@ pub unsafe fn rep_cmps<T: RegisterType>(a: *const T, b: *const T, len: usize) ->
                 options(nostack, readonly)
                 );
             }
-            _ => {
+            Some(AsmArm::Byte) => {
                 asm!(
                 "test rcx, rcx",
                 "repe cmpsb",
@@ -157,6 +340,12 @@ pub unsafe fn rep_cmps<T: RegisterType>(a: *const T, b: *const T, len: usize) ->
                 options(nostack, readonly)
                 );
             }
+            None => {
+                return core::slice::from_raw_parts(a, len)
+                    .iter()
+                    .zip(core::slice::from_raw_parts(b, len))
+                    .position(|(a, b)| !a.bitwise_eq(b));
+            }
         }
         if (eq & 0b1) == 0 {
             Some(p.offset_from(a) as usize - 1)
@@ -166,10 +355,220 @@ pub unsafe fn rep_cmps<T: RegisterType>(a: *const T, b: *const T, len: usize) ->
     }
     #[cfg(not(all(target_arch = "x86_64", not(miri))))]
     {
+        #[cfg(miri)]
+        {
+            preconditions::check_region("rep_cmps", "a", a, len);
+            preconditions::check_region("rep_cmps", "b", b, len);
+        }
+        #[cfg(miri)]
+        return core::slice::from_raw_parts(a, len)
+            .iter()
+            .zip(core::slice::from_raw_parts(b, len))
+            .position(|(a, b)| !a.bitwise_eq(b));
+        #[cfg(not(miri))]
+        crate::swar::mismatch(a, b, len)
+    }
+}
+
+/// Return the index of the last mismatching element between `a` and `b`.
+///
+/// On x86_64 this implementation will use inline `repe cmps` instructions with the direction
+/// flag set, comparing from the end of both regions towards their start. The direction flag is
+/// cleared again in the same assembly block, as required by the ABI.
+///
+/// On other architectures this will fall back to `Iterator::rposition`.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::read`] apply:
+///
+///  - `a` and `b` need to be valid for the given `len`
+///  - pointers need to be properly aligned
+#[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
+pub unsafe fn rep_cmps_reverse<T: RegisterType>(
+    a: *const T,
+    b: *const T,
+    len: usize,
+) -> Option<usize> {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use crate::AsmArm;
+        use core::arch::asm;
+
+        #[cfg(feature = "checked")]
+        {
+            preconditions::check_aligned("rep_cmps_reverse", "a", a);
+            preconditions::check_aligned("rep_cmps_reverse", "b", b);
+        }
+
+        if len == 0 {
+            return None;
+        }
+        let (last_a, last_b) = (a.add(len - 1), b.add(len - 1));
+        let mut eq: u8;
+        let mut p: *const T;
+        match T::ASM_ARM {
+            Some(AsmArm::Qword) => {
+                asm!(
+                "std",
+                "repe cmpsq",
+                "cld",
+                "sete {eq}",
+                inout("rcx") len => _, inout("rdi") last_a => p, inout("rsi") last_b => _, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Dword) => {
+                asm!(
+                "std",
+                "repe cmpsd",
+                "cld",
+                "sete {eq}",
+                inout("rcx") len => _, inout("rdi") last_a => p, inout("rsi") last_b => _, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Word) => {
+                asm!(
+                "std",
+                "repe cmpsw",
+                "cld",
+                "sete {eq}",
+                inout("rcx") len => _, inout("rdi") last_a => p, inout("rsi") last_b => _, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Byte) => {
+                asm!(
+                "std",
+                "repe cmpsb",
+                "cld",
+                "sete {eq}",
+                inout("rcx") len => _, inout("rdi") last_a => p, inout("rsi") last_b => _, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            None => {
+                return core::slice::from_raw_parts(a, len)
+                    .iter()
+                    .zip(core::slice::from_raw_parts(b, len))
+                    .rposition(|(a, b)| !a.bitwise_eq(b));
+            }
+        }
+        if (eq & 0b1) == 0 {
+            // `rdi` was decremented past the mismatching element
+            Some(p.wrapping_add(1).offset_from(a) as usize)
+        } else {
+            None
+        }
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        #[cfg(miri)]
+        {
+            preconditions::check_region("rep_cmps_reverse", "a", a, len);
+            preconditions::check_region("rep_cmps_reverse", "b", b, len);
+        }
         core::slice::from_raw_parts(a, len)
             .iter()
             .zip(core::slice::from_raw_parts(b, len))
-            .position(|(a, b)| !a.bitwise_eq(b))
+            .rposition(|(a, b)| !a.bitwise_eq(b))
+    }
+}
+
+/// Return the index of the first element that is equal in `a` and `b`.
+///
+/// On x86_64 this implementation will use inline `repne cmps` instructions.
+///
+/// On other architectures this will fall back to a word at a time scalar loop.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::read`] apply:
+///
+///  - `a` and `b` need to be valid for the given `len`
+///  - pointers need to be properly aligned
+#[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
+pub unsafe fn repne_cmps<T: RegisterType>(a: *const T, b: *const T, len: usize) -> Option<usize> {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use crate::AsmArm;
+        use core::arch::asm;
+
+        #[cfg(feature = "checked")]
+        {
+            preconditions::check_aligned("repne_cmps", "a", a);
+            preconditions::check_aligned("repne_cmps", "b", b);
+        }
+
+        let mut eq: u8;
+        let mut p: *const T;
+        match T::ASM_ARM {
+            Some(AsmArm::Qword) => {
+                asm!(
+                "test rdi, rdi # clear ZF",
+                "repne cmpsq",
+                "sete {eq}",
+                inout("rcx") len => _, inout("rdi") a => p, inout("rsi") b => _, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Dword) => {
+                asm!(
+                "test rdi, rdi # clear ZF",
+                "repne cmpsd",
+                "sete {eq}",
+                inout("rcx") len => _, inout("rdi") a => p, inout("rsi") b => _, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Word) => {
+                asm!(
+                "test rdi, rdi # clear ZF",
+                "repne cmpsw",
+                "sete {eq}",
+                inout("rcx") len => _, inout("rdi") a => p, inout("rsi") b => _, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Byte) => {
+                asm!(
+                "test rdi, rdi # clear ZF",
+                "repne cmpsb",
+                "sete {eq}",
+                inout("rcx") len => _, inout("rdi") a => p, inout("rsi") b => _, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            None => {
+                return core::slice::from_raw_parts(a, len)
+                    .iter()
+                    .zip(core::slice::from_raw_parts(b, len))
+                    .position(|(a, b)| a.bitwise_eq(b));
+            }
+        }
+        if (eq & 0b1) != 0 {
+            Some(p.offset_from(a) as usize - 1)
+        } else {
+            None
+        }
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        #[cfg(miri)]
+        {
+            preconditions::check_region("repne_cmps", "a", a, len);
+            preconditions::check_region("repne_cmps", "b", b, len);
+        }
+        #[cfg(miri)]
+        return core::slice::from_raw_parts(a, len)
+            .iter()
+            .zip(core::slice::from_raw_parts(b, len))
+            .position(|(a, b)| a.bitwise_eq(b));
+        #[cfg(not(miri))]
+        crate::swar::match_position(a, b, len)
     }
 }
 
@@ -177,25 +576,29 @@ pub unsafe fn rep_cmps<T: RegisterType>(a: *const T, b: *const T, len: usize) ->
 ///
 /// On x86_64 this implementation will use inline `rep scas` instructions.
 ///
-/// On other architectures this will fall back to `slice::iter::position`.
+/// On other architectures this will fall back to a word at a time scalar loop.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// The same safety considerations as for [`core::ptr::write`] apply:
 ///
 ///  - `src` needs to be valid for the given `len`
 ///  - pointers need to be properly aligned
 #[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
 pub unsafe fn rep_scas<T: RegisterType>(src: *const T, value: T, len: usize) -> Option<usize> {
     #[cfg(all(target_arch = "x86_64", not(miri)))]
     {
+        use crate::AsmArm;
         use core::arch::asm;
 
-        let size = core::mem::size_of::<T>();
+        #[cfg(feature = "checked")]
+        preconditions::check_aligned("rep_scas", "src", src);
+
         let mut eq: u8;
         let mut p: *const T;
-        match size {
-            8 => {
+        match T::ASM_ARM {
+            Some(AsmArm::Qword) => {
                 let value: u64 = core::mem::transmute_copy(&value);
                 asm!(
                 "test rdi, rdi # clear ZF",
@@ -205,7 +608,7 @@ pub unsafe fn rep_scas<T: RegisterType>(src: *const T, value: T, len: usize) ->
                 options(nostack, readonly)
                 );
             }
-            4 => {
+            Some(AsmArm::Dword) => {
                 let value: u32 = core::mem::transmute_copy(&value);
                 asm! {
                 "test rdi, rdi # clear ZF",
@@ -215,7 +618,7 @@ pub unsafe fn rep_scas<T: RegisterType>(src: *const T, value: T, len: usize) ->
                 options(nostack, readonly)
                 };
             }
-            2 => {
+            Some(AsmArm::Word) => {
                 let value: u16 = core::mem::transmute_copy(&value);
                 asm!(
                 "test rdi, rdi # clear ZF",
@@ -225,7 +628,7 @@ pub unsafe fn rep_scas<T: RegisterType>(src: *const T, value: T, len: usize) ->
                 options(nostack, readonly)
                 );
             }
-            _ => {
+            Some(AsmArm::Byte) => {
                 let value: u8 = core::mem::transmute_copy(&value);
                 asm!(
                 "test rdi, rdi # clear ZF",
@@ -235,6 +638,11 @@ pub unsafe fn rep_scas<T: RegisterType>(src: *const T, value: T, len: usize) ->
                 options(nostack, readonly)
                 );
             }
+            None => {
+                return core::slice::from_raw_parts(src, len)
+                    .iter()
+                    .position(|a| a.bitwise_eq(&value));
+            }
         }
         if (eq & 0b1) != 0 {
             Some(p.offset_from(src) as usize - 1)
@@ -244,12 +652,329 @@ pub unsafe fn rep_scas<T: RegisterType>(src: *const T, value: T, len: usize) ->
     }
     #[cfg(not(all(target_arch = "x86_64", not(miri))))]
     {
+        #[cfg(miri)]
+        preconditions::check_region("rep_scas", "src", src, len);
+        #[cfg(miri)]
+        return core::slice::from_raw_parts(src, len)
+            .iter()
+            .position(|a| a.bitwise_eq(&value));
+        #[cfg(not(miri))]
+        crate::swar::position(src, value, len)
+    }
+}
+
+/// Return the index of the last occurrence of `value` in `src`.
+///
+/// On x86_64 this implementation will use inline `repne scas` instructions with the direction
+/// flag set, scanning from the end of `src` towards its start. The direction flag is cleared
+/// again in the same assembly block, as required by the ABI.
+///
+/// On other architectures this will fall back to `Iterator::rposition`.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::read`] apply:
+///
+///  - `src` needs to be valid for the given `len`
+///  - pointers need to be properly aligned
+#[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
+pub unsafe fn rep_scas_reverse<T: RegisterType>(
+    src: *const T,
+    value: T,
+    len: usize,
+) -> Option<usize> {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use crate::AsmArm;
+        use core::arch::asm;
+
+        #[cfg(feature = "checked")]
+        preconditions::check_aligned("rep_scas_reverse", "src", src);
+
+        if len == 0 {
+            return None;
+        }
+        let last = src.add(len - 1);
+        let mut eq: u8;
+        let mut p: *const T;
+        match T::ASM_ARM {
+            Some(AsmArm::Qword) => {
+                let value: u64 = core::mem::transmute_copy(&value);
+                asm!(
+                "test rdi, rdi # clear ZF",
+                "std",
+                "repne scasq",
+                "cld",
+                "sete {eq}",
+                in("rax") value, inout("rcx") len => _, inout("rdi") last => p, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Dword) => {
+                let value: u32 = core::mem::transmute_copy(&value);
+                asm!(
+                "test rdi, rdi # clear ZF",
+                "std",
+                "repne scasd",
+                "cld",
+                "sete {eq}",
+                in("eax") value, inout("rcx") len => _, inout("rdi") last => p, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Word) => {
+                let value: u16 = core::mem::transmute_copy(&value);
+                asm!(
+                "test rdi, rdi # clear ZF",
+                "std",
+                "repne scasw",
+                "cld",
+                "sete {eq}",
+                in("ax") value, inout("rcx") len => _, inout("rdi") last => p, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Byte) => {
+                let value: u8 = core::mem::transmute_copy(&value);
+                asm!(
+                "test rdi, rdi # clear ZF",
+                "std",
+                "repne scasb",
+                "cld",
+                "sete {eq}",
+                in("al") value, inout("rcx") len => _, inout("rdi") last => p, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            None => {
+                return core::slice::from_raw_parts(src, len)
+                    .iter()
+                    .rposition(|a| a.bitwise_eq(&value));
+            }
+        }
+        if (eq & 0b1) != 0 {
+            // `rdi` was decremented past the matching element
+            Some(p.wrapping_add(1).offset_from(src) as usize)
+        } else {
+            None
+        }
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        #[cfg(miri)]
+        preconditions::check_region("rep_scas_reverse", "src", src, len);
         core::slice::from_raw_parts(src, len)
             .iter()
-            .position(|a| a.bitwise_eq(&value))
+            .rposition(|a| a.bitwise_eq(&value))
+    }
+}
+
+/// Return the index of the first element in `src` that is not equal to `value`.
+///
+/// On x86_64 this implementation will use inline `repe scas` instructions, which makes it
+/// suitable for measuring the length of a run of equal elements.
+///
+/// On other architectures this will fall back to a word at a time scalar loop.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::read`] apply:
+///
+///  - `src` needs to be valid for the given `len`
+///  - pointers need to be properly aligned
+#[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
+pub unsafe fn repe_scas<T: RegisterType>(src: *const T, value: T, len: usize) -> Option<usize> {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use crate::AsmArm;
+        use core::arch::asm;
+
+        #[cfg(feature = "checked")]
+        preconditions::check_aligned("repe_scas", "src", src);
+
+        let mut eq: u8;
+        let mut p: *const T;
+        match T::ASM_ARM {
+            Some(AsmArm::Qword) => {
+                let value: u64 = core::mem::transmute_copy(&value);
+                asm!(
+                "test rcx, rcx",
+                "repe scasq",
+                "sete {eq}",
+                in("rax") value, inout("rcx") len => _, inout("rdi") src => p, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Dword) => {
+                let value: u32 = core::mem::transmute_copy(&value);
+                asm!(
+                "test rcx, rcx",
+                "repe scasd",
+                "sete {eq}",
+                in("eax") value, inout("rcx") len => _, inout("rdi") src => p, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Word) => {
+                let value: u16 = core::mem::transmute_copy(&value);
+                asm!(
+                "test rcx, rcx",
+                "repe scasw",
+                "sete {eq}",
+                in("ax") value, inout("rcx") len => _, inout("rdi") src => p, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            Some(AsmArm::Byte) => {
+                let value: u8 = core::mem::transmute_copy(&value);
+                asm!(
+                "test rcx, rcx",
+                "repe scasb",
+                "sete {eq}",
+                in("al") value, inout("rcx") len => _, inout("rdi") src => p, eq = lateout(reg_byte) eq,
+                options(nostack, readonly)
+                );
+            }
+            None => {
+                return core::slice::from_raw_parts(src, len)
+                    .iter()
+                    .position(|a| !a.bitwise_eq(&value));
+            }
+        }
+        if (eq & 0b1) == 0 {
+            Some(p.offset_from(src) as usize - 1)
+        } else {
+            None
+        }
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        #[cfg(miri)]
+        preconditions::check_region("repe_scas", "src", src, len);
+        #[cfg(miri)]
+        return core::slice::from_raw_parts(src, len)
+            .iter()
+            .position(|a| !a.bitwise_eq(&value));
+        #[cfg(not(miri))]
+        crate::swar::position_not(src, value, len)
     }
 }
 
+/// Load the `len` elements of `src` in order and return the last one, or `None` if `len` is
+/// zero, together with the source pointer advanced past the loaded elements.
+///
+/// On x86_64 this implementation will use inline `rep lods` instructions. Every iteration
+/// overwrites the accumulator, so this is mainly useful as a building block for hand-written
+/// scan loops which continue at the returned pointer.
+///
+/// On other architectures this will fall back to reading the last element.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::read`] apply:
+///
+///  - `src` needs to be valid for the given `len`
+///  - pointers need to be properly aligned
+#[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
+pub unsafe fn rep_lods<T: RegisterType>(src: *const T, len: usize) -> (Option<T>, *const T) {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use crate::AsmArm;
+        use core::arch::asm;
+
+        #[cfg(feature = "checked")]
+        preconditions::check_aligned("rep_lods", "src", src);
+
+        if len == 0 {
+            return (None, src);
+        }
+        let p: *const T;
+        let last: T = match T::ASM_ARM {
+            Some(AsmArm::Qword) => {
+                let value: u64;
+                asm!(
+                "rep lodsq",
+                out("rax") value, inout("rcx") len => _, inout("rsi") src => p,
+                options(nostack, readonly, preserves_flags)
+                );
+                core::mem::transmute_copy(&value)
+            }
+            Some(AsmArm::Dword) => {
+                let value: u32;
+                asm!(
+                "rep lodsd",
+                out("eax") value, inout("rcx") len => _, inout("rsi") src => p,
+                options(nostack, readonly, preserves_flags)
+                );
+                core::mem::transmute_copy(&value)
+            }
+            Some(AsmArm::Word) => {
+                let value: u16;
+                asm!(
+                "rep lodsw",
+                out("ax") value, inout("rcx") len => _, inout("rsi") src => p,
+                options(nostack, readonly, preserves_flags)
+                );
+                core::mem::transmute_copy(&value)
+            }
+            Some(AsmArm::Byte) => {
+                let value: u8;
+                asm!(
+                "rep lodsb",
+                out("al") value, inout("rcx") len => _, inout("rsi") src => p,
+                options(nostack, readonly, preserves_flags)
+                );
+                core::mem::transmute_copy(&value)
+            }
+            None => {
+                p = src.add(len);
+                p.sub(1).read()
+            }
+        };
+        (Some(last), p)
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        #[cfg(miri)]
+        preconditions::check_region("rep_lods", "src", src, len);
+        let last = len.checked_sub(1).map(|index| src.add(index).read());
+        (last, src.add(len))
+    }
+}
+
+/// Copy elements from `src` to `dst` up to and including the first occurrence of `stop`, like
+/// POSIX `memccpy`, and return the number of copied elements and whether `stop` was found.
+///
+/// If `stop` does not occur in the first `len` elements of `src`, all of them are copied. The
+/// stop element is located using [`rep_scas`] and the elements up to it are copied using
+/// [`rep_movs`].
+///
+/// # Safety
+///
+/// The same safety considerations as for [`rep_movs`] apply:
+///
+///  - `src` and `dst` need to be valid for the given `len`
+///  - `src` and `dst` memory regions must not overlap
+///  - pointers need to be properly aligned
+#[inline(always)]
+#[cfg_attr(any(miri, feature = "checked"), track_caller)]
+pub unsafe fn rep_copy_until<T: RegisterType>(
+    src: *const T,
+    dst: *mut T,
+    stop: T,
+    len: usize,
+) -> (usize, bool) {
+    let (count, found) = match rep_scas(src, stop, len) {
+        Some(index) => (index + 1, true),
+        None => (len, false),
+    };
+    rep_movs(src, dst, count);
+    (count, found)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -445,4 +1170,232 @@ mod tests {
             assert_eq!(rep_scas([1_f64, 2_f64, 3_f64].as_ptr(), 2_f64, 3), Some(1));
         }
     }
+
+    #[test]
+    fn test_wide_register_types() {
+        let a = [1_u128, 2, 3, 1 << 64];
+        let b = [1_u128, 2, 3, 0];
+        unsafe {
+            assert_eq!(rep_scas(a.as_ptr(), 1 << 64, 4), Some(3));
+            assert_eq!(rep_scas(a.as_ptr(), 4, 4), None);
+            assert_eq!(repe_scas(a.as_ptr(), 1, 4), Some(1));
+            assert_eq!(rep_cmps(a.as_ptr(), b.as_ptr(), 4), Some(3));
+            assert_eq!(repne_cmps(a.as_ptr(), b.as_ptr(), 4), Some(0));
+            assert_eq!(rep_cmps(a.as_ptr(), b.as_ptr(), 3), None);
+        }
+        assert_eq!(i128::ASM_ARM, None);
+        assert_eq!(u64::ASM_ARM.map(crate::AsmArm::width), Some(u64::WIDTH));
+        assert_eq!(f32::ASM_ARM.map(crate::AsmArm::width), Some(f32::WIDTH));
+        assert_eq!(i8::WIDTH, core::mem::size_of::<i8>());
+    }
+
+    #[test]
+    fn test_repne_cmps() {
+        unsafe {
+            assert_eq!(repne_cmps::<u8>([].as_ptr(), [].as_ptr(), 0), None);
+            assert_eq!(repne_cmps([1_u8].as_ptr(), [1].as_ptr(), 1), Some(0));
+            assert_eq!(
+                repne_cmps([1_u8, 2, 3].as_ptr(), [3, 2, 1].as_ptr(), 3),
+                Some(1)
+            );
+            assert_eq!(repne_cmps([1_u16, 2].as_ptr(), [3, 4].as_ptr(), 2), None);
+            assert_eq!(
+                repne_cmps([1_i32, 2, 3].as_ptr(), [0, 0, 3].as_ptr(), 3),
+                Some(2)
+            );
+            assert_eq!(
+                repne_cmps([1_u64, 2, 3].as_ptr(), [0, 0, 3].as_ptr(), 2),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_rep_scas_reverse() {
+        let a = [1_u8, 2, 3, 2, 1];
+        unsafe {
+            assert_eq!(rep_scas_reverse(a.as_ptr(), 2, 5), Some(3));
+            assert_eq!(rep_scas_reverse(a.as_ptr(), 1, 5), Some(4));
+            assert_eq!(rep_scas_reverse(a.as_ptr(), 1, 4), Some(0));
+            assert_eq!(rep_scas_reverse(a.as_ptr(), 3, 2), None);
+            assert_eq!(rep_scas_reverse(a.as_ptr(), 1, 0), None);
+        }
+        let words = [7_u16, 8, 7, 9];
+        let dwords = [7_u32, 8, 7, 9];
+        let qwords = [7_u64, 8, 7, 9];
+        let wide = [7_u128, 8, 7, 9];
+        unsafe {
+            assert_eq!(rep_scas_reverse(words.as_ptr(), 7, 4), Some(2));
+            assert_eq!(rep_scas_reverse(dwords.as_ptr(), 7, 4), Some(2));
+            assert_eq!(rep_scas_reverse(qwords.as_ptr(), 7, 4), Some(2));
+            assert_eq!(rep_scas_reverse(qwords.as_ptr(), 9, 3), None);
+            assert_eq!(rep_scas_reverse(wide.as_ptr(), 7, 4), Some(2));
+        }
+        // the direction flag is cleared again for following forward operations
+        let mut dst = [0_u8; 4];
+        unsafe { rep_movs(a.as_ptr(), dst.as_mut_ptr(), 4) };
+        assert_eq!(dst, [1, 2, 3, 2]);
+    }
+
+    #[test]
+    fn test_rep_lods() {
+        let bytes = [1_u8, 2, 3];
+        let words = [4_u16, 5];
+        let dwords = [-6_i32, 7, 8];
+        let qwords = [9.5_f64, -10.5];
+        let wide = [11_u128, 12];
+        unsafe {
+            assert_eq!(rep_lods(bytes.as_ptr(), 0), (None, bytes.as_ptr()));
+            assert_eq!(
+                rep_lods(bytes.as_ptr(), 3),
+                (Some(3), bytes.as_ptr().add(3))
+            );
+            assert_eq!(
+                rep_lods(bytes.as_ptr(), 1),
+                (Some(1), bytes.as_ptr().add(1))
+            );
+            assert_eq!(
+                rep_lods(words.as_ptr(), 2),
+                (Some(5), words.as_ptr().add(2))
+            );
+            assert_eq!(
+                rep_lods(dwords.as_ptr(), 3),
+                (Some(8), dwords.as_ptr().add(3))
+            );
+            assert_eq!(
+                rep_lods(qwords.as_ptr(), 2),
+                (Some(-10.5), qwords.as_ptr().add(2))
+            );
+            assert_eq!(rep_lods(wide.as_ptr(), 2), (Some(12), wide.as_ptr().add(2)));
+        }
+    }
+
+    #[test]
+    fn test_rep_copy_until() {
+        let src = *b"GET / HTTP/1.1\r\nHost";
+        let mut dst = [0_u8; 20];
+        unsafe {
+            assert_eq!(
+                rep_copy_until(src.as_ptr(), dst.as_mut_ptr(), b'\n', 20),
+                (16, true)
+            );
+            assert_eq!(&dst[..16], b"GET / HTTP/1.1\r\n");
+            assert_eq!(
+                rep_copy_until(src.as_ptr(), dst.as_mut_ptr(), b'\n', 5),
+                (5, false)
+            );
+            assert_eq!(
+                rep_copy_until(src.as_ptr(), dst.as_mut_ptr(), b'G', 20),
+                (1, true)
+            );
+            assert_eq!(
+                rep_copy_until(src.as_ptr(), dst.as_mut_ptr(), b'G', 0),
+                (0, false)
+            );
+        }
+        assert_eq!(dst[16..], [0; 4]);
+
+        let samples = [3_i32, -1, 4, 0, 5];
+        let mut out = [9_i32; 5];
+        unsafe {
+            assert_eq!(
+                rep_copy_until(samples.as_ptr(), out.as_mut_ptr(), 0, 5),
+                (4, true)
+            );
+        }
+        assert_eq!(out, [3, -1, 4, 0, 9]);
+    }
+
+    #[test]
+    fn test_rep_cmps_reverse() {
+        let a = [1_u8, 2, 3, 4, 5];
+        let b = [1_u8, 9, 3, 9, 5];
+        unsafe {
+            assert_eq!(rep_cmps_reverse(a.as_ptr(), b.as_ptr(), 5), Some(3));
+            assert_eq!(rep_cmps_reverse(a.as_ptr(), b.as_ptr(), 3), Some(1));
+            assert_eq!(rep_cmps_reverse(a.as_ptr(), b.as_ptr(), 1), None);
+            assert_eq!(rep_cmps_reverse(a.as_ptr(), b.as_ptr(), 0), None);
+            assert_eq!(rep_cmps_reverse(a.as_ptr(), a.as_ptr(), 5), None);
+        }
+        let words = ([7_u16, 8, 7, 9], [6_u16, 8, 7, 9]);
+        let dwords = ([7_u32, 8, 7, 9], [7_u32, 8, 0, 9]);
+        let qwords = ([7_u64, 8, 7, 9], [7_u64, 0, 7, 9]);
+        let wide = ([7_u128, 8, 7, 9], [7_u128, 8, 7, 0]);
+        unsafe {
+            assert_eq!(
+                rep_cmps_reverse(words.0.as_ptr(), words.1.as_ptr(), 4),
+                Some(0)
+            );
+            assert_eq!(
+                rep_cmps_reverse(dwords.0.as_ptr(), dwords.1.as_ptr(), 4),
+                Some(2)
+            );
+            assert_eq!(
+                rep_cmps_reverse(qwords.0.as_ptr(), qwords.1.as_ptr(), 4),
+                Some(1)
+            );
+            assert_eq!(
+                rep_cmps_reverse(wide.0.as_ptr(), wide.1.as_ptr(), 4),
+                Some(3)
+            );
+        }
+        // the direction flag is cleared again for following forward operations
+        unsafe { assert_eq!(rep_cmps(a.as_ptr(), b.as_ptr(), 5), Some(1)) };
+    }
+
+    #[test]
+    fn test_repe_scasb() {
+        unsafe {
+            assert_eq!(repe_scas([].as_ptr(), 1_u8, 0), None);
+            assert_eq!(repe_scas([1].as_ptr(), 1_u8, 1), None);
+            assert_eq!(repe_scas([1].as_ptr(), 2_u8, 1), Some(0));
+            assert_eq!(repe_scas([2, 2, 1].as_ptr(), 2_u8, 3), Some(2));
+            assert_eq!(repe_scas([2, 2, 2].as_ptr(), 2_u8, 3), None);
+        }
+    }
+
+    #[test]
+    fn test_repe_scasw() {
+        unsafe {
+            assert_eq!(repe_scas([].as_ptr(), 1_u16, 0), None);
+            assert_eq!(repe_scas([7, 7, 7, 8].as_ptr(), 7_u16, 4), Some(3));
+            assert_eq!(repe_scas([7, 7, 7, 8].as_ptr(), 7_u16, 3), None);
+        }
+    }
+
+    #[test]
+    fn test_repe_scasd() {
+        unsafe {
+            assert_eq!(repe_scas([5, 6].as_ptr(), 5_i32, 2), Some(1));
+            assert_eq!(repe_scas([5, 5].as_ptr(), 5_i32, 2), None);
+        }
+    }
+
+    #[test]
+    fn test_repe_scasq() {
+        unsafe {
+            assert_eq!(repe_scas([1.5, 1.5, 2.5].as_ptr(), 1.5_f64, 3), Some(2));
+            assert_eq!(repe_scas([u64::MAX; 4].as_ptr(), u64::MAX, 4), None);
+        }
+    }
+
+    #[test]
+    #[cfg(miri)]
+    #[should_panic(expected = "rep_movs: `src`")]
+    fn test_rep_movs_overlap() {
+        let mut buffer = [0_u8; 8];
+        unsafe {
+            rep_movs(buffer.as_ptr(), buffer.as_mut_ptr().add(2), 4);
+        }
+    }
+
+    #[test]
+    #[cfg(miri)]
+    #[should_panic(expected = "rep_stos: `dst` pointer")]
+    fn test_rep_stos_misaligned() {
+        let mut buffer = [0_u32; 4];
+        unsafe {
+            rep_stos(1_u32, buffer.as_mut_ptr().cast::<u8>().add(1).cast(), 2);
+        }
+    }
 }
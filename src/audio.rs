@@ -0,0 +1,157 @@
+//! Helpers for real-time audio buffers, available with the `audio` feature.
+
+use crate::{rep_movs, Error, RegisterType, SliceExt};
+
+/// Sample formats with a defined silence value.
+pub trait Sample: RegisterType {
+    /// Value representing silence, zero for signed and floating point samples and the midpoint
+    /// for unsigned samples.
+    const SILENCE: Self;
+}
+
+impl Sample for u8 {
+    const SILENCE: Self = 0x80;
+}
+impl Sample for i8 {
+    const SILENCE: Self = 0;
+}
+impl Sample for i16 {
+    const SILENCE: Self = 0;
+}
+impl Sample for i32 {
+    const SILENCE: Self = 0;
+}
+impl Sample for f32 {
+    const SILENCE: Self = 0.0;
+}
+impl Sample for f64 {
+    const SILENCE: Self = 0.0;
+}
+
+/// Fill `buffer` with silence using a `rep stos` instruction.
+#[inline]
+pub fn fill_silence<T: Sample>(buffer: &mut [T]) {
+    buffer.inline_fill(T::SILENCE)
+}
+
+/// Copy `len` samples from the ring buffer `src`, starting at `src_start`, into the ring buffer
+/// `dst`, starting at `dst_start`.
+///
+/// Both positions continue at the beginning of their buffer when reaching its end, so the copy
+/// is split into at most three `rep movs` instructions.
+///
+/// # Panics
+///
+/// Panics if `len` exceeds the length of either buffer, or if a start position is out of bounds.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_region_with_wrap<T: Copy>(
+    src: &[T],
+    src_start: usize,
+    dst: &mut [T],
+    dst_start: usize,
+    len: usize,
+) {
+    if try_copy_region_with_wrap(src, src_start, dst, dst_start, len).is_err() {
+        if len > src.len() || len > dst.len() {
+            panic!("length exceeds ring capacity")
+        }
+        panic!("start out of bounds")
+    }
+}
+
+/// Same as `copy_region_with_wrap`, but returns [`Error::LimitExceeded`] without modifying `dst`
+/// instead of panicking. The error contains `len` and the shorter buffer length if `len` exceeds
+/// the length of either buffer, or the start position and the length of its buffer if a start
+/// position is out of bounds.
+#[inline]
+pub fn try_copy_region_with_wrap<T: Copy>(
+    src: &[T],
+    src_start: usize,
+    dst: &mut [T],
+    dst_start: usize,
+    len: usize,
+) -> Result<(), Error> {
+    let capacity = src.len().min(dst.len());
+    if len > capacity {
+        return Err(Error::LimitExceeded {
+            len,
+            limit: capacity,
+        });
+    }
+    if len == 0 {
+        return Ok(());
+    }
+    for (start, limit) in [(src_start, src.len()), (dst_start, dst.len())] {
+        if start >= limit {
+            return Err(Error::LimitExceeded { len: start, limit });
+        }
+    }
+    let (mut src_pos, mut dst_pos, mut remaining) = (src_start, dst_start, len);
+    while remaining > 0 {
+        let n = remaining.min(src.len() - src_pos).min(dst.len() - dst_pos);
+        unsafe { rep_movs(src.as_ptr().add(src_pos), dst.as_mut_ptr().add(dst_pos), n) };
+        src_pos = (src_pos + n) % src.len();
+        dst_pos = (dst_pos + n) % dst.len();
+        remaining -= n;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_silence() {
+        let mut a = [1_u8; 4];
+        fill_silence(&mut a);
+        assert_eq!(a, [0x80; 4]);
+
+        let mut a = [1.5_f32; 4];
+        fill_silence(&mut a);
+        assert_eq!(a, [0.0; 4]);
+
+        let mut a = [-7_i16; 4];
+        fill_silence(&mut a);
+        assert_eq!(a, [0; 4]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_region_with_wrap() {
+        let src = [1_i16, 2, 3, 4, 5];
+        let mut dst = [0_i16; 4];
+        copy_region_with_wrap(&src, 3, &mut dst, 2, 4);
+        assert_eq!(dst, [1, 2, 4, 5]);
+
+        let mut dst = [0_i16; 6];
+        copy_region_with_wrap(&src, 0, &mut dst, 1, 5);
+        assert_eq!(dst, [0, 1, 2, 3, 4, 5]);
+        copy_region_with_wrap(&src, 0, &mut dst, 0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "length exceeds ring capacity")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_region_with_wrap_panic() {
+        copy_region_with_wrap(&[1_i16, 2, 3], 0, &mut [0; 2], 0, 3);
+    }
+
+    #[test]
+    fn test_try_copy_region_with_wrap() {
+        let src = [1_i16, 2, 3];
+        let mut dst = [0_i16; 2];
+        assert_eq!(
+            try_copy_region_with_wrap(&src, 0, &mut dst, 0, 3),
+            Err(Error::LimitExceeded { len: 3, limit: 2 })
+        );
+        assert_eq!(
+            try_copy_region_with_wrap(&src, 1, &mut dst, 2, 1),
+            Err(Error::LimitExceeded { len: 2, limit: 2 })
+        );
+        assert_eq!(dst, [0; 2]);
+        assert_eq!(try_copy_region_with_wrap(&src, 2, &mut dst, 1, 2), Ok(()));
+        assert_eq!(dst, [1, 3]);
+        assert_eq!(try_copy_region_with_wrap(&src, 9, &mut dst, 9, 0), Ok(()));
+    }
+}
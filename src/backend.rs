@@ -0,0 +1,735 @@
+//! Pluggable implementations of the byte oriented copy, fill, scan and compare operations.
+//!
+//! The functions in this module dispatch to the backend that was installed using [`register`],
+//! or to [`RepBackend`] if no backend was registered. Registering a custom backend allows
+//! substituting other kernels, for example for hardware accelerators, while keeping the safe
+//! API of this crate.
+//!
+//! How the backend is selected is controlled by a [`Config`], either the global one or one that
+//! is passed explicitly to the `*_with_config` functions.
+
+use crate::nontemporal::{copy_nontemporal_unfenced, store_fence};
+use crate::{rep_cmps, rep_movs, rep_scas, rep_stos, Config, CopyWidth, Error, LARGE_MIN};
+use std::sync::OnceLock;
+
+/// The operations implemented by a [`Backend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    Copy,
+    Fill,
+    Position,
+    Mismatch,
+}
+
+/// Implementation of the basic byte oriented operations.
+///
+/// The methods follow the same contracts as the corresponding functions in the crate root,
+/// so that for example [`Backend::copy`] can be substituted for [`rep_movs`].
+pub trait Backend: Sync {
+    /// Name of this backend, used for diagnostics.
+    fn name(&self) -> &'static str;
+
+    /// The instruction used to implement `op`, if this backend uses a single instruction form.
+    fn instruction(&self, _op: Op) -> Option<&'static str> {
+        None
+    }
+
+    /// Whether typed copies with [`CopyWidth::Element`] may use the `rep movs` form matching the
+    /// element size instead of [`Backend::copy`]. Other backends always receive byte copies.
+    fn element_copies(&self) -> bool {
+        false
+    }
+
+    /// Copy `len` bytes from `src` to `dst`.
+    ///
+    /// # Safety
+    ///
+    /// The same safety considerations as for [`rep_movs`] apply.
+    unsafe fn copy(&self, src: *const u8, dst: *mut u8, len: usize);
+
+    /// Store `value` into `len` bytes starting at `dst`.
+    ///
+    /// # Safety
+    ///
+    /// The same safety considerations as for [`rep_stos`] apply.
+    unsafe fn fill(&self, value: u8, dst: *mut u8, len: usize);
+
+    /// Return the index of the first occurrence of `value` in the `len` bytes at `src`.
+    ///
+    /// # Safety
+    ///
+    /// The same safety considerations as for [`rep_scas`] apply.
+    unsafe fn position(&self, src: *const u8, value: u8, len: usize) -> Option<usize>;
+
+    /// Return the index of the first mismatching byte between `a` and `b`.
+    ///
+    /// # Safety
+    ///
+    /// The same safety considerations as for [`rep_cmps`] apply.
+    unsafe fn mismatch(&self, a: *const u8, b: *const u8, len: usize) -> Option<usize>;
+}
+
+/// Backend using the inline `rep movs/stos/scas/cmps` instructions.
+///
+/// This is the default backend if no other backend was registered.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RepBackend;
+
+impl Backend for RepBackend {
+    fn name(&self) -> &'static str {
+        "rep"
+    }
+
+    fn instruction(&self, op: Op) -> Option<&'static str> {
+        Some(match op {
+            Op::Copy => "rep movsb",
+            Op::Fill => "rep stosb",
+            Op::Position => "repne scasb",
+            Op::Mismatch => "repe cmpsb",
+        })
+    }
+
+    fn element_copies(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    unsafe fn copy(&self, src: *const u8, dst: *mut u8, len: usize) {
+        rep_movs(src, dst, len)
+    }
+
+    #[inline]
+    unsafe fn fill(&self, value: u8, dst: *mut u8, len: usize) {
+        rep_stos(value, dst, len)
+    }
+
+    #[inline]
+    unsafe fn position(&self, src: *const u8, value: u8, len: usize) -> Option<usize> {
+        rep_scas(src, value, len)
+    }
+
+    #[inline]
+    unsafe fn mismatch(&self, a: *const u8, b: *const u8, len: usize) -> Option<usize> {
+        rep_cmps(a, b, len)
+    }
+}
+
+/// Portable backend using the implementations of the standard library.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScalarBackend;
+
+impl Backend for ScalarBackend {
+    fn name(&self) -> &'static str {
+        "scalar"
+    }
+
+    #[inline]
+    unsafe fn copy(&self, src: *const u8, dst: *mut u8, len: usize) {
+        core::ptr::copy_nonoverlapping(src, dst, len)
+    }
+
+    #[inline]
+    unsafe fn fill(&self, value: u8, dst: *mut u8, len: usize) {
+        core::ptr::write_bytes(dst, value, len)
+    }
+
+    #[inline]
+    unsafe fn position(&self, src: *const u8, value: u8, len: usize) -> Option<usize> {
+        core::slice::from_raw_parts(src, len)
+            .iter()
+            .position(|a| *a == value)
+    }
+
+    #[inline]
+    unsafe fn mismatch(&self, a: *const u8, b: *const u8, len: usize) -> Option<usize> {
+        core::slice::from_raw_parts(a, len)
+            .iter()
+            .zip(core::slice::from_raw_parts(b, len))
+            .position(|(a, b)| a != b)
+    }
+}
+
+static BACKEND: OnceLock<&'static dyn Backend> = OnceLock::new();
+
+/// Install `backend` as the implementation used by the functions in this module.
+///
+/// A backend can only be registered once, usually during startup of the application.
+/// If a backend was already registered, it is returned as the error value.
+pub fn register(backend: &'static dyn Backend) -> Result<(), &'static dyn Backend> {
+    let mut installed = false;
+    let current = BACKEND.get_or_init(|| {
+        installed = true;
+        backend
+    });
+    if installed {
+        Ok(())
+    } else {
+        Err(*current)
+    }
+}
+
+/// Return the currently registered backend, or [`RepBackend`] if none was registered.
+#[inline]
+pub fn current() -> &'static dyn Backend {
+    match BACKEND.get() {
+        Some(backend) => *backend,
+        None => &RepBackend,
+    }
+}
+
+/// Select the backend for `op` on `len` bytes.
+#[inline]
+fn select(config: &Config, op: Op, len: usize) -> &'static dyn Backend {
+    let threshold = match op {
+        Op::Copy => config.thresholds.copy,
+        Op::Fill => config.thresholds.fill,
+        Op::Position => config.thresholds.position,
+        Op::Mismatch => config.thresholds.mismatch,
+    };
+    if len < threshold {
+        &ScalarBackend
+    } else {
+        config.force_backend.unwrap_or_else(current)
+    }
+}
+
+/// Whether a copy of `len` bytes uses non-temporal stores instead of the selected backend.
+#[inline]
+fn use_nt_stores(config: &Config, len: usize) -> bool {
+    config.allow_nt_stores && len >= LARGE_MIN
+}
+
+/// Copy `src` into `dst` using non-temporal stores in chunks of `chunk_size` bytes, followed by a
+/// single `sfence`.
+fn copy_nt_chunks(src: &[u8], dst: &mut [u8], chunk_size: usize) {
+    for (src, dst) in src.chunks(chunk_size).zip(dst.chunks_mut(chunk_size)) {
+        unsafe { copy_nontemporal_unfenced(src.as_ptr(), dst.as_mut_ptr(), dst.len()) }
+    }
+    store_fence();
+}
+
+/// Description of how an operation would be executed, as returned by [`explain`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    /// Name of the selected backend.
+    pub backend: &'static str,
+    /// Instruction used by the selected backend, if it uses a single instruction form.
+    pub instruction: Option<&'static str>,
+    /// Number of backend calls the operation is split into.
+    pub chunks: usize,
+}
+
+/// Describe how `op` on `len` bytes would be executed using the global configuration.
+///
+/// `elem_size` is the size in bytes of the elements of a typed copy like [`try_copy_slice`],
+/// and 1 for the byte oriented operations. The operation itself is not executed. The decision
+/// only depends on the lengths, not on the alignment of the involved buffers.
+pub fn explain(op: Op, len: usize, elem_size: usize) -> Decision {
+    explain_with_config(op, len, elem_size, Config::global())
+}
+
+/// Describe how `op` on `len` bytes of elements of `elem_size` bytes would be executed using the
+/// given configuration.
+///
+/// Typed copies with [`CopyWidth::Element`] report the `rep movs` form matching `elem_size`, for
+/// example `rep movsq` for `u64` elements, if the selected backend supports [element
+/// copies](Backend::element_copies).
+pub fn explain_with_config(op: Op, len: usize, elem_size: usize, config: &Config) -> Decision {
+    let chunks = len.div_ceil(config.chunk_size.max(1));
+    if op == Op::Copy && use_nt_stores(config, len) {
+        return Decision {
+            backend: "nontemporal",
+            instruction: Some("movnti"),
+            chunks,
+        };
+    }
+    let backend = select(config, op, len);
+    if op == Op::Copy
+        && elem_size > 1
+        && config.copy_width == CopyWidth::Element
+        && backend.element_copies()
+    {
+        let chunk_size = (config.chunk_size / elem_size).max(1);
+        return Decision {
+            backend: backend.name(),
+            instruction: Some(match elem_size {
+                8 => "rep movsq",
+                4 => "rep movsd",
+                2 => "rep movsw",
+                _ => "rep movsb",
+            }),
+            chunks: (len / elem_size).div_ceil(chunk_size),
+        };
+    }
+    Decision {
+        backend: backend.name(),
+        instruction: backend.instruction(op),
+        chunks,
+    }
+}
+
+/// Return [`Error::LenMismatch`] if the lengths of the two operands differ.
+#[inline]
+fn check_len(src_len: usize, dst_len: usize) -> Result<(), Error> {
+    if src_len != dst_len {
+        return Err(Error::LenMismatch { src_len, dst_len });
+    }
+    Ok(())
+}
+
+/// Copy all bytes from `src` into `dst` using the global configuration.
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy(src: &[u8], dst: &mut [u8]) {
+    copy_with_config(src, dst, Config::global())
+}
+
+/// Same as `copy`, but returns [`Error::LenMismatch`] instead of panicking if `src` and `dst`
+/// have different lengths.
+pub fn try_copy(src: &[u8], dst: &mut [u8]) -> Result<(), Error> {
+    try_copy_with_config(src, dst, Config::global())
+}
+
+/// Copy all bytes from `src` into `dst` using the given configuration.
+///
+/// If [`Config::allow_nt_stores`] is set, copies of at least [`LARGE_MIN`] bytes are written
+/// using non-temporal stores instead of the selected backend, see
+/// [`copy_nontemporal`](crate::copy_nontemporal).
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_with_config(src: &[u8], dst: &mut [u8], config: &Config) {
+    if try_copy_with_config(src, dst, config).is_err() {
+        panic!("length mismatch");
+    }
+}
+
+/// Same as `copy_with_config`, but returns [`Error::LenMismatch`] instead of panicking if
+/// `src` and `dst` have different lengths.
+pub fn try_copy_with_config(src: &[u8], dst: &mut [u8], config: &Config) -> Result<(), Error> {
+    check_len(src.len(), dst.len())?;
+    let chunk_size = config.chunk_size.max(1);
+    if use_nt_stores(config, dst.len()) {
+        copy_nt_chunks(src, dst, chunk_size);
+        return Ok(());
+    }
+    let backend = select(config, Op::Copy, dst.len());
+    for (src, dst) in src.chunks(chunk_size).zip(dst.chunks_mut(chunk_size)) {
+        unsafe { backend.copy(src.as_ptr(), dst.as_mut_ptr(), dst.len()) }
+    }
+    Ok(())
+}
+
+/// Copy all elements from `src` into `dst` using the global configuration.
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_slice<T: Copy>(src: &[T], dst: &mut [T]) {
+    copy_slice_with_config(src, dst, Config::global())
+}
+
+/// Same as `copy_slice`, but returns [`Error::LenMismatch`] instead of panicking if `src` and
+/// `dst` have different lengths.
+pub fn try_copy_slice<T: Copy>(src: &[T], dst: &mut [T]) -> Result<(), Error> {
+    try_copy_slice_with_config(src, dst, Config::global())
+}
+
+/// Copy all elements from `src` into `dst` using the given configuration.
+///
+/// The backend is selected for the length of the slices in bytes, like in [`copy_with_config`].
+/// With [`CopyWidth::Element`] and a backend that supports [element
+/// copies](Backend::element_copies) the `rep movs` instruction matching the size of `T` is used,
+/// all other combinations copy the bytes of the slices like [`copy_with_config`], including the
+/// non-temporal stores for large copies if [allowed](Config::allow_nt_stores). The chunk size of
+/// the configuration applies in all cases.
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_slice_with_config<T: Copy>(src: &[T], dst: &mut [T], config: &Config) {
+    if try_copy_slice_with_config(src, dst, config).is_err() {
+        panic!("length mismatch");
+    }
+}
+
+/// Same as `copy_slice_with_config`, but returns [`Error::LenMismatch`] instead of panicking
+/// if `src` and `dst` have different lengths.
+pub fn try_copy_slice_with_config<T: Copy>(
+    src: &[T],
+    dst: &mut [T],
+    config: &Config,
+) -> Result<(), Error> {
+    check_len(src.len(), dst.len())?;
+    let backend = select(config, Op::Copy, core::mem::size_of_val(dst));
+    if config.copy_width == CopyWidth::Element
+        && backend.element_copies()
+        && !use_nt_stores(config, core::mem::size_of_val(dst))
+    {
+        let chunk_size = (config.chunk_size / core::mem::size_of::<T>().max(1)).max(1);
+        for (src, dst) in src.chunks(chunk_size).zip(dst.chunks_mut(chunk_size)) {
+            unsafe { rep_movs(src.as_ptr(), dst.as_mut_ptr(), dst.len()) }
+        }
+    } else {
+        let (src, dst) = unsafe {
+            (
+                core::slice::from_raw_parts(src.as_ptr().cast::<u8>(), core::mem::size_of_val(src)),
+                core::slice::from_raw_parts_mut(
+                    dst.as_mut_ptr().cast::<u8>(),
+                    core::mem::size_of_val(dst),
+                ),
+            )
+        };
+        try_copy_with_config(src, dst, config)?;
+    }
+    Ok(())
+}
+
+/// Store `value` into all bytes of `dst` using the global configuration.
+pub fn fill(dst: &mut [u8], value: u8) {
+    fill_with_config(dst, value, Config::global())
+}
+
+/// Store `value` into all bytes of `dst` using the given configuration.
+pub fn fill_with_config(dst: &mut [u8], value: u8, config: &Config) {
+    let backend = select(config, Op::Fill, dst.len());
+    for dst in dst.chunks_mut(config.chunk_size.max(1)) {
+        unsafe { backend.fill(value, dst.as_mut_ptr(), dst.len()) }
+    }
+}
+
+/// Return the index of the first occurrence of `value` in `src` using the global configuration.
+pub fn position(src: &[u8], value: u8) -> Option<usize> {
+    position_with_config(src, value, Config::global())
+}
+
+/// Return the index of the first occurrence of `value` in `src` using the given configuration.
+pub fn position_with_config(src: &[u8], value: u8, config: &Config) -> Option<usize> {
+    let backend = select(config, Op::Position, src.len());
+    let chunk_size = config.chunk_size.max(1);
+    src.chunks(chunk_size).enumerate().find_map(|(i, src)| {
+        let position = unsafe { backend.position(src.as_ptr(), value, src.len()) };
+        position.map(|index| i * chunk_size + index)
+    })
+}
+
+/// Return the index of the first mismatching byte between `a` and `b` using the global
+/// configuration.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+pub fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    mismatch_with_config(a, b, Config::global())
+}
+
+/// Same as `mismatch`, but returns [`Error::LenMismatch`] instead of panicking if `a` and `b`
+/// have different lengths.
+pub fn try_mismatch(a: &[u8], b: &[u8]) -> Result<Option<usize>, Error> {
+    try_mismatch_with_config(a, b, Config::global())
+}
+
+/// Return the index of the first mismatching byte between `a` and `b` using the given
+/// configuration.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+pub fn mismatch_with_config(a: &[u8], b: &[u8], config: &Config) -> Option<usize> {
+    match try_mismatch_with_config(a, b, config) {
+        Ok(index) => index,
+        Err(_) => panic!("length mismatch"),
+    }
+}
+
+/// Same as `mismatch_with_config`, but returns [`Error::LenMismatch`] instead of panicking if
+/// `a` and `b` have different lengths.
+pub fn try_mismatch_with_config(
+    a: &[u8],
+    b: &[u8],
+    config: &Config,
+) -> Result<Option<usize>, Error> {
+    check_len(a.len(), b.len())?;
+    let backend = select(config, Op::Mismatch, a.len());
+    let chunk_size = config.chunk_size.max(1);
+    Ok(a.chunks(chunk_size)
+        .zip(b.chunks(chunk_size))
+        .enumerate()
+        .find_map(|(i, (a, b))| {
+            let position = unsafe { backend.mismatch(a.as_ptr(), b.as_ptr(), a.len()) };
+            position.map(|index| i * chunk_size + index)
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Thresholds;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingBackend(AtomicUsize);
+
+    impl Backend for CountingBackend {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        unsafe fn copy(&self, src: *const u8, dst: *mut u8, len: usize) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            RepBackend.copy(src, dst, len)
+        }
+
+        unsafe fn fill(&self, value: u8, dst: *mut u8, len: usize) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            RepBackend.fill(value, dst, len)
+        }
+
+        unsafe fn position(&self, src: *const u8, value: u8, len: usize) -> Option<usize> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            RepBackend.position(src, value, len)
+        }
+
+        unsafe fn mismatch(&self, a: *const u8, b: *const u8, len: usize) -> Option<usize> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            RepBackend.mismatch(a, b, len)
+        }
+    }
+
+    static FORCED: CountingBackend = CountingBackend(AtomicUsize::new(0));
+
+    fn check_backend(backend: &dyn Backend) {
+        let mut buffer = [0_u8; 7];
+        unsafe {
+            backend.fill(42, buffer.as_mut_ptr(), 5);
+            assert_eq!(buffer, [42, 42, 42, 42, 42, 0, 0]);
+            backend.copy([1, 2, 3].as_ptr(), buffer.as_mut_ptr().add(4), 3);
+            assert_eq!(buffer, [42, 42, 42, 42, 1, 2, 3]);
+            assert_eq!(backend.position(buffer.as_ptr(), 2, 7), Some(5));
+            assert_eq!(backend.position(buffer.as_ptr(), 2, 5), None);
+            assert_eq!(
+                backend.mismatch(buffer.as_ptr(), [42; 7].as_ptr(), 7),
+                Some(4)
+            );
+            assert_eq!(backend.mismatch(buffer.as_ptr(), [42; 4].as_ptr(), 4), None);
+        }
+    }
+
+    #[test]
+    fn test_builtin_backends() {
+        check_backend(&RepBackend);
+        check_backend(&ScalarBackend);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_panic() {
+        copy(&[1, 2, 3], &mut [0; 2]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_panicking() {
+        let config = Config::new();
+        let mut dst = [0_u16; 3];
+        copy_slice(&[1, 2, 3], &mut dst);
+        copy_slice_with_config(&[4, 5], &mut dst[1..], &config);
+        assert_eq!(dst, [1, 4, 5]);
+        let mut dst = [0_u8; 3];
+        copy(&[1, 2, 3], &mut dst);
+        copy_with_config(&[4], &mut dst[..1], &config);
+        assert_eq!(mismatch(&dst, &[4, 2, 3]), None);
+        assert_eq!(mismatch_with_config(&dst, &[4, 2, 4], &config), Some(2));
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let error = Err(Error::LenMismatch {
+            src_len: 3,
+            dst_len: 2,
+        });
+        assert_eq!(try_copy(&[1, 2, 3], &mut [0; 2]), error);
+        assert_eq!(try_copy_slice(&[1_u32, 2, 3], &mut [0; 2]), error);
+        assert_eq!(try_mismatch(&[1, 2, 3], &[0; 2]), error.map(|()| None));
+    }
+
+    #[test]
+    fn test_with_config() {
+        let config = Config {
+            thresholds: Thresholds {
+                copy: 4,
+                fill: 4,
+                position: 4,
+                mismatch: 4,
+            },
+            allow_nt_stores: false,
+            force_backend: Some(&FORCED),
+            chunk_size: 3,
+            copy_width: CopyWidth::Element,
+            scratch_limit: 0,
+        };
+        let mut dst = [0_u8; 3];
+        try_copy_with_config(&[1, 2, 3], &mut dst, &config).unwrap();
+        fill_with_config(&mut dst[..1], 5, &config);
+        assert_eq!(position_with_config(&dst, 3, &config), Some(2));
+        assert_eq!(
+            try_mismatch_with_config(&dst, &[5, 2, 4], &config),
+            Ok(Some(2))
+        );
+        assert_eq!(FORCED.0.load(Ordering::Relaxed), 0);
+
+        let mut dst = [0_u8; 8];
+        try_copy_with_config(&[1, 2, 3, 4, 5, 6, 7, 8], &mut dst, &config).unwrap();
+        assert_eq!(dst, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(FORCED.0.load(Ordering::Relaxed), 3);
+        fill_with_config(&mut dst[1..6], 0, &config);
+        assert_eq!(dst, [1, 0, 0, 0, 0, 0, 7, 8]);
+        assert_eq!(FORCED.0.load(Ordering::Relaxed), 5);
+        assert_eq!(position_with_config(&dst, 7, &config), Some(6));
+        assert_eq!(position_with_config(&dst, 9, &config), None);
+        assert_eq!(
+            try_mismatch_with_config(&dst, &[1, 0, 0, 0, 0, 0, 7, 9], &config),
+            Ok(Some(7))
+        );
+        assert_eq!(try_mismatch_with_config(&dst, &dst, &config), Ok(None));
+    }
+
+    #[test]
+    fn test_copy_slice() {
+        static BYTES: CountingBackend = CountingBackend(AtomicUsize::new(0));
+        let src = (0..100_u64).map(|i| i << 40 | i).collect::<Vec<_>>();
+        for copy_width in [CopyWidth::Element, CopyWidth::Bytes] {
+            let config = Config {
+                force_backend: Some(&BYTES),
+                ..Config::new()
+                    .with_copy_width(copy_width)
+                    .with_max_burst_bytes(64)
+            };
+            let mut dst = vec![0_u64; 100];
+            try_copy_slice_with_config(&src, &mut dst, &config).unwrap();
+            assert_eq!(dst, src);
+            let mut dst = [0_u16; 3];
+            try_copy_slice_with_config(&[1, 2, 3], &mut dst, &config).unwrap();
+            assert_eq!(dst, [1, 2, 3]);
+            try_copy_slice_with_config(&[(); 3], &mut [(); 3], &config).unwrap();
+        }
+        // 800 bytes in chunks of 64 and 6 bytes in one chunk for each width, since the forced
+        // backend does not support element copies
+        assert_eq!(BYTES.0.load(Ordering::Relaxed), 28);
+
+        let config = Config {
+            force_backend: Some(&RepBackend),
+            ..Config::new().with_copy_width(CopyWidth::Element)
+        };
+        let mut dst = vec![0_u64; 100];
+        try_copy_slice_with_config(&src, &mut dst, &config).unwrap();
+        assert_eq!(dst, src);
+
+        let mut dst = [0_u32; 2];
+        try_copy_slice(&[5, 6], &mut dst).unwrap();
+        assert_eq!(dst, [5, 6]);
+    }
+
+    #[test]
+    fn test_nt_stores() {
+        static NT: CountingBackend = CountingBackend(AtomicUsize::new(0));
+        let config = Config {
+            force_backend: Some(&NT),
+            ..Config::new()
+                .with_nt_stores(true)
+                .with_max_burst_bytes(1000)
+        };
+        let src = (0..LARGE_MIN + 3).map(|i| i as u8).collect::<Vec<_>>();
+        let mut dst = vec![0_u8; src.len()];
+        try_copy_with_config(&src, &mut dst, &config).unwrap();
+        assert_eq!(dst, src);
+        let mut dst = vec![0_u8; src.len() - 1];
+        try_copy_slice_with_config(&src[1..], &mut dst, &config).unwrap();
+        assert_eq!(dst, src[1..]);
+        assert_eq!(NT.0.load(Ordering::Relaxed), 0);
+
+        let mut dst = [0_u8; 3];
+        try_copy_with_config(&[1, 2, 3], &mut dst, &config).unwrap();
+        assert_eq!(dst, [1, 2, 3]);
+        assert_eq!(NT.0.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_slice_panic() {
+        copy_slice(&[1_u32, 2, 3], &mut [0; 2]);
+    }
+
+    #[test]
+    fn test_explain() {
+        let config = Config {
+            thresholds: Thresholds {
+                copy: 16,
+                ..Thresholds::NONE
+            },
+            allow_nt_stores: false,
+            force_backend: Some(&RepBackend),
+            chunk_size: 4096,
+            copy_width: CopyWidth::Element,
+            scratch_limit: 0,
+        };
+        let decision = explain_with_config(Op::Copy, 8, 1, &config);
+        assert_eq!(decision.backend, "scalar");
+        assert_eq!(decision.instruction, None);
+        assert_eq!(decision.chunks, 1);
+
+        let decision = explain_with_config(Op::Copy, 10000, 1, &config);
+        assert_eq!(decision.backend, "rep");
+        assert_eq!(decision.instruction, Some("rep movsb"));
+        assert_eq!(decision.chunks, 3);
+
+        let decision = explain_with_config(Op::Copy, 10000, 8, &config);
+        assert_eq!(decision.instruction, Some("rep movsq"));
+        assert_eq!(decision.chunks, 3);
+        assert_eq!(
+            explain_with_config(Op::Copy, 10000, 2, &config).instruction,
+            Some("rep movsw")
+        );
+        let bytes = config.with_copy_width(CopyWidth::Bytes);
+        let decision = explain_with_config(Op::Copy, 10000, 8, &bytes);
+        assert_eq!(decision.instruction, Some("rep movsb"));
+        assert_eq!(
+            explain_with_config(Op::Copy, 8, 8, &config).backend,
+            "scalar"
+        );
+        assert_eq!(
+            explain_with_config(Op::Fill, 10000, 8, &config).instruction,
+            Some("rep stosb")
+        );
+
+        let decision = explain_with_config(Op::Position, 0, 1, &config);
+        assert_eq!(decision.instruction, Some("repne scasb"));
+        assert_eq!(decision.chunks, 0);
+
+        let bounded = Config::new().with_max_burst_bytes(1 << 16);
+        assert_eq!(
+            explain_with_config(Op::Fill, 1 << 20, 1, &bounded).chunks,
+            16
+        );
+
+        let nt = config.with_nt_stores(true);
+        let decision = explain_with_config(Op::Copy, LARGE_MIN, 8, &nt);
+        assert_eq!(decision.backend, "nontemporal");
+        assert_eq!(decision.instruction, Some("movnti"));
+        assert_eq!(explain_with_config(Op::Copy, 10000, 1, &nt).backend, "rep");
+        assert_eq!(
+            explain_with_config(Op::Fill, LARGE_MIN, 1, &nt).backend,
+            "rep"
+        );
+    }
+}
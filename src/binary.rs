@@ -0,0 +1,124 @@
+//! Search for fixed width integers at arbitrary byte offsets, for example magic numbers and
+//! frame markers in binary formats.
+//!
+//! Every byte offset is checked with a single unaligned load of the integer width, which is
+//! compared against the encoded value, instead of comparing byte by byte.
+
+/// Unsigned integer that can be loaded from an unaligned address.
+trait Word: Copy + Eq {
+    const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// Load a word in native byte order.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` needs to be valid for reading `Self::SIZE` bytes.
+    unsafe fn load(ptr: *const u8) -> Self;
+}
+
+macro_rules! impl_word {
+    ($($ty:ty),*) => {
+        $(impl Word for $ty {
+            #[inline(always)]
+            unsafe fn load(ptr: *const u8) -> Self {
+                ptr.cast::<$ty>().read_unaligned()
+            }
+        })*
+    };
+}
+
+impl_word!(u16, u32, u64);
+
+/// Return the first byte offset of `haystack` at which a native endian load equals `native`.
+#[inline]
+fn find_word<W: Word>(haystack: &[u8], native: W) -> Option<usize> {
+    let offsets = haystack.len().checked_sub(W::SIZE)? + 1;
+    let ptr = haystack.as_ptr();
+    (0..offsets).find(|&offset| unsafe { W::load(ptr.add(offset)) } == native)
+}
+
+/// Return the byte offset of the first occurrence of `value` encoded as little endian.
+pub fn find_u16_le(haystack: &[u8], value: u16) -> Option<usize> {
+    find_word(haystack, value.to_le())
+}
+
+/// Return the byte offset of the first occurrence of `value` encoded as big endian.
+pub fn find_u16_be(haystack: &[u8], value: u16) -> Option<usize> {
+    find_word(haystack, value.to_be())
+}
+
+/// Return the byte offset of the first occurrence of `value` encoded as little endian.
+pub fn find_u32_le(haystack: &[u8], value: u32) -> Option<usize> {
+    find_word(haystack, value.to_le())
+}
+
+/// Return the byte offset of the first occurrence of `value` encoded as big endian.
+pub fn find_u32_be(haystack: &[u8], value: u32) -> Option<usize> {
+    find_word(haystack, value.to_be())
+}
+
+/// Return the byte offset of the first occurrence of `value` encoded as little endian.
+pub fn find_u64_le(haystack: &[u8], value: u64) -> Option<usize> {
+    find_word(haystack, value.to_le())
+}
+
+/// Return the byte offset of the first occurrence of `value` encoded as big endian.
+pub fn find_u64_be(haystack: &[u8], value: u64) -> Option<usize> {
+    find_word(haystack, value.to_be())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_u16() {
+        let data = [0x00, 0xFF, 0xD8, 0xFF, 0xE0];
+        assert_eq!(find_u16_be(&data, 0xFFD8), Some(1));
+        assert_eq!(find_u16_le(&data, 0xD8FF), Some(1));
+        assert_eq!(find_u16_be(&data, 0xFFE0), Some(3));
+        assert_eq!(find_u16_be(&data, 0xE0FF), None);
+        assert_eq!(find_u16_le(&[0x34], 0x1234), None);
+    }
+
+    #[test]
+    fn test_find_u32() {
+        let data = b"xx\x89PNG\r\n";
+        assert_eq!(find_u32_be(data, 0x89504E47), Some(2));
+        assert_eq!(find_u32_le(data, 0x474E5089), Some(2));
+        assert_eq!(find_u32_le(data, 0x89504E47), None);
+    }
+
+    #[test]
+    fn test_find_u64() {
+        let mut data = [0_u8; 19];
+        data[3..11].copy_from_slice(&0x0102030405060708_u64.to_le_bytes());
+        assert_eq!(find_u64_le(&data, 0x0102030405060708), Some(3));
+        assert_eq!(find_u64_be(&data, 0x0807060504030201), Some(3));
+        assert_eq!(find_u64_be(&data, 0x0102030405060708), None);
+    }
+
+    #[test]
+    fn test_find_all_offsets() {
+        let data = (0..40_u8).map(|i| i.wrapping_mul(37)).collect::<Vec<_>>();
+        for start in 0..data.len() {
+            for end in start..=data.len() {
+                let haystack = &data[start..end];
+                let naive = |needle: &[u8]| {
+                    haystack
+                        .windows(needle.len())
+                        .position(|window| window == needle)
+                };
+                for offset in 0..data.len() - 8 {
+                    let bytes = &data[offset..offset + 8];
+                    let value = u64::from_le_bytes(bytes.try_into().unwrap());
+                    assert_eq!(find_u64_le(haystack, value), naive(bytes));
+                    let value = u32::from_be_bytes(bytes[..4].try_into().unwrap());
+                    assert_eq!(find_u32_be(haystack, value), naive(&bytes[..4]));
+                    let value = u16::from_le_bytes(bytes[..2].try_into().unwrap());
+                    assert_eq!(find_u16_le(haystack, value), naive(&bytes[..2]));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{Error, RegisterType, SliceExt};
+
+/// Return the index of the first bit in `from..end` that equals `set`, or `end` if there is none.
+///
+/// Bits are numbered in least significant bit first order, so bit `i` is stored in
+/// `bitmap[i / 8] & (1 << (i % 8))`.
+#[inline]
+fn scan_bits(bitmap: &[u8], from: usize, end: usize, set: bool) -> usize {
+    let skip = if set { 0x00 } else { 0xFF };
+    let mut i = from;
+    while i < end {
+        let byte = bitmap[i / 8];
+        if byte == skip {
+            i = (i / 8 + 1) * 8;
+            continue;
+        }
+        let bits = if set { byte } else { !byte } >> (i % 8);
+        if bits != 0 {
+            return (i + bits.trailing_zeros() as usize).min(end);
+        }
+        i = (i / 8 + 1) * 8;
+    }
+    end
+}
+
+/// Store `value` into every element of `dst` whose corresponding bit in `bitmap` is set.
+///
+/// Bits are numbered in least significant bit first order, the same layout as used by
+/// Apache Arrow validity bitmaps. Each run of consecutive set bits is filled using a single
+/// `rep stos` instruction.
+///
+/// # Panics
+///
+/// Panics if `bitmap` contains less than `dst.len()` bits.
+#[cfg(not(feature = "no-panic"))]
+pub fn fill_where<T: RegisterType>(dst: &mut [T], bitmap: &[u8], value: T) {
+    if try_fill_where(dst, bitmap, value).is_err() {
+        panic!("bitmap too short")
+    }
+}
+
+/// Same as `fill_where`, but returns [`Error::LimitExceeded`] with the required and the actual
+/// length of `bitmap` in bytes without modifying `dst` instead of panicking if `bitmap` contains
+/// less than `dst.len()` bits.
+#[inline]
+pub fn try_fill_where<T: RegisterType>(
+    dst: &mut [T],
+    bitmap: &[u8],
+    value: T,
+) -> Result<(), Error> {
+    let len = dst.len();
+    if bitmap.len() < len.div_ceil(8) {
+        return Err(Error::LimitExceeded {
+            len: len.div_ceil(8),
+            limit: bitmap.len(),
+        });
+    }
+    let mut i = 0;
+    while i < len {
+        let start = scan_bits(bitmap, i, len, true);
+        let end = scan_bits(bitmap, start, len, false);
+        // `scan_bits` returns positions in `i..=len`, so the run is within `dst`
+        unsafe { dst.get_unchecked_mut(start..end) }.inline_fill(value);
+        i = end;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_bits() {
+        let bitmap = [0b1111_0000_u8, 0b0000_0000, 0b0000_0001];
+        assert_eq!(scan_bits(&bitmap, 0, 24, true), 4);
+        assert_eq!(scan_bits(&bitmap, 4, 24, true), 4);
+        assert_eq!(scan_bits(&bitmap, 4, 24, false), 8);
+        assert_eq!(scan_bits(&bitmap, 8, 24, true), 16);
+        assert_eq!(scan_bits(&bitmap, 8, 12, true), 12);
+        assert_eq!(scan_bits(&bitmap, 17, 24, true), 24);
+        assert_eq!(scan_bits(&bitmap, 0, 0, true), 0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_fill_where() {
+        let mut a = [0_u32; 12];
+        fill_where(&mut a, &[0b0110_0011, 0b0000_1100], 7);
+        assert_eq!(a, [7, 7, 0, 0, 0, 7, 7, 0, 0, 0, 7, 7]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_fill_where_ignores_trailing_bits() {
+        let mut a = [0_u8; 3];
+        fill_where(&mut a, &[0xFF], 1);
+        assert_eq!(a, [1, 1, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bitmap too short")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_fill_where_panic() {
+        let mut a = [0_u8; 9];
+        fill_where(&mut a, &[0xFF], 1);
+    }
+
+    #[test]
+    fn test_try_fill_where() {
+        let mut a = [0_u8; 9];
+        assert_eq!(
+            try_fill_where(&mut a, &[0xFF], 1),
+            Err(Error::LimitExceeded { len: 2, limit: 1 })
+        );
+        assert_eq!(a, [0; 9]);
+        assert_eq!(try_fill_where(&mut a, &[0b1000_0001, 0xFE], 1), Ok(()));
+        assert_eq!(a, [1, 0, 0, 0, 0, 0, 0, 1, 0]);
+    }
+}
@@ -0,0 +1,279 @@
+//! Bit granular operations on packed bitmaps.
+//!
+//! Bits are numbered in least significant bit first order, so bit `i` is stored in
+//! `bitmap[i / 8] & (1 << (i % 8))`, the same layout as used by
+//! [`try_fill_where`](crate::try_fill_where).
+
+use crate::{rep_movs, Error, SliceExt};
+use core::ops::Range;
+
+/// Byte indices and masks of the first and last byte touched by a non-empty bit range.
+struct Edges {
+    first: usize,
+    last: usize,
+    head_mask: u8,
+    tail_mask: u8,
+}
+
+impl Edges {
+    /// Validate `range` for a bitmap of `len` bytes, returns `None` for empty ranges.
+    ///
+    /// Returns [`Error::LimitExceeded`] with the end of the range and the number of bits, or
+    /// [`Error::InvalidRange`] if the range is reversed.
+    #[inline]
+    fn new(range: &Range<usize>, len: usize) -> Result<Option<Self>, Error> {
+        let bits = len.saturating_mul(8);
+        if range.start > range.end {
+            return Err(Error::InvalidRange {
+                start: range.start,
+                end: range.end,
+            });
+        }
+        if range.end > bits {
+            return Err(Error::LimitExceeded {
+                len: range.end,
+                limit: bits,
+            });
+        }
+        if range.is_empty() {
+            return Ok(None);
+        }
+        let (first, last) = (range.start / 8, (range.end - 1) / 8);
+        let head_mask = 0xFF_u8 << (range.start % 8);
+        let tail_mask = 0xFF_u8 >> (7 - (range.end - 1) % 8);
+        Ok(Some(if first == last {
+            Self {
+                first,
+                last,
+                head_mask: head_mask & tail_mask,
+                tail_mask: 0,
+            }
+        } else {
+            Self {
+                first,
+                last,
+                head_mask,
+                tail_mask,
+            }
+        }))
+    }
+
+    /// Byte range between the first and last byte, which is fully covered by the bit range.
+    #[inline]
+    fn body(&self) -> Range<usize> {
+        (self.first + 1).min(self.last)..self.last
+    }
+}
+
+#[inline]
+fn set_masked(byte: &mut u8, mask: u8, value: u8) {
+    *byte = (*byte & !mask) | (value & mask);
+}
+
+/// Set all bits in `range` to `value`.
+///
+/// The partial bytes at the start and end of the range are updated using masks, the full bytes
+/// in between are filled using a `rep stos` instruction.
+///
+/// # Panics
+///
+/// Panics if `range` is not within the bits of `bitmap`.
+#[cfg(not(feature = "no-panic"))]
+pub fn fill_range(bitmap: &mut [u8], range: Range<usize>, value: bool) {
+    if try_fill_range(bitmap, range, value).is_err() {
+        panic!("bit range out of bounds");
+    }
+}
+
+/// Same as `fill_range`, but returns [`Error::LimitExceeded`] instead of panicking if `range`
+/// is not within the bits of `bitmap`, or [`Error::InvalidRange`] if it is reversed.
+#[inline]
+pub fn try_fill_range(bitmap: &mut [u8], range: Range<usize>, value: bool) -> Result<(), Error> {
+    let Some(edges) = Edges::new(&range, bitmap.len())? else {
+        return Ok(());
+    };
+    let byte = if value { 0xFF } else { 0x00 };
+    // the edges were validated against the length of `bitmap`
+    unsafe {
+        set_masked(bitmap.get_unchecked_mut(edges.first), edges.head_mask, byte);
+        bitmap.get_unchecked_mut(edges.body()).inline_fill(byte);
+        set_masked(bitmap.get_unchecked_mut(edges.last), edges.tail_mask, byte);
+    }
+    Ok(())
+}
+
+/// Copy the bits in `range` from `src` to the same positions in `dst`.
+///
+/// The partial bytes at the start and end of the range are merged using masks, the full bytes
+/// in between are copied using a `rep movs` instruction.
+///
+/// # Panics
+///
+/// Panics if `range` is not within the bits of both `src` and `dst`.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_range(src: &[u8], dst: &mut [u8], range: Range<usize>) {
+    if try_copy_range(src, dst, range).is_err() {
+        panic!("bit range out of bounds");
+    }
+}
+
+/// Same as `copy_range`, but returns [`Error::LimitExceeded`] instead of panicking if `range`
+/// is not within the bits of both `src` and `dst`, or [`Error::InvalidRange`] if it is
+/// reversed.
+#[inline]
+pub fn try_copy_range(src: &[u8], dst: &mut [u8], range: Range<usize>) -> Result<(), Error> {
+    let Some(edges) = Edges::new(&range, src.len().min(dst.len()))? else {
+        return Ok(());
+    };
+    // the edges were validated against the length of both slices
+    unsafe {
+        let (src, dst) = (src.as_ptr(), dst.as_mut_ptr());
+        set_masked(
+            &mut *dst.add(edges.first),
+            edges.head_mask,
+            *src.add(edges.first),
+        );
+        let body = edges.body();
+        rep_movs(src.add(body.start), dst.add(body.start), body.len());
+        set_masked(
+            &mut *dst.add(edges.last),
+            edges.tail_mask,
+            *src.add(edges.last),
+        );
+    }
+    Ok(())
+}
+
+/// Count the number of set bits in `range`.
+///
+/// The body of the range is processed in 64 bit words, which compile to `popcnt` instructions
+/// when the target supports them. The partial bytes at the start and end are masked.
+///
+/// # Panics
+///
+/// Panics if `range` is not within the bits of `bitmap`.
+#[cfg(not(feature = "no-panic"))]
+pub fn count_ones_range(bitmap: &[u8], range: Range<usize>) -> usize {
+    match try_count_ones_range(bitmap, range) {
+        Ok(count) => count,
+        Err(_) => panic!("bit range out of bounds"),
+    }
+}
+
+/// Same as `count_ones_range`, but returns [`Error::LimitExceeded`] instead of panicking if
+/// `range` is not within the bits of `bitmap`, or [`Error::InvalidRange`] if it is reversed.
+#[inline]
+pub fn try_count_ones_range(bitmap: &[u8], range: Range<usize>) -> Result<usize, Error> {
+    let Some(edges) = Edges::new(&range, bitmap.len())? else {
+        return Ok(0);
+    };
+    // the edges were validated against the length of `bitmap`
+    let (body, first, last) = unsafe {
+        (
+            bitmap.get_unchecked(edges.body()),
+            *bitmap.get_unchecked(edges.first),
+            *bitmap.get_unchecked(edges.last),
+        )
+    };
+    let mut words = body.chunks_exact(8);
+    let mut count = (&mut words)
+        .map(|word| u64::from_le_bytes(word.try_into().unwrap_or_default()).count_ones() as usize)
+        .sum::<usize>();
+    count += words
+        .remainder()
+        .iter()
+        .map(|byte| byte.count_ones() as usize)
+        .sum::<usize>();
+    count += (first & edges.head_mask).count_ones() as usize;
+    count += (last & edges.tail_mask).count_ones() as usize;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "no-panic"))]
+    fn get(bitmap: &[u8], i: usize) -> bool {
+        bitmap[i / 8] & (1 << (i % 8)) != 0
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_fill_range() {
+        for start in 0..32 {
+            for end in start..=32 {
+                let mut bitmap = [0_u8; 4];
+                fill_range(&mut bitmap, start..end, true);
+                for i in 0..32 {
+                    assert_eq!(get(&bitmap, i), (start..end).contains(&i));
+                }
+                let mut bitmap = [0xFF_u8; 4];
+                fill_range(&mut bitmap, start..end, false);
+                for i in 0..32 {
+                    assert_eq!(get(&bitmap, i), !(start..end).contains(&i));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_range() {
+        let src = [0b1010_1010_u8, 0b1100_1100, 0b1111_0000, 0b0101_0101];
+        for start in 0..32 {
+            for end in start..=32 {
+                let mut dst = [0_u8; 4];
+                copy_range(&src, &mut dst, start..end);
+                for i in 0..32 {
+                    assert_eq!(get(&dst, i), (start..end).contains(&i) && get(&src, i));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_count_ones_range() {
+        let bitmap = (0..24_u8).map(|i| i.wrapping_mul(37)).collect::<Vec<_>>();
+        let bits = bitmap.len() * 8;
+        for start in 0..bits {
+            for end in start..=bits {
+                let expected = (start..end).filter(|&i| get(&bitmap, i)).count();
+                assert_eq!(count_ones_range(&bitmap, start..end), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bit range out of bounds")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_fill_range_panic() {
+        fill_range(&mut [0; 2], 3..17, true);
+    }
+
+    #[test]
+    #[should_panic(expected = "bit range out of bounds")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_range_panic() {
+        copy_range(&[0; 3], &mut [0; 2], 3..17);
+    }
+
+    #[test]
+    fn test_try_range_errors() {
+        let mut bitmap = [0_u8; 2];
+        assert_eq!(
+            try_fill_range(&mut bitmap, 3..17, true),
+            Err(Error::LimitExceeded { len: 17, limit: 16 })
+        );
+        assert_eq!(
+            try_copy_range(&[0xFF; 3], &mut bitmap, Range { start: 9, end: 8 }),
+            Err(Error::InvalidRange { start: 9, end: 8 })
+        );
+        assert_eq!(bitmap, [0, 0]);
+        assert_eq!(try_copy_range(&[0xFF; 3], &mut bitmap, 4..12), Ok(()));
+        assert_eq!(bitmap, [0xF0, 0x0F]);
+        assert_eq!(try_count_ones_range(&bitmap, 0..16), Ok(8));
+        assert!(try_count_ones_range(&bitmap, 0..17).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{rep_cmps, rep_scas, Error};
+use core::num::NonZeroUsize;
+
+/// Iterator over the offsets of blocks in a haystack that are equal to a given block, returned by
+/// [`try_find_matching_block`].
+#[derive(Debug, Clone)]
+pub struct MatchingBlocks<'a> {
+    haystack: &'a [u8],
+    block: &'a [u8],
+    step: NonZeroUsize,
+    next: usize,
+}
+
+impl Iterator for MatchingBlocks<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let (haystack, block) = (self.haystack, self.block);
+        let last = haystack.len().checked_sub(block.len())?;
+        while self.next <= last {
+            let mut offset = self.next;
+            if let Some(&first) = block.first() {
+                let candidates = &haystack[offset..=last];
+                let index = unsafe { rep_scas(candidates.as_ptr(), first, candidates.len()) }?;
+                offset += index;
+                let misalignment = offset % self.step;
+                if misalignment != 0 {
+                    self.next = offset.saturating_add(self.step.get() - misalignment);
+                    continue;
+                }
+            }
+            self.next = offset.saturating_add(self.step.get());
+            let equal = unsafe {
+                rep_cmps(haystack.as_ptr().add(offset), block.as_ptr(), block.len()).is_none()
+            };
+            if equal {
+                return Some(offset);
+            }
+        }
+        None
+    }
+}
+
+/// Return the offsets that are multiples of `step` at which `haystack` contains `needle_block`.
+///
+/// Candidate offsets are located by scanning for the first byte of the block using `repne scasb`
+/// and verified using `repe cmpsb`, which makes this a fast verification primitive for
+/// deduplication and rolling hash backends. Matches may overlap if `step` is shorter than the
+/// block.
+///
+/// # Panics
+///
+/// Panics if `needle_block` is not `block_size` bytes long or if `step` is zero.
+#[cfg(not(feature = "no-panic"))]
+pub fn find_matching_block<'a>(
+    haystack: &'a [u8],
+    needle_block: &'a [u8],
+    block_size: usize,
+    step: usize,
+) -> MatchingBlocks<'a> {
+    match try_find_matching_block(haystack, needle_block, block_size, step) {
+        Ok(blocks) => blocks,
+        Err(Error::LenMismatch { .. }) => panic!("length mismatch"),
+        Err(_) => panic!("step must be non-zero"),
+    }
+}
+
+/// Same as `find_matching_block`, but returns [`Error::LenMismatch`] if `needle_block` is not
+/// `block_size` bytes long and [`Error::InvalidArgument`] if `step` is zero instead of panicking.
+#[inline]
+pub fn try_find_matching_block<'a>(
+    haystack: &'a [u8],
+    needle_block: &'a [u8],
+    block_size: usize,
+    step: usize,
+) -> Result<MatchingBlocks<'a>, Error> {
+    if needle_block.len() != block_size {
+        return Err(Error::LenMismatch {
+            src_len: needle_block.len(),
+            dst_len: block_size,
+        });
+    }
+    let step = NonZeroUsize::new(step).ok_or(Error::InvalidArgument { name: "step" })?;
+    Ok(MatchingBlocks {
+        haystack,
+        block: needle_block,
+        step,
+        next: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "no-panic"))]
+    fn collect(haystack: &[u8], block: &[u8], step: usize) -> Vec<usize> {
+        find_matching_block(haystack, block, block.len(), step).collect()
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_find_matching_block() {
+        let haystack = b"abcdXXXXabcdabcdYabcd";
+        assert_eq!(collect(haystack, b"abcd", 1), [0, 8, 12, 17]);
+        assert_eq!(collect(haystack, b"abcd", 4), [0, 8, 12]);
+        assert_eq!(collect(haystack, b"abcd", 8), [0, 8]);
+        assert_eq!(collect(haystack, b"XX", 1), [4, 5, 6]);
+        assert_eq!(collect(haystack, b"XX", 2), [4, 6]);
+        assert_eq!(collect(haystack, b"abce", 1), []);
+        assert_eq!(collect(b"ab", b"abc", 1), []);
+        assert_eq!(collect(b"abc", b"", 2), [0, 2]);
+        assert_eq!(collect(b"", b"", 1), [0]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_find_matching_block_oracle() {
+        let haystack = (0..500_u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 30) as u8)
+            .collect::<Vec<_>>();
+        for block_size in 1..4 {
+            let block = &haystack[100..100 + block_size];
+            for step in 1..6 {
+                let expected = (0..=haystack.len() - block_size)
+                    .step_by(step)
+                    .filter(|&i| &haystack[i..i + block_size] == block)
+                    .collect::<Vec<_>>();
+                assert_eq!(collect(&haystack, block, step), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_find_matching_block_panic() {
+        find_matching_block(b"abc", b"ab", 3, 1);
+    }
+
+    #[test]
+    fn test_try_find_matching_block() {
+        assert_eq!(
+            try_find_matching_block(b"abc", b"ab", 3, 1).err(),
+            Some(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 3
+            })
+        );
+        assert_eq!(
+            try_find_matching_block(b"abc", b"ab", 2, 0).err(),
+            Some(Error::InvalidArgument { name: "step" })
+        );
+        let blocks = try_find_matching_block(b"ababab", b"ab", 2, 4).unwrap();
+        assert_eq!(blocks.collect::<Vec<_>>(), [0, 4]);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::Error;
+
+/// Copy the 16 byte blocks of `len` bytes from `src` to `dst`, shuffling the bytes of each block
+/// according to `shuffle`, and return the number of copied bytes.
+///
+/// # Safety
+///
+/// `src` and `dst` need to be valid for `len` bytes and the processor needs to support SSSE3.
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+#[target_feature(enable = "ssse3")]
+unsafe fn copy_shuffled_ssse3(
+    src: *const u8,
+    dst: *mut u8,
+    len: usize,
+    shuffle: [u8; 16],
+) -> usize {
+    use core::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_shuffle_epi8, _mm_storeu_si128};
+
+    let shuffle = _mm_loadu_si128(shuffle.as_ptr().cast::<__m128i>());
+    let blocks = len / 16;
+    for i in 0..blocks {
+        let data = _mm_loadu_si128(src.add(i * 16).cast::<__m128i>());
+        _mm_storeu_si128(
+            dst.add(i * 16).cast::<__m128i>(),
+            _mm_shuffle_epi8(data, shuffle),
+        );
+    }
+    blocks * 16
+}
+
+/// Byte index permutation that reverses the bytes of each `WIDTH` byte lane of a 16 byte block.
+const fn reverse_lanes<const WIDTH: usize>() -> [u8; 16] {
+    let mut shuffle = [0; 16];
+    let mut i = 0;
+    while i < 16 {
+        shuffle[i] = (i - i % WIDTH + (WIDTH - 1 - i % WIDTH)) as u8;
+        i += 1;
+    }
+    shuffle
+}
+
+macro_rules! copy_bswap {
+    ($(#[$attr:meta])* $name:ident, $try_name:ident, $ty:ty) => {
+        $(#[$attr])*
+        ///
+        /// On x86_64 processors supporting SSSE3 blocks of 16 bytes are swapped using `pshufb`,
+        /// the remaining elements and other processors use `swap_bytes`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `src` and `dst` have different lengths.
+        #[cfg(not(feature = "no-panic"))]
+        pub fn $name(src: &[$ty], dst: &mut [$ty]) {
+            if $try_name(src, dst).is_err() {
+                panic!("length mismatch")
+            }
+        }
+
+        #[doc = concat!("Same as `", stringify!($name), "`, but returns [`Error::LenMismatch`]")]
+        /// instead of panicking if `src` and `dst` have different lengths.
+        pub fn $try_name(src: &[$ty], dst: &mut [$ty]) -> Result<(), Error> {
+            if src.len() != dst.len() {
+                return Err(Error::LenMismatch {
+                    src_len: src.len(),
+                    dst_len: dst.len(),
+                });
+            }
+            #[allow(unused_mut)]
+            let mut done = 0;
+            #[cfg(all(target_arch = "x86_64", not(miri)))]
+            if std::is_x86_feature_detected!("ssse3") {
+                const SHUFFLE: [u8; 16] = reverse_lanes::<{ core::mem::size_of::<$ty>() }>();
+                let bytes = unsafe {
+                    copy_shuffled_ssse3(
+                        src.as_ptr().cast::<u8>(),
+                        dst.as_mut_ptr().cast::<u8>(),
+                        core::mem::size_of_val(src),
+                        SHUFFLE,
+                    )
+                };
+                done = bytes / core::mem::size_of::<$ty>();
+            }
+            for (dst, src) in dst.iter_mut().zip(src).skip(done) {
+                *dst = src.swap_bytes();
+            }
+            Ok(())
+        }
+    };
+}
+
+copy_bswap!(
+    /// Copy `src` into `dst`, reversing the byte order of each element.
+    copy_bswap16,
+    try_copy_bswap16,
+    u16
+);
+copy_bswap!(
+    /// Copy `src` into `dst`, reversing the byte order of each element.
+    copy_bswap32,
+    try_copy_bswap32,
+    u32
+);
+copy_bswap!(
+    /// Copy `src` into `dst`, reversing the byte order of each element.
+    copy_bswap64,
+    try_copy_bswap64,
+    u64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reverse_lanes() {
+        assert_eq!(reverse_lanes::<2>()[..4], [1, 0, 3, 2]);
+        assert_eq!(reverse_lanes::<4>()[..8], [3, 2, 1, 0, 7, 6, 5, 4]);
+        assert_eq!(reverse_lanes::<8>()[8..], [15, 14, 13, 12, 11, 10, 9, 8]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_bswap() {
+        for len in 0..40 {
+            let src = (0..len as u64)
+                .map(|i| i.wrapping_mul(0x0123_4567_89AB_CDEF))
+                .collect::<Vec<_>>();
+
+            let src16 = src.iter().map(|&x| x as u16).collect::<Vec<_>>();
+            let mut dst16 = vec![0; len];
+            copy_bswap16(&src16, &mut dst16);
+            assert!(dst16.iter().zip(&src16).all(|(d, s)| *d == s.swap_bytes()));
+
+            let src32 = src.iter().map(|&x| x as u32).collect::<Vec<_>>();
+            let mut dst32 = vec![0; len];
+            copy_bswap32(&src32, &mut dst32);
+            assert!(dst32.iter().zip(&src32).all(|(d, s)| *d == s.swap_bytes()));
+
+            let mut dst64 = vec![0; len];
+            copy_bswap64(&src, &mut dst64);
+            assert!(dst64.iter().zip(&src).all(|(d, s)| *d == s.swap_bytes()));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_bswap_panic() {
+        copy_bswap32(&[1, 2], &mut [0]);
+    }
+
+    #[test]
+    fn test_try_copy_bswap() {
+        let mut dst = [0_u32; 1];
+        assert_eq!(
+            try_copy_bswap32(&[1, 2], &mut dst),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 1
+            })
+        );
+        assert_eq!(try_copy_bswap32(&[0x0102_0304], &mut dst), Ok(()));
+        assert_eq!(dst, [0x0403_0201]);
+        assert_eq!(try_copy_bswap16(&[], &mut []), Ok(()));
+    }
+}
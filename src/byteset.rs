@@ -0,0 +1,128 @@
+use crate::{rep_scas, repe_scas};
+
+/// Set of byte values, stored as a 256 bit table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ByteSet {
+    bits: [u64; 4],
+}
+
+impl ByteSet {
+    /// Empty set.
+    pub const EMPTY: ByteSet = ByteSet { bits: [0; 4] };
+
+    /// Create a set containing the bytes of `values`.
+    pub const fn new(values: &[u8]) -> Self {
+        let mut set = Self::EMPTY;
+        let mut i = 0;
+        while i < values.len() {
+            set = set.with(values[i]);
+            i += 1;
+        }
+        set
+    }
+
+    /// Return this set with `byte` added.
+    #[inline]
+    pub const fn with(mut self, byte: u8) -> Self {
+        self.bits[(byte >> 6) as usize] |= 1 << (byte & 63);
+        self
+    }
+
+    /// Return the set of all bytes that are not in this set.
+    #[inline]
+    pub const fn complement(self) -> Self {
+        let [a, b, c, d] = self.bits;
+        Self {
+            bits: [!a, !b, !c, !d],
+        }
+    }
+
+    #[inline]
+    pub const fn contains(&self, byte: u8) -> bool {
+        self.bits[(byte >> 6) as usize] & (1 << (byte & 63)) != 0
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        let [a, b, c, d] = self.bits;
+        (a.count_ones() + b.count_ones() + c.count_ones() + d.count_ones()) as usize
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the only element if the set contains exactly one byte.
+    #[inline]
+    fn single(&self) -> Option<u8> {
+        if self.len() != 1 {
+            return None;
+        }
+        let word = self.bits.iter().position(|&bits| bits != 0)?;
+        Some((word * 64) as u8 + self.bits[word].trailing_zeros() as u8)
+    }
+}
+
+/// Return the index of the first byte of `haystack` that is contained in `set`.
+///
+/// Sets with a single byte use `repne scasb`, other sets classify each byte using the table.
+#[inline]
+pub fn position_any(haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    match set.single() {
+        Some(byte) => unsafe { rep_scas(haystack.as_ptr(), byte, haystack.len()) },
+        None => haystack.iter().position(|&byte| set.contains(byte)),
+    }
+}
+
+/// Return the index of the first byte of `haystack` that is not contained in `set`, for example
+/// the end of an identifier or number in a lexer.
+///
+/// Sets with a single byte use `repe scasb`, other sets classify each byte using the table.
+#[inline]
+pub fn position_not_any(haystack: &[u8], set: &ByteSet) -> Option<usize> {
+    match set.single() {
+        Some(byte) => unsafe { repe_scas(haystack.as_ptr(), byte, haystack.len()) },
+        None => haystack.iter().position(|&byte| !set.contains(byte)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGITS: ByteSet = ByteSet::new(b"0123456789");
+
+    #[test]
+    fn test_byte_set() {
+        assert_eq!(DIGITS.len(), 10);
+        assert!(DIGITS.contains(b'7') && !DIGITS.contains(b'a'));
+        assert_eq!(DIGITS.complement().len(), 246);
+        assert!(!DIGITS.complement().contains(b'0'));
+        assert!(ByteSet::EMPTY.is_empty());
+        assert_eq!(ByteSet::new(&[255]).single(), Some(255));
+        assert_eq!(ByteSet::new(&[64]).single(), Some(64));
+        assert_eq!(DIGITS.single(), None);
+    }
+
+    #[test]
+    fn test_position_any() {
+        assert_eq!(position_any(b"abc123", &DIGITS), Some(3));
+        assert_eq!(position_any(b"abc", &DIGITS), None);
+        assert_eq!(position_any(b"a,b", &ByteSet::new(b",")), Some(1));
+        assert_eq!(position_any(b"", &DIGITS), None);
+    }
+
+    #[test]
+    fn test_position_not_any() {
+        assert_eq!(position_not_any(b"12345+6", &DIGITS), Some(5));
+        assert_eq!(position_not_any(b"12345", &DIGITS), None);
+        assert_eq!(position_not_any(b"    x", &ByteSet::new(b" ")), Some(4));
+        assert_eq!(position_not_any(b"abc", &ByteSet::EMPTY), Some(0));
+        for len in 0..100 {
+            let mut input = vec![b'9'; len];
+            input.push(b'x');
+            assert_eq!(position_not_any(&input, &DIGITS), Some(len));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{backend, Config, CpuFlags, Microarch, Thresholds};
+use core::fmt;
+
+/// Description of the build and runtime environment of this crate, as returned by
+/// [`capabilities`].
+///
+/// The [`Display`](fmt::Display) output is meant to be pasted into bug reports, with the `serde`
+/// feature the report can also be serialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Capabilities {
+    /// Version of this crate.
+    pub version: &'static str,
+    /// Enabled cargo features of this crate.
+    pub features: Vec<&'static str>,
+    pub microarch: Microarch,
+    pub cpu_flags: CpuFlags,
+    /// Name of the registered backend.
+    pub backend: &'static str,
+    /// Name of the backend forced by the global configuration.
+    pub force_backend: Option<&'static str>,
+    /// Thresholds of the global configuration.
+    pub thresholds: Thresholds,
+    /// Chunk size of the global configuration.
+    pub chunk_size: usize,
+}
+
+/// Collect the compiled features, detected processor, registered backend and global
+/// configuration into a single report.
+pub fn capabilities() -> Capabilities {
+    let features = [
+        ("audio", cfg!(feature = "audio")),
+        ("checked", cfg!(feature = "checked")),
+        ("codegen", cfg!(feature = "codegen")),
+        ("core-ops", cfg!(feature = "core-ops")),
+        ("dispatch", cfg!(feature = "dispatch")),
+        ("io", cfg!(feature = "io")),
+        ("kernels", cfg!(feature = "kernels")),
+        ("measure", cfg!(feature = "measure")),
+        ("mem", cfg!(feature = "mem")),
+        ("no-panic", cfg!(feature = "no-panic")),
+        ("search", cfg!(feature = "search")),
+        ("serde", cfg!(feature = "serde")),
+        ("simd", cfg!(feature = "simd")),
+        ("std", cfg!(feature = "std")),
+        ("testing", cfg!(feature = "testing")),
+    ];
+    let config = Config::global();
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        features: features
+            .into_iter()
+            .filter_map(|(name, enabled)| enabled.then_some(name))
+            .collect(),
+        microarch: Microarch::detect(),
+        cpu_flags: CpuFlags::detect(),
+        backend: backend::current().name(),
+        force_backend: config.force_backend.map(|backend| backend.name()),
+        thresholds: config.thresholds,
+        chunk_size: config.chunk_size,
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "x86_strings_ops {}", self.version)?;
+        writeln!(f, "features: {}", self.features.join(", "))?;
+        writeln!(f, "microarch: {:?}", self.microarch)?;
+        writeln!(f, "cpu flags: {:?}", self.cpu_flags)?;
+        writeln!(f, "backend: {}", self.backend)?;
+        writeln!(f, "force backend: {}", self.force_backend.unwrap_or("none"))?;
+        writeln!(f, "thresholds: {:?}", self.thresholds)?;
+        write!(f, "chunk size: {}", self.chunk_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let report = capabilities();
+        assert_eq!(report.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(report.microarch, Microarch::detect());
+        assert_eq!(
+            report.features.contains(&"checked"),
+            cfg!(feature = "checked")
+        );
+        let text = report.to_string();
+        assert!(text.starts_with("x86_strings_ops "));
+        assert!(text.contains(&format!("backend: {}", report.backend)));
+        assert_eq!(text.lines().count(), 8);
+    }
+}
@@ -0,0 +1,308 @@
+//! Machine code encodings of the string instructions, for code generators that want to inline
+//! them into generated code.
+//!
+//! Each function returns the instruction preceded by `xchg` instructions that move the operands
+//! from the chosen registers into the fixed registers used by the string instruction. The
+//! generated code assumes the direction flag to be cleared, as required by the x86_64 ABI, and
+//! clobbers `rsi`, `rdi`, `rcx` and the chosen registers.
+
+use crate::{rep_movs, Error};
+
+/// General purpose 64 bit register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Reg {
+    Rax = 0,
+    Rcx = 1,
+    Rdx = 2,
+    Rbx = 3,
+    Rsp = 4,
+    Rbp = 5,
+    Rsi = 6,
+    Rdi = 7,
+    R8 = 8,
+    R9 = 9,
+    R10 = 10,
+    R11 = 11,
+    R12 = 12,
+    R13 = 13,
+    R14 = 14,
+    R15 = 15,
+}
+
+/// Element size of a string instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Width {
+    Byte,
+    Word,
+    Dword,
+    Qword,
+}
+
+/// Encoded instruction sequence of at most 16 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Code {
+    bytes: [u8; 16],
+    len: usize,
+}
+
+impl Code {
+    fn new() -> Self {
+        Self {
+            bytes: [0; 16],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    /// Encode `xchg a, b`.
+    fn xchg(&mut self, a: Reg, b: Reg) {
+        let (a, b) = (a as u8, b as u8);
+        let rex = 0x48 | ((a >> 3) << 2) | (b >> 3);
+        self.push(&[rex, 0x87, 0xC0 | ((a & 7) << 3) | (b & 7)]);
+    }
+
+    /// Move the values in `sources` into the corresponding `targets` using `xchg` instructions,
+    /// or return [`Error::Overlap`] if `sources` are not distinct.
+    fn setup(&mut self, targets: [Reg; 3], mut sources: [Reg; 3]) -> Result<(), Error> {
+        if sources[0] == sources[1] || sources[0] == sources[2] || sources[1] == sources[2] {
+            return Err(Error::Overlap);
+        }
+        for i in 0..targets.len() {
+            let (target, source) = (targets[i], sources[i]);
+            if target != source {
+                self.xchg(target, source);
+                // the previous value of `target` now lives in `source`
+                for pending in &mut sources[i + 1..] {
+                    if *pending == target {
+                        *pending = source;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode a string instruction with the given repeat prefix and byte sized opcode.
+    fn string_op(&mut self, prefix: u8, opcode: u8, width: Width) {
+        match width {
+            Width::Byte => self.push(&[prefix, opcode]),
+            Width::Word => self.push(&[0x66, prefix, opcode + 1]),
+            Width::Dword => self.push(&[prefix, opcode + 1]),
+            Width::Qword => self.push(&[prefix, 0x48, opcode + 1]),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Write the encoded bytes to the start of `buf` and return their number, or `None` if `buf`
+    /// is too short.
+    pub fn emit(&self, buf: &mut [u8]) -> Option<usize> {
+        let dst = buf.get_mut(..self.len)?;
+        unsafe { rep_movs(self.bytes.as_ptr(), dst.as_mut_ptr(), self.len) };
+        Some(self.len)
+    }
+}
+
+/// Encode `rep movs` copying `count` elements from the address in `src` to the address in `dst`.
+///
+/// # Panics
+///
+/// Panics if the registers are not distinct.
+#[cfg(not(feature = "no-panic"))]
+pub fn rep_movs_code(width: Width, src: Reg, dst: Reg, count: Reg) -> Code {
+    match try_rep_movs_code(width, src, dst, count) {
+        Ok(code) => code,
+        Err(_) => panic!("operand registers must be distinct"),
+    }
+}
+
+/// Same as `rep_movs_code`, but returns [`Error::Overlap`] instead of panicking if the registers
+/// are not distinct.
+pub fn try_rep_movs_code(width: Width, src: Reg, dst: Reg, count: Reg) -> Result<Code, Error> {
+    let mut code = Code::new();
+    code.setup([Reg::Rsi, Reg::Rdi, Reg::Rcx], [src, dst, count])?;
+    code.string_op(0xF3, 0xA4, width);
+    Ok(code)
+}
+
+/// Encode `rep stos` storing the low `width` bytes of `value` to `count` elements at the
+/// address in `dst`.
+///
+/// # Panics
+///
+/// Panics if the registers are not distinct.
+#[cfg(not(feature = "no-panic"))]
+pub fn rep_stos_code(width: Width, value: Reg, dst: Reg, count: Reg) -> Code {
+    match try_rep_stos_code(width, value, dst, count) {
+        Ok(code) => code,
+        Err(_) => panic!("operand registers must be distinct"),
+    }
+}
+
+/// Same as `rep_stos_code`, but returns [`Error::Overlap`] instead of panicking if the registers
+/// are not distinct.
+pub fn try_rep_stos_code(width: Width, value: Reg, dst: Reg, count: Reg) -> Result<Code, Error> {
+    let mut code = Code::new();
+    code.setup([Reg::Rax, Reg::Rdi, Reg::Rcx], [value, dst, count])?;
+    code.string_op(0xF3, 0xAA, width);
+    Ok(code)
+}
+
+/// Encode `repne scas` searching `count` elements at the address in `src` for the low `width`
+/// bytes of `value`.
+///
+/// After execution `ZF` is set if a match was found, in which case `rdi` points one element past
+/// the match.
+///
+/// # Panics
+///
+/// Panics if the registers are not distinct.
+#[cfg(not(feature = "no-panic"))]
+pub fn repne_scas_code(width: Width, value: Reg, src: Reg, count: Reg) -> Code {
+    match try_repne_scas_code(width, value, src, count) {
+        Ok(code) => code,
+        Err(_) => panic!("operand registers must be distinct"),
+    }
+}
+
+/// Same as `repne_scas_code`, but returns [`Error::Overlap`] instead of panicking if the
+/// registers are not distinct.
+pub fn try_repne_scas_code(width: Width, value: Reg, src: Reg, count: Reg) -> Result<Code, Error> {
+    let mut code = Code::new();
+    code.setup([Reg::Rax, Reg::Rdi, Reg::Rcx], [value, src, count])?;
+    code.string_op(0xF2, 0xAE, width);
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply the `xchg` instructions at the start of `code` to a register file and return the
+    /// remaining bytes.
+    fn simulate_xchg<'a>(mut code: &'a [u8], registers: &mut [u64; 16]) -> &'a [u8] {
+        while let [rex @ 0x48..=0x4F, 0x87, modrm, rest @ ..] = code {
+            let a = ((rex >> 2) & 1) << 3 | ((modrm >> 3) & 7);
+            let b = (rex & 1) << 3 | (modrm & 7);
+            registers.swap(a as usize, b as usize);
+            code = rest;
+        }
+        code
+    }
+
+    #[test]
+    fn test_string_op_encodings() {
+        let (src, dst, count) = (Reg::Rsi, Reg::Rdi, Reg::Rcx);
+        let cases = [
+            (Width::Byte, &[0xF3, 0xA4][..]),
+            (Width::Word, &[0x66, 0xF3, 0xA5]),
+            (Width::Dword, &[0xF3, 0xA5]),
+            (Width::Qword, &[0xF3, 0x48, 0xA5]),
+        ];
+        for (width, expected) in cases {
+            assert_eq!(
+                try_rep_movs_code(width, src, dst, count)
+                    .unwrap()
+                    .as_bytes(),
+                expected
+            );
+        }
+        let stosb = try_rep_stos_code(Width::Byte, Reg::Rax, Reg::Rdi, Reg::Rcx).unwrap();
+        assert_eq!(stosb.as_bytes(), &[0xF3, 0xAA]);
+        let scasb = try_repne_scas_code(Width::Byte, Reg::Rax, Reg::Rdi, Reg::Rcx).unwrap();
+        assert_eq!(scasb.as_bytes(), &[0xF2, 0xAE]);
+        let scasq = try_repne_scas_code(Width::Qword, Reg::Rax, Reg::Rdi, Reg::Rcx).unwrap();
+        assert_eq!(scasq.as_bytes(), &[0xF2, 0x48, 0xAF]);
+    }
+
+    #[test]
+    fn test_xchg_encoding() {
+        // xchg rsi, rdx
+        let code = try_rep_movs_code(Width::Byte, Reg::Rdx, Reg::Rdi, Reg::Rcx).unwrap();
+        assert_eq!(code.as_bytes(), &[0x48, 0x87, 0xF2, 0xF3, 0xA4]);
+        // xchg rax, r9
+        let code = try_rep_stos_code(Width::Byte, Reg::R9, Reg::Rdi, Reg::Rcx).unwrap();
+        assert_eq!(code.as_bytes(), &[0x49, 0x87, 0xC1, 0xF3, 0xAA]);
+    }
+
+    #[test]
+    fn test_setup_permutations() {
+        let registers = [
+            Reg::Rax,
+            Reg::Rcx,
+            Reg::Rdx,
+            Reg::Rsi,
+            Reg::Rdi,
+            Reg::R8,
+            Reg::R15,
+        ];
+        for &src in &registers {
+            for &dst in &registers {
+                for &count in &registers {
+                    if src == dst || src == count || dst == count {
+                        continue;
+                    }
+                    let code = try_rep_movs_code(Width::Byte, src, dst, count).unwrap();
+                    let mut file = [0_u64; 16];
+                    for (i, value) in file.iter_mut().enumerate() {
+                        *value = i as u64;
+                    }
+                    let rest = simulate_xchg(code.as_bytes(), &mut file);
+                    assert_eq!(rest, &[0xF3, 0xA4]);
+                    assert_eq!(file[Reg::Rsi as usize], src as u64);
+                    assert_eq!(file[Reg::Rdi as usize], dst as u64);
+                    assert_eq!(file[Reg::Rcx as usize], count as u64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_emit() {
+        let code = try_rep_movs_code(Width::Qword, Reg::R8, Reg::R9, Reg::R10).unwrap();
+        let mut buf = [0xCC_u8; 16];
+        assert_eq!(code.emit(&mut buf), Some(code.len()));
+        assert_eq!(&buf[..code.len()], code.as_bytes());
+        assert_eq!(buf[code.len()], 0xCC);
+        assert_eq!(code.emit(&mut buf[..3]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "operand registers must be distinct")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_registers_not_distinct() {
+        rep_movs_code(Width::Byte, Reg::Rdx, Reg::Rdx, Reg::Rcx);
+    }
+
+    #[test]
+    fn test_try_registers_not_distinct() {
+        let (rdx, rcx) = (Reg::Rdx, Reg::Rcx);
+        assert_eq!(
+            try_rep_movs_code(Width::Byte, rdx, rdx, rcx),
+            Err(Error::Overlap)
+        );
+        assert_eq!(
+            try_rep_stos_code(Width::Byte, rcx, rdx, rcx),
+            Err(Error::Overlap)
+        );
+        assert_eq!(
+            try_repne_scas_code(Width::Byte, rdx, rcx, rdx),
+            Err(Error::Overlap)
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use crate::backend::Backend;
+use crate::cpuid::{CpuFlags, Microarch};
+use std::sync::OnceLock;
+
+/// Minimum lengths in bytes from which on the selected backend is used.
+///
+/// Shorter operations use the [`ScalarBackend`](crate::backend::ScalarBackend) instead, which
+/// avoids the startup overhead of `rep` instructions on processors without the fast short
+/// `rep` features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Thresholds {
+    pub copy: usize,
+    pub fill: usize,
+    pub position: usize,
+    pub mismatch: usize,
+}
+
+impl Thresholds {
+    /// Thresholds that always use the selected backend.
+    pub const NONE: Thresholds = Thresholds {
+        copy: 0,
+        fill: 0,
+        position: 0,
+        mismatch: 0,
+    };
+
+    /// Built-in thresholds for the microarchitecture of the current processor.
+    ///
+    /// Processors without fast short `rep movsb`, like Skylake or Zen 2, use the scalar backend
+    /// for short copies. Unknown processors use [`Thresholds::NONE`].
+    pub fn detected() -> Thresholds {
+        Microarch::detect().thresholds()
+    }
+
+    /// Thresholds for copies and fills measured on the current processor, by comparing the
+    /// [`RepBackend`](crate::backend::RepBackend) and the
+    /// [`ScalarBackend`](crate::backend::ScalarBackend) for lengths of up to 1 KiB.
+    ///
+    /// Measuring takes a few milliseconds and is therefore meant to run once during startup.
+    /// Operations for which the `rep` instructions were slower even at 1 KiB, and the position
+    /// and mismatch thresholds, use the [detected](Thresholds::detected) thresholds.
+    #[cfg(feature = "measure")]
+    pub fn calibrated() -> Thresholds {
+        use crate::backend::{Backend, RepBackend, ScalarBackend};
+        use crate::measure::crossover;
+
+        const SIZES: [usize; 8] = [8, 16, 32, 64, 128, 256, 512, 1024];
+        let copy = |backend: &'static dyn Backend| {
+            move |src: &[u8], dst: &mut [u8]| unsafe {
+                backend.copy(src.as_ptr(), dst.as_mut_ptr(), dst.len())
+            }
+        };
+        let fill = |backend: &'static dyn Backend| {
+            move |_: &[u8], dst: &mut [u8]| unsafe { backend.fill(0, dst.as_mut_ptr(), dst.len()) }
+        };
+        let detected = Thresholds::detected();
+        Thresholds {
+            copy: crossover(&SIZES, copy(&RepBackend), copy(&ScalarBackend))
+                .unwrap_or(detected.copy),
+            fill: crossover(&SIZES, fill(&RepBackend), fill(&ScalarBackend))
+                .unwrap_or(detected.fill),
+            ..detected
+        }
+    }
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Instruction width used to copy slices with elements larger than a byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyWidth {
+    /// Use the instruction matching the element size, for example `rep movsq` for `u64`, if the
+    /// selected backend supports [element copies](crate::backend::Backend::element_copies).
+    /// Other backends, including the scalar backend below the copy threshold, copy bytes.
+    #[default]
+    Element,
+    /// Use `rep movsb` with the length in bytes, through the selected backend.
+    ///
+    /// On processors with enhanced `rep movsb` (ERMS) the byte form uses the optimized microcode
+    /// paths, and is often faster than the wider forms.
+    Bytes,
+}
+
+impl CopyWidth {
+    /// [`CopyWidth::Bytes`] on processors with enhanced `rep movsb`, [`CopyWidth::Element`]
+    /// otherwise.
+    pub fn detected() -> CopyWidth {
+        if CpuFlags::detect().erms {
+            CopyWidth::Bytes
+        } else {
+            CopyWidth::Element
+        }
+    }
+}
+
+/// Settings used by the dispatching functions in the [`backend`](crate::backend) module.
+///
+/// A configuration can either be installed globally using [`Config::install`], or passed
+/// explicitly to the `*_with_config` functions.
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Length thresholds below which the scalar backend is used.
+    pub thresholds: Thresholds,
+    /// Whether copies of at least [`LARGE_MIN`](crate::LARGE_MIN) bytes use non-temporal stores
+    /// instead of the selected backend.
+    pub allow_nt_stores: bool,
+    /// Backend to use instead of the registered one.
+    pub force_backend: Option<&'static dyn Backend>,
+    /// Maximum number of bytes passed to a single backend call, larger operations are split
+    /// into multiple calls.
+    pub chunk_size: usize,
+    /// Instruction width used to copy slices with elements larger than a byte.
+    pub copy_width: CopyWidth,
+    /// Maximum length of the buffers retained per thread by [`with_scratch`](crate::with_scratch),
+    /// longer buffers are allocated on every call.
+    pub scratch_limit: usize,
+}
+
+impl Config {
+    /// Configuration that always uses the registered backend without splitting operations.
+    pub const fn new() -> Self {
+        Self {
+            thresholds: Thresholds::NONE,
+            allow_nt_stores: false,
+            force_backend: None,
+            chunk_size: usize::MAX,
+            copy_width: CopyWidth::Element,
+            scratch_limit: crate::DEFAULT_SCRATCH_LIMIT,
+        }
+    }
+
+    /// Limit the number of bytes passed to a single backend call, and therefore processed by a
+    /// single uninterruptible `rep` instruction, to `max_burst_bytes`.
+    ///
+    /// Although `rep` instructions can be interrupted by the hardware, a single call does not
+    /// return to the caller until it completes, so the caller can not yield, check for
+    /// cancellation or meet a deadline in between. Splitting large operations bounds the time
+    /// spent in a single call, at the cost of the startup overhead of one `rep` instruction per
+    /// chunk. Chunks of tens of KiB usually keep the throughput of a single call, while chunks of
+    /// a few KiB make the startup overhead noticeable. The `bench_burst` benchmark measures the
+    /// tradeoff on the current machine.
+    ///
+    /// This sets [`Config::chunk_size`], which is enforced for all operations of the
+    /// [`backend`](crate::backend) module.
+    #[inline]
+    pub const fn with_max_burst_bytes(self, max_burst_bytes: usize) -> Self {
+        Self {
+            chunk_size: max_burst_bytes,
+            ..self
+        }
+    }
+
+    /// Use non-temporal stores for copies of at least [`LARGE_MIN`](crate::LARGE_MIN) bytes.
+    ///
+    /// Non-temporal stores bypass the cache, so that a large copy into a buffer that is not read
+    /// again soon does not evict the working set of the application. They bypass the selected
+    /// backend as well, and are slower than `rep movsb` if the destination is read right after
+    /// the copy.
+    ///
+    /// This sets [`Config::allow_nt_stores`], which is used by
+    /// [`try_copy_with_config`](crate::backend::try_copy_with_config) and
+    /// [`try_copy_slice_with_config`](crate::backend::try_copy_slice_with_config).
+    #[inline]
+    pub const fn with_nt_stores(self, allow_nt_stores: bool) -> Self {
+        Self {
+            allow_nt_stores,
+            ..self
+        }
+    }
+
+    /// Copy slices with elements larger than a byte using the given instruction width.
+    ///
+    /// This sets [`Config::copy_width`], which is used by
+    /// [`try_copy_slice_with_config`](crate::backend::try_copy_slice_with_config).
+    #[inline]
+    pub const fn with_copy_width(self, copy_width: CopyWidth) -> Self {
+        Self { copy_width, ..self }
+    }
+
+    /// Retain scratch buffers of up to `scratch_limit` bytes per thread.
+    ///
+    /// This sets [`Config::scratch_limit`].
+    #[inline]
+    pub const fn with_scratch_limit(self, scratch_limit: usize) -> Self {
+        Self {
+            scratch_limit,
+            ..self
+        }
+    }
+
+    /// Install this configuration as the global configuration.
+    ///
+    /// The global configuration can only be installed once, usually during startup of the
+    /// application. If a configuration was already installed, this configuration is returned
+    /// as the error value.
+    pub fn install(self) -> Result<(), Config> {
+        GLOBAL.set(self)
+    }
+
+    /// Configuration using the [detected](Thresholds::detected) thresholds and
+    /// [copy width](CopyWidth::detected) of the current processor.
+    pub fn detected() -> Self {
+        Self {
+            thresholds: Thresholds::detected(),
+            copy_width: CopyWidth::detected(),
+            ..Self::new()
+        }
+    }
+
+    /// Return the global configuration, or the [detected](Config::detected) configuration if
+    /// none was installed.
+    #[inline]
+    pub fn global() -> &'static Config {
+        static DETECTED: OnceLock<Config> = OnceLock::new();
+        GLOBAL
+            .get()
+            .unwrap_or_else(|| DETECTED.get_or_init(Config::detected))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Config {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Config")
+            .field("thresholds", &self.thresholds)
+            .field("allow_nt_stores", &self.allow_nt_stores)
+            .field("force_backend", &self.force_backend.map(|b| b.name()))
+            .field("chunk_size", &self.chunk_size)
+            .field("copy_width", &self.copy_width)
+            .field("scratch_limit", &self.scratch_limit)
+            .finish()
+    }
+}
+
+static GLOBAL: OnceLock<Config> = OnceLock::new();
+
+#[cfg(all(test, feature = "measure"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibrated() {
+        let calibrated = Thresholds::calibrated();
+        let detected = Thresholds::detected();
+        assert!(calibrated.copy <= 1024 || calibrated.copy == detected.copy);
+        assert!(calibrated.fill <= 1024 || calibrated.fill == detected.fill);
+        assert_eq!(calibrated.position, detected.position);
+        assert_eq!(calibrated.mismatch, detected.mismatch);
+    }
+}
@@ -0,0 +1,424 @@
+use crate::{rep_movs, rep_stos, Error, RegisterType};
+
+/// Result of [`copy_truncated`] and
+/// [`SliceExt::try_inline_fill_at_most`](crate::SliceExt::try_inline_fill_at_most).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyOutcome {
+    /// Number of elements that were copied.
+    pub copied: usize,
+    /// Whether `src` was longer than the destination and not all elements were copied.
+    pub truncated: bool,
+}
+
+/// Copy as many elements of `src` as fit into `dst`, starting at the beginning of both slices.
+///
+/// Elements of `dst` after the copied prefix are left unchanged.
+#[inline]
+pub fn copy_truncated<T: Copy>(src: &[T], dst: &mut [T]) -> CopyOutcome {
+    let copied = src.len().min(dst.len());
+    unsafe { rep_movs(src.as_ptr(), dst.as_mut_ptr(), copied) };
+    CopyOutcome {
+        copied,
+        truncated: copied < src.len(),
+    }
+}
+
+/// Copy `src` into `dst` if both have the same length, otherwise return
+/// [`Error::LenMismatch`] without modifying `dst`.
+#[inline]
+pub fn copy_exact_or_err<T: Copy>(src: &[T], dst: &mut [T]) -> Result<(), Error> {
+    if src.len() != dst.len() {
+        return Err(Error::LenMismatch {
+            src_len: src.len(),
+            dst_len: dst.len(),
+        });
+    }
+    unsafe { rep_movs(src.as_ptr(), dst.as_mut_ptr(), dst.len()) };
+    Ok(())
+}
+
+/// Copy the bytes of `src` into `dst`, which can have a different element type of the same
+/// total size in bytes, using `rep movsb`.
+///
+/// Both element types are restricted to [`RegisterType`], which have no padding bytes and for
+/// which every bit pattern is a valid value. Since `dst` is a slice of `U`, its alignment is
+/// already guaranteed by the type system, `src` is read byte-wise and needs no alignment beyond
+/// that of `T`.
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different sizes in bytes.
+#[cfg(not(feature = "no-panic"))]
+#[inline]
+pub fn copy_bytes_between<T: RegisterType, U: RegisterType>(src: &[T], dst: &mut [U]) {
+    if try_copy_bytes_between(src, dst).is_err() {
+        panic!("byte length mismatch");
+    }
+}
+
+/// Same as `copy_bytes_between`, but returns [`Error::LenMismatch`] with the sizes in bytes
+/// instead of panicking if `src` and `dst` have different sizes in bytes.
+#[inline]
+pub fn try_copy_bytes_between<T: RegisterType, U: RegisterType>(
+    src: &[T],
+    dst: &mut [U],
+) -> Result<(), Error> {
+    let len = core::mem::size_of_val(dst);
+    if core::mem::size_of_val(src) != len {
+        return Err(Error::LenMismatch {
+            src_len: core::mem::size_of_val(src),
+            dst_len: len,
+        });
+    }
+    unsafe {
+        rep_movs(
+            src.as_ptr().cast::<u8>(),
+            dst.as_mut_ptr().cast::<u8>(),
+            len,
+        )
+    }
+    Ok(())
+}
+
+/// Element transformation applied by [`try_map_copy`].
+///
+/// Like [`FillWith`](crate::FillWith), the variants let generic code pass trivial transforms
+/// through the same call as arbitrary closures without losing the `rep` fast paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform<T, F> {
+    /// Elements are copied unchanged.
+    Identity,
+    /// Every element is replaced by the same value.
+    Const(T),
+    /// Every element is replaced by the result of the function.
+    Map(F),
+}
+
+impl<T> Transform<T, fn(T) -> T> {
+    /// Identity transform that does not need to name a function type.
+    #[inline]
+    pub const fn identity() -> Self {
+        Transform::Identity
+    }
+
+    /// Constant transform that does not need to name a function type.
+    #[inline]
+    pub const fn constant(value: T) -> Self {
+        Transform::Const(value)
+    }
+}
+
+/// Write the transformed elements of `src` into `dst`.
+///
+/// [`Transform::Identity`] uses `rep movs` and [`Transform::Const`] uses `rep stos`, other
+/// transforms use a simple loop that the compiler can vectorize.
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+#[inline]
+pub fn map_copy<T: RegisterType, F: Fn(T) -> T>(
+    src: &[T],
+    dst: &mut [T],
+    transform: Transform<T, F>,
+) {
+    if try_map_copy(src, dst, transform).is_err() {
+        panic!("length mismatch");
+    }
+}
+
+/// Same as `map_copy`, but returns [`Error::LenMismatch`] instead of panicking if `src` and
+/// `dst` have different lengths.
+#[inline]
+pub fn try_map_copy<T: RegisterType, F: Fn(T) -> T>(
+    src: &[T],
+    dst: &mut [T],
+    transform: Transform<T, F>,
+) -> Result<(), Error> {
+    if src.len() != dst.len() {
+        return Err(Error::LenMismatch {
+            src_len: src.len(),
+            dst_len: dst.len(),
+        });
+    }
+    match transform {
+        Transform::Identity => unsafe { rep_movs(src.as_ptr(), dst.as_mut_ptr(), dst.len()) },
+        Transform::Const(value) => unsafe { rep_stos(value, dst.as_mut_ptr(), dst.len()) },
+        Transform::Map(f) => {
+            for (dst, &src) in dst.iter_mut().zip(src) {
+                *dst = f(src);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Swap the contents of `a` and `b` using three `rep movsb` copies through a
+/// [scratch buffer](crate::with_scratch).
+///
+/// Slices longer than the scratch limit are swapped in chunks of at most that size.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths.
+#[cfg(all(feature = "std", not(feature = "no-panic")))]
+pub fn swap_slices<T: Copy>(a: &mut [T], b: &mut [T]) {
+    if try_swap_slices(a, b).is_err() {
+        panic!("length mismatch");
+    }
+}
+
+/// Same as `swap_slices`, but returns [`Error::LenMismatch`] without modifying the slices
+/// instead of panicking if `a` and `b` have different lengths.
+#[cfg(feature = "std")]
+pub fn try_swap_slices<T: Copy>(a: &mut [T], b: &mut [T]) -> Result<(), Error> {
+    if a.len() != b.len() {
+        return Err(Error::LenMismatch {
+            src_len: a.len(),
+            dst_len: b.len(),
+        });
+    }
+    let size = core::mem::size_of::<T>();
+    let chunk_len = (crate::scratch::scratch_limit() / size.max(1)).max(1);
+    for (a, b) in a.chunks_mut(chunk_len).zip(b.chunks_mut(chunk_len)) {
+        let len = core::mem::size_of_val(a);
+        let (a, b) = (a.as_mut_ptr().cast::<u8>(), b.as_mut_ptr().cast::<u8>());
+        crate::with_scratch(len, |tmp| unsafe {
+            rep_movs(a, tmp.as_mut_ptr(), len);
+            rep_movs(b, a, len);
+            rep_movs(tmp.as_ptr(), b, len);
+        });
+    }
+    Ok(())
+}
+
+/// Copy `src` into `dst` using `rep movs`, inlined into the caller.
+///
+/// Used by [`rep_copy_inline!`](macro@crate::rep_copy_inline), see there for details.
+#[doc(hidden)]
+#[cfg(not(feature = "no-panic"))]
+#[inline(always)]
+pub fn rep_copy_inline<T: Copy>(src: &[T], dst: &mut [T]) {
+    assert_eq!(src.len(), dst.len(), "length mismatch");
+    unsafe { rep_movs(src.as_ptr(), dst.as_mut_ptr(), dst.len()) }
+}
+
+/// Out of line version of [`rep_copy_inline`](fn@rep_copy_inline), a single symbol per element
+/// type that is shared by all callers.
+///
+/// Used by [`rep_copy_outline!`](macro@crate::rep_copy_outline), see there for details.
+#[doc(hidden)]
+#[cfg(not(feature = "no-panic"))]
+#[inline(never)]
+pub fn rep_copy_outline<T: Copy>(src: &[T], dst: &mut [T]) {
+    rep_copy_inline(src, dst)
+}
+
+/// Copy the slice `$src` into the slice `$dst` using `rep movs` instructions inlined at the call
+/// site.
+///
+/// Inlining avoids the call overhead, at the cost of a few bytes of code for the length check and
+/// register setup at every call site. See
+/// [`rep_copy_outline!`](macro@crate::rep_copy_outline) for the alternative.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths, [`copy_exact_or_err`] returns an error instead.
+#[cfg(not(feature = "no-panic"))]
+#[macro_export]
+macro_rules! rep_copy_inline {
+    ($src:expr, $dst:expr $(,)?) => {
+        $crate::rep_copy_inline($src, $dst)
+    };
+}
+
+/// Copy the slice `$src` into the slice `$dst` by calling a shared out of line function that
+/// uses `rep movs` instructions.
+///
+/// All call sites with the same element type share one copy of the code, which reduces
+/// instruction cache pressure in code with many copies, for example in the opcode handlers of an
+/// interpreter.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths, [`copy_exact_or_err`] returns an error instead.
+#[cfg(not(feature = "no-panic"))]
+#[macro_export]
+macro_rules! rep_copy_outline {
+    ($src:expr, $dst:expr $(,)?) => {
+        $crate::rep_copy_outline($src, $dst)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_truncated() {
+        let mut dst = [0_u8; 4];
+        let outcome = copy_truncated(b"abcdef", &mut dst);
+        assert_eq!(
+            outcome,
+            CopyOutcome {
+                copied: 4,
+                truncated: true
+            }
+        );
+        assert_eq!(&dst, b"abcd");
+
+        let outcome = copy_truncated(b"xy", &mut dst);
+        assert_eq!(
+            outcome,
+            CopyOutcome {
+                copied: 2,
+                truncated: false
+            }
+        );
+        assert_eq!(&dst, b"xycd");
+    }
+
+    #[test]
+    fn test_copy_exact_or_err() {
+        let mut dst = [0_u32; 3];
+        assert_eq!(copy_exact_or_err(&[1, 2, 3], &mut dst), Ok(()));
+        assert_eq!(dst, [1, 2, 3]);
+        assert_eq!(
+            copy_exact_or_err(&[4, 5], &mut dst),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 3
+            })
+        );
+        assert_eq!(dst, [1, 2, 3]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", not(feature = "no-panic")))]
+    fn test_swap_slices() {
+        let mut a = (0..10_000_u32).collect::<Vec<_>>();
+        let mut b = (10_000..20_000_u32).collect::<Vec<_>>();
+        swap_slices(&mut a, &mut b);
+        assert!(a.iter().copied().eq(10_000..20_000));
+        assert!(b.iter().copied().eq(0..10_000));
+        let (left, right) = a.split_at_mut(5_000);
+        swap_slices(&mut left[1..4], &mut right[..3]);
+        assert_eq!(a[..5], [10_000, 15_000, 15_001, 15_002, 10_004]);
+        swap_slices::<u8>(&mut [], &mut []);
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", not(feature = "no-panic")))]
+    #[should_panic(expected = "length mismatch")]
+    fn test_swap_slices_panic() {
+        swap_slices(&mut [1_u8, 2], &mut [0; 3]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_rep_copy_macros() {
+        let src = [1_u16, 2, 3, 4];
+        let mut dst = [0_u16; 4];
+        crate::rep_copy_inline!(&src, &mut dst);
+        assert_eq!(dst, src);
+        let mut dst = [0_u16; 4];
+        crate::rep_copy_outline!(&src[1..], &mut dst[..3]);
+        assert_eq!(dst, [2, 3, 4, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_rep_copy_outline_panic() {
+        crate::rep_copy_outline!(&[1_u8, 2], &mut [0; 3]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_bytes_between() {
+        let src = [0x0403_0201_u32, 0x0807_0605];
+        let mut bytes = [0_u8; 8];
+        copy_bytes_between(&src, &mut bytes);
+        assert_eq!(bytes[..4], src[0].to_ne_bytes());
+        assert_eq!(bytes[4..], src[1].to_ne_bytes());
+
+        let mut words = [0_u16; 4];
+        copy_bytes_between(&bytes, &mut words);
+        let mut roundtrip = [0_u32; 2];
+        copy_bytes_between(&words, &mut roundtrip);
+        assert_eq!(roundtrip, src);
+
+        copy_bytes_between::<u64, f32>(&[], &mut []);
+    }
+
+    #[test]
+    #[should_panic(expected = "byte length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_bytes_between_panic() {
+        copy_bytes_between(&[1_u32, 2], &mut [0_u16; 3]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_map_copy() {
+        let src = [1_u32, 2, 3, 4];
+        let mut dst = [0_u32; 4];
+        map_copy(&src, &mut dst, Transform::identity());
+        assert_eq!(dst, src);
+        map_copy(&src, &mut dst, Transform::constant(9));
+        assert_eq!(dst, [9; 4]);
+        map_copy(&src, &mut dst, Transform::Map(|x: u32| x * 10));
+        assert_eq!(dst, [10, 20, 30, 40]);
+        map_copy(&[], &mut [], Transform::Map(|x: f64| -x));
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_map_copy_panic() {
+        map_copy(&[1_u8], &mut [], Transform::identity());
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let mut bytes = [0_u8; 4];
+        assert_eq!(
+            try_copy_bytes_between(&[1_u32, 2], &mut [0_u16; 3]),
+            Err(Error::LenMismatch {
+                src_len: 8,
+                dst_len: 6
+            })
+        );
+        assert_eq!(try_copy_bytes_between(&[u32::MAX], &mut bytes), Ok(()));
+        assert_eq!(bytes, [0xFF; 4]);
+        assert_eq!(
+            try_map_copy(&[1_u8], &mut bytes, Transform::identity()),
+            Err(Error::LenMismatch {
+                src_len: 1,
+                dst_len: 4
+            })
+        );
+        assert_eq!(
+            try_map_copy(&[1, 2, 3, 4], &mut bytes, Transform::Map(|x: u8| x + 1)),
+            Ok(())
+        );
+        assert_eq!(bytes, [2, 3, 4, 5]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_try_swap_slices() {
+        let (mut a, mut b) = ([1_u8, 2], [0; 3]);
+        assert_eq!(
+            try_swap_slices(&mut a, &mut b),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 3
+            })
+        );
+        assert_eq!((a, b), ([1, 2], [0; 3]));
+        assert_eq!(try_swap_slices(&mut a, &mut b[1..]), Ok(()));
+        assert_eq!((a, b), ([0, 0], [0, 1, 2]));
+    }
+}
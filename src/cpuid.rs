@@ -0,0 +1,259 @@
+//! Classification of the processor microarchitecture using the `cpuid` instruction.
+
+use crate::Thresholds;
+use std::sync::OnceLock;
+
+/// Processor microarchitecture, as far as it affects the performance of string instructions.
+///
+/// Use [`Microarch::detect`] to classify the current processor. Hybrid processors are
+/// classified by their performance cores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum Microarch {
+    /// Skylake and its Kaby Lake, Coffee Lake and Cascade Lake derivatives, without FSRM.
+    Skylake,
+    /// Ice Lake and Tiger Lake, the first cores with fast short `rep movsb`.
+    Icelake,
+    /// Alder Lake and Sapphire Rapids performance cores, with fast zero length `rep movsb`.
+    GoldenCove,
+    /// Raptor Lake and Emerald Rapids performance cores, with fast short `rep cmpsb/scasb`.
+    RaptorCove,
+    Zen,
+    Zen2,
+    Zen3,
+    Zen4,
+    Zen5,
+    Unknown,
+}
+
+impl Microarch {
+    /// Classify the processor from its vendor string and the family and model numbers, including
+    /// the extended family and model bits.
+    fn classify(vendor: &[u8; 12], family: u32, model: u32) -> Self {
+        match (vendor, family) {
+            (b"GenuineIntel", 6) => match model {
+                0x4E | 0x5E | 0x55 | 0x8E | 0x9E | 0xA5 | 0xA6 => Microarch::Skylake,
+                0x6A | 0x6C | 0x7D | 0x7E | 0x8C | 0x8D => Microarch::Icelake,
+                0x8F | 0x97 | 0x9A => Microarch::GoldenCove,
+                0xB7 | 0xBA | 0xBF | 0xCF => Microarch::RaptorCove,
+                _ => Microarch::Unknown,
+            },
+            (b"AuthenticAMD", 0x17) => match model {
+                0x00..=0x2F => Microarch::Zen,
+                _ => Microarch::Zen2,
+            },
+            (b"AuthenticAMD", 0x19) => match model {
+                0x10..=0x1F | 0x60..=0x7F | 0xA0..=0xAF => Microarch::Zen4,
+                _ => Microarch::Zen3,
+            },
+            (b"AuthenticAMD", 0x1A) => Microarch::Zen5,
+            _ => Microarch::Unknown,
+        }
+    }
+
+    /// Detect the microarchitecture of the current processor.
+    ///
+    /// The result is cached after the first call. Returns [`Microarch::Unknown`] on other
+    /// architectures and under Miri.
+    pub fn detect() -> Self {
+        static DETECTED: OnceLock<Microarch> = OnceLock::new();
+        *DETECTED.get_or_init(|| {
+            #[cfg(all(target_arch = "x86_64", not(miri)))]
+            {
+                use core::arch::x86_64::__cpuid;
+
+                let leaf0 = __cpuid(0);
+                let mut vendor = [0_u8; 12];
+                vendor[0..4].copy_from_slice(&leaf0.ebx.to_le_bytes());
+                vendor[4..8].copy_from_slice(&leaf0.edx.to_le_bytes());
+                vendor[8..12].copy_from_slice(&leaf0.ecx.to_le_bytes());
+                if leaf0.eax < 1 {
+                    return Microarch::Unknown;
+                }
+                let signature = __cpuid(1).eax;
+                let base_family = (signature >> 8) & 0xF;
+                let base_model = (signature >> 4) & 0xF;
+                let family = match base_family {
+                    0xF => base_family + ((signature >> 20) & 0xFF),
+                    _ => base_family,
+                };
+                let model = match base_family {
+                    0x6 | 0xF => base_model | (((signature >> 16) & 0xF) << 4),
+                    _ => base_model,
+                };
+                Microarch::classify(&vendor, family, model)
+            }
+            #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+            Microarch::Unknown
+        })
+    }
+
+    /// Whether this is an Intel microarchitecture.
+    pub fn is_intel(self) -> bool {
+        matches!(
+            self,
+            Microarch::Skylake | Microarch::Icelake | Microarch::GoldenCove | Microarch::RaptorCove
+        )
+    }
+
+    /// Whether this is an AMD microarchitecture.
+    pub fn is_amd(self) -> bool {
+        matches!(
+            self,
+            Microarch::Zen | Microarch::Zen2 | Microarch::Zen3 | Microarch::Zen4 | Microarch::Zen5
+        )
+    }
+
+    /// Whether `rep movsb` is fast also for short copies of up to 128 bytes (FSRM).
+    pub fn has_fast_rep_movsb(self) -> bool {
+        matches!(
+            self,
+            Microarch::Icelake
+                | Microarch::GoldenCove
+                | Microarch::RaptorCove
+                | Microarch::Zen3
+                | Microarch::Zen4
+                | Microarch::Zen5
+        )
+    }
+
+    /// Default thresholds for this microarchitecture.
+    ///
+    /// Processors without fast short `rep movsb` pay a startup cost of a few dozen cycles per
+    /// instruction, which dominates short operations. `rep cmps` and `rep scas` are microcoded
+    /// and slower than scalar loops for short inputs on all processors. For unknown processors
+    /// no thresholds are applied.
+    ///
+    /// Only the Raptor Cove row is measured, all other rows are estimates based on the features
+    /// the processors report, see [`CpuFlags`]. `Thresholds::calibrated` measures the copy and
+    /// fill thresholds on the current processor instead.
+    pub(crate) fn thresholds(self) -> Thresholds {
+        // Raptor Cove: measured on an Emerald Rapids "Intel(R) Xeon(R) Processor" (family 6,
+        // model 0xCF) under KVM. `Thresholds::calibrated` reported a copy and fill crossover of 0
+        // in three runs. Comparing `position` and `mismatch` of the `RepBackend` and the
+        // `ScalarBackend` with `measure_throughput` for 8 to 1024 bytes, the `rep` instructions
+        // were slower at 8 and 16 bytes (0.6 vs 1.1 GB/s for `position` at 8 bytes) and faster
+        // from 32 bytes on, in three runs.
+        let (copy, fill, search) = match self {
+            Microarch::Skylake | Microarch::Zen | Microarch::Zen2 => (256, 128, 64),
+            Microarch::Icelake | Microarch::Zen3 => (0, 128, 64),
+            Microarch::GoldenCove | Microarch::Zen4 | Microarch::Zen5 => (0, 0, 64),
+            Microarch::RaptorCove => (0, 0, 32),
+            Microarch::Unknown => (0, 0, 0),
+        };
+        Thresholds {
+            copy,
+            fill,
+            position: search,
+            mismatch: search,
+        }
+    }
+}
+
+/// Processor features affecting the string instructions, as reported by `cpuid` leaf 7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CpuFlags {
+    /// Enhanced `rep movsb` and `rep stosb` (ERMS).
+    pub erms: bool,
+    /// Fast short `rep movsb` (FSRM).
+    pub fsrm: bool,
+    /// Fast zero length `rep movsb` (FZLRM).
+    pub fast_zero_len_movsb: bool,
+    /// Fast short `rep stosb` (FSRS).
+    pub fast_short_stosb: bool,
+    /// Fast short `rep cmpsb` and `rep scasb` (FSRCS).
+    pub fast_short_cmpsb_scasb: bool,
+}
+
+impl CpuFlags {
+    /// Detect the flags of the current processor.
+    ///
+    /// All flags are false on other architectures and under Miri.
+    pub fn detect() -> Self {
+        #[cfg(all(target_arch = "x86_64", not(miri)))]
+        {
+            use core::arch::x86_64::{__cpuid, __cpuid_count};
+
+            let mut flags = CpuFlags::default();
+            if __cpuid(0).eax < 7 {
+                return flags;
+            }
+            let leaf7 = __cpuid_count(7, 0);
+            flags.erms = leaf7.ebx & (1 << 9) != 0;
+            flags.fsrm = leaf7.edx & (1 << 4) != 0;
+            if leaf7.eax >= 1 {
+                let subleaf1 = __cpuid_count(7, 1).eax;
+                flags.fast_zero_len_movsb = subleaf1 & (1 << 10) != 0;
+                flags.fast_short_stosb = subleaf1 & (1 << 11) != 0;
+                flags.fast_short_cmpsb_scasb = subleaf1 & (1 << 12) != 0;
+            }
+            flags
+        }
+        #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+        CpuFlags::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let intel = b"GenuineIntel";
+        let amd = b"AuthenticAMD";
+        assert_eq!(Microarch::classify(intel, 6, 0x5E), Microarch::Skylake);
+        assert_eq!(Microarch::classify(intel, 6, 0x7E), Microarch::Icelake);
+        assert_eq!(Microarch::classify(intel, 6, 0x97), Microarch::GoldenCove);
+        assert_eq!(Microarch::classify(intel, 6, 0xB7), Microarch::RaptorCove);
+        assert_eq!(Microarch::classify(intel, 6, 0x1A), Microarch::Unknown);
+        assert_eq!(Microarch::classify(intel, 0x17, 0x71), Microarch::Unknown);
+        assert_eq!(Microarch::classify(amd, 0x17, 0x08), Microarch::Zen);
+        assert_eq!(Microarch::classify(amd, 0x17, 0x71), Microarch::Zen2);
+        assert_eq!(Microarch::classify(amd, 0x19, 0x21), Microarch::Zen3);
+        assert_eq!(Microarch::classify(amd, 0x19, 0x61), Microarch::Zen4);
+        assert_eq!(Microarch::classify(amd, 0x1A, 0x44), Microarch::Zen5);
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Microarch::detect(), Microarch::detect());
+        if cfg!(not(target_arch = "x86_64")) || cfg!(miri) {
+            assert_eq!(Microarch::detect(), Microarch::Unknown);
+        }
+    }
+
+    #[test]
+    fn test_cpu_flags() {
+        let flags = CpuFlags::detect();
+        assert_eq!(flags, CpuFlags::detect());
+        if Microarch::detect().has_fast_rep_movsb() {
+            assert!(flags.erms && flags.fsrm);
+        }
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert!(Microarch::Zen2.is_amd() && !Microarch::Zen2.is_intel());
+        assert!(Microarch::Skylake.is_intel() && !Microarch::Skylake.is_amd());
+        assert!(!Microarch::Unknown.is_intel() && !Microarch::Unknown.is_amd());
+        assert!(!Microarch::Skylake.has_fast_rep_movsb());
+        assert!(!Microarch::Zen2.has_fast_rep_movsb());
+        assert!(Microarch::Icelake.has_fast_rep_movsb());
+        assert!(Microarch::Zen3.has_fast_rep_movsb());
+        assert!(!Microarch::Unknown.has_fast_rep_movsb());
+    }
+
+    #[test]
+    fn test_thresholds() {
+        assert_eq!(Microarch::Unknown.thresholds(), Thresholds::NONE);
+        let raptor_cove = Microarch::RaptorCove.thresholds();
+        assert_eq!((raptor_cove.copy, raptor_cove.fill), (0, 0));
+        assert!(raptor_cove.position < Microarch::GoldenCove.thresholds().position);
+        let skylake = Microarch::Skylake.thresholds();
+        assert!(skylake.copy > Microarch::Icelake.thresholds().copy);
+        assert_eq!(skylake.position, skylake.mismatch);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::rep_cmps;
+
+/// Return the index of the first inner slice that differs between `a` and `b`.
+///
+/// The lengths of all inner slices are compared before any of their contents, so that batches
+/// differing in the length of a later element are rejected without touching the data of the
+/// earlier ones. Only inner slices before the first length difference are then compared using
+/// `rep cmpsb`. If one outer slice is a prefix of the other, the length of the shorter one is
+/// returned.
+pub fn deep_mismatch(a: &[&[u8]], b: &[&[u8]]) -> Option<usize> {
+    let len = a.len().min(b.len());
+    let bound = a[..len]
+        .iter()
+        .zip(&b[..len])
+        .position(|(a, b)| a.len() != b.len())
+        .unwrap_or(len);
+    let data = a[..bound]
+        .iter()
+        .zip(&b[..bound])
+        .position(|(a, b)| unsafe { rep_cmps(a.as_ptr(), b.as_ptr(), a.len()) }.is_some());
+    data.or((bound < len || a.len() != b.len()).then_some(bound))
+}
+
+/// Check whether `a` and `b` contain the same number of inner slices with equal contents.
+///
+/// See [`deep_mismatch`] for the order in which lengths and contents are compared.
+pub fn deep_eq(a: &[&[u8]], b: &[&[u8]]) -> bool {
+    a.len() == b.len() && deep_mismatch(a, b).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deep_mismatch() {
+        let a: &[&[u8]] = &[b"ls", b"-la", b"/tmp"];
+        assert_eq!(deep_mismatch(a, a), None);
+        assert_eq!(deep_mismatch(a, &[b"ls", b"-lh", b"/tmp"]), Some(1));
+        assert_eq!(deep_mismatch(a, &[b"ls", b"-lh", b"/var/tmp"]), Some(1));
+        assert_eq!(deep_mismatch(a, &[b"ls", b"-la", b"/var"]), Some(2));
+        assert_eq!(deep_mismatch(a, &[b"ls", b"-la"]), Some(2));
+        assert_eq!(deep_mismatch(&[], a), Some(0));
+        assert_eq!(deep_mismatch(&[], &[]), None);
+        assert_eq!(deep_mismatch(&[b""], &[b""]), None);
+    }
+
+    #[test]
+    fn test_deep_eq() {
+        let a: &[&[u8]] = &[b"abc", b"", b"def"];
+        assert!(deep_eq(a, &[b"abc", b"", b"def"]));
+        assert!(!deep_eq(a, &[b"abc", b"", b"deg"]));
+        assert!(!deep_eq(a, &[b"abc", b""]));
+        assert!(!deep_eq(a, &[b"abc", b"d", b"ef"]));
+        assert!(deep_eq(&[], &[]));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{rep_cmps, Error};
+
+/// Summary of the differences between two equally long byte buffers, see [`try_diff_summary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffSummary {
+    /// Index of the first differing byte, `None` if the buffers are equal.
+    pub first_diff: Option<usize>,
+    /// Index of the last differing byte, `None` if the buffers are equal.
+    pub last_diff: Option<usize>,
+    /// Number of positions at which both buffers contain the same byte.
+    pub equal_bytes: usize,
+}
+
+impl DiffSummary {
+    /// Length of the range from the first to the last differing byte, which would need to be
+    /// stored by a delta encoding of `b` relative to `a`.
+    pub fn diff_len(&self) -> usize {
+        match (self.first_diff, self.last_diff) {
+            (Some(first), Some(last)) => last - first + 1,
+            _ => 0,
+        }
+    }
+}
+
+/// Compare `a` and `b` and summarize where they differ.
+///
+/// The first difference is found using `rep cmps`, the last one by comparing backwards from the
+/// end. The equal bytes in between are counted by repeatedly skipping equal runs with `rep cmps`,
+/// so that pass only touches the range between the first and last difference.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+pub fn diff_summary(a: &[u8], b: &[u8]) -> DiffSummary {
+    match try_diff_summary(a, b) {
+        Ok(summary) => summary,
+        Err(_) => panic!("length mismatch"),
+    }
+}
+
+/// Same as `diff_summary`, but returns [`Error::LenMismatch`] instead of panicking if `a` and
+/// `b` have different lengths.
+#[inline]
+pub fn try_diff_summary(a: &[u8], b: &[u8]) -> Result<DiffSummary, Error> {
+    let len = a.len();
+    if len != b.len() {
+        return Err(Error::LenMismatch {
+            src_len: len,
+            dst_len: b.len(),
+        });
+    }
+    let Some(first) = (unsafe { rep_cmps(a.as_ptr(), b.as_ptr(), len) }) else {
+        return Ok(DiffSummary {
+            first_diff: None,
+            last_diff: None,
+            equal_bytes: len,
+        });
+    };
+    // `rep_cmps` returns an index within both slices
+    let (a_rest, b_rest) = unsafe { (a.get_unchecked(first..), b.get_unchecked(first..)) };
+    let last = a_rest
+        .iter()
+        .zip(b_rest)
+        .rposition(|(a, b)| a != b)
+        .map_or(first, |index| first + index);
+
+    let mut equal_bytes = first + (len - 1 - last);
+    let mut start = first + 1;
+    while start < last {
+        let remaining = last - start;
+        match unsafe { rep_cmps(a.as_ptr().add(start), b.as_ptr().add(start), remaining) } {
+            Some(index) => {
+                equal_bytes += index;
+                start += index + 1;
+            }
+            None => {
+                equal_bytes += remaining;
+                break;
+            }
+        }
+    }
+    Ok(DiffSummary {
+        first_diff: Some(first),
+        last_diff: Some(last),
+        equal_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "no-panic"))]
+    fn reference_summary(a: &[u8], b: &[u8]) -> DiffSummary {
+        let differs = |(a, b): (&u8, &u8)| a != b;
+        DiffSummary {
+            first_diff: a.iter().zip(b).position(differs),
+            last_diff: a.iter().zip(b).rposition(differs),
+            equal_bytes: a.iter().zip(b).filter(|(a, b)| a == b).count(),
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_diff_summary() {
+        let a = b"the quick brown fox jumps";
+        let b = b"the quack brown fix jumps";
+        let summary = diff_summary(a, b);
+        assert_eq!(
+            summary,
+            DiffSummary {
+                first_diff: Some(6),
+                last_diff: Some(17),
+                equal_bytes: 23
+            }
+        );
+        assert_eq!(summary.diff_len(), 12);
+        assert_eq!(diff_summary(a, a).diff_len(), 0);
+        assert_eq!(diff_summary(b"", b"").equal_bytes, 0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_diff_summary_patterns() {
+        let a = (0..40_u8).collect::<Vec<_>>();
+        for mask in [
+            0_u64,
+            1,
+            1 << 39,
+            0b1010,
+            0xFF00,
+            0x8000_0001,
+            u64::MAX >> 24,
+        ] {
+            let b = a
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| if mask & (1 << i) != 0 { !x } else { x })
+                .collect::<Vec<_>>();
+            assert_eq!(diff_summary(&a, &b), reference_summary(&a, &b), "{mask:#x}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_diff_summary_panic() {
+        diff_summary(b"ab", b"abc");
+    }
+
+    #[test]
+    fn test_try_diff_summary() {
+        assert_eq!(
+            try_diff_summary(b"ab", b"abc"),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 3
+            })
+        );
+        assert_eq!(
+            try_diff_summary(b"abcd", b"xbcy"),
+            Ok(DiffSummary {
+                first_diff: Some(0),
+                last_diff: Some(3),
+                equal_bytes: 2
+            })
+        );
+    }
+}
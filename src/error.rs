@@ -0,0 +1,100 @@
+use core::fmt;
+
+/// Error returned by the fallible operations of this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Source and destination have different lengths.
+    LenMismatch { src_len: usize, dst_len: usize },
+    /// Source and destination overlap in a way the operation does not support.
+    Overlap,
+    /// A pointer is `offset` bytes past the previous multiple of the required alignment.
+    Misaligned { align: usize, offset: usize },
+    /// A length exceeds the limit supported by the operation.
+    LimitExceeded { len: usize, limit: usize },
+    /// The operation is not supported on this processor or platform.
+    Unsupported,
+    /// The input is not well formed at `offset`.
+    Malformed { offset: usize },
+    /// A range starts after its end.
+    InvalidRange { start: usize, end: usize },
+    /// The argument `name` has a value the operation does not support, for example a zero
+    /// stride.
+    InvalidArgument { name: &'static str },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::LenMismatch { src_len, dst_len } => write!(
+                f,
+                "length mismatch: source has {src_len} elements, destination has {dst_len}"
+            ),
+            Error::Overlap => f.write_str("source and destination overlap"),
+            Error::Misaligned { align, offset } => write!(
+                f,
+                "pointer is not aligned to {align} bytes, it is {offset} bytes past the previous \
+                 aligned address"
+            ),
+            Error::LimitExceeded { len, limit } => {
+                write!(f, "length {len} exceeds the limit of {limit}")
+            }
+            Error::Unsupported => f.write_str("operation is not supported on this platform"),
+            Error::Malformed { offset } => write!(f, "malformed input at offset {offset}"),
+            Error::InvalidRange { start, end } => {
+                write!(f, "range start {start} is greater than its end {end}")
+            }
+            Error::InvalidArgument { name } => write!(f, "invalid value of argument `{name}`"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = Error::LenMismatch {
+            src_len: 2,
+            dst_len: 3,
+        };
+        assert_eq!(
+            error.to_string(),
+            "length mismatch: source has 2 elements, destination has 3"
+        );
+        assert_eq!(
+            Error::Misaligned {
+                align: 8,
+                offset: 3
+            }
+            .to_string(),
+            "pointer is not aligned to 8 bytes, it is 3 bytes past the previous aligned address"
+        );
+        assert_eq!(
+            Error::Malformed { offset: 7 }.to_string(),
+            "malformed input at offset 7"
+        );
+        assert_eq!(
+            Error::InvalidRange { start: 5, end: 3 }.to_string(),
+            "range start 5 is greater than its end 3"
+        );
+        assert_eq!(
+            Error::InvalidArgument { name: "stride" }.to_string(),
+            "invalid value of argument `stride`"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_std_error() {
+        let boxed: Box<dyn std::error::Error> = Box::new(Error::Unsupported);
+        assert_eq!(
+            boxed.to_string(),
+            "operation is not supported on this platform"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Byte stuffing as used by the HDLC-like framing of PPP (RFC 1662).
+//!
+//! Every special byte is replaced by the escape byte followed by the special byte with bit 5
+//! flipped, so that for example frame delimiters do not appear inside of the escaped data. The
+//! escape byte itself is always escaped. Clean runs between special bytes are located using
+//! [`position_any`] and copied using `rep movsb`.
+//!
+//! SLIP (RFC 1055), which replaces special bytes with fixed substitutes instead of flipping a
+//! bit, and COBS are not supported.
+
+use crate::{position_any, rep_movs, rep_scas, ByteSet, Error};
+
+/// Bit flipped in escaped bytes.
+const FLIP: u8 = 0x20;
+
+/// Length of `src` after escaping the bytes in `specials` and `escape`.
+pub fn escaped_len(src: &[u8], escape: u8, specials: &ByteSet) -> usize {
+    let specials = specials.with(escape);
+    let mut len = src.len();
+    let mut rest = src;
+    while let Some(index) = position_any(rest, &specials) {
+        len += 1;
+        rest = &rest[index + 1..];
+    }
+    len
+}
+
+/// Copy `src` into `dst`, escaping the bytes in `specials` and `escape`, and return the number
+/// of bytes written, or [`Error::LimitExceeded`] with the required length if `dst` is too
+/// small.
+///
+/// At most twice the length of `src` is required, [`escaped_len`] returns the exact length.
+/// When an error is returned the contents of `dst` are unspecified.
+pub fn try_escape_copy(
+    src: &[u8],
+    dst: &mut [u8],
+    escape: u8,
+    specials: &ByteSet,
+) -> Result<usize, Error> {
+    unsafe { escape_into(src, dst.as_mut_ptr(), dst.len(), escape, specials) }.ok_or_else(|| {
+        Error::LimitExceeded {
+            len: escaped_len(src, escape, specials),
+            limit: dst.len(),
+        }
+    })
+}
+
+/// Escape `src` into the `capacity` bytes at `dst`, returning the number of bytes written or
+/// `None` if `dst` is too small. The bytes at `dst` are only written, never read.
+///
+/// # Safety
+///
+/// `dst` needs to be valid for writes of `capacity` bytes and must not overlap `src`.
+pub(crate) unsafe fn escape_into(
+    src: &[u8],
+    dst: *mut u8,
+    capacity: usize,
+    escape: u8,
+    specials: &ByteSet,
+) -> Option<usize> {
+    let all_specials = specials.with(escape);
+    let (mut read, mut written) = (0, 0);
+    while read < src.len() {
+        let rest = &src[read..];
+        let run = position_any(rest, &all_specials).unwrap_or(rest.len());
+        let escaped = usize::from(run < rest.len());
+        if written + run + 2 * escaped > capacity {
+            return None;
+        }
+        rep_movs(rest.as_ptr(), dst.add(written), run);
+        written += run;
+        if escaped == 1 {
+            dst.add(written).write(escape);
+            dst.add(written + 1).write(rest[run] ^ FLIP);
+            written += 2;
+        }
+        read += run + escaped;
+    }
+    Some(written)
+}
+
+/// Copy `src` into `dst`, escaping the bytes in `specials` and `escape`, and return the number
+/// of bytes written.
+///
+/// # Panics
+///
+/// Panics if `dst` is shorter than the [escaped length](escaped_len) of `src`.
+#[cfg(not(feature = "no-panic"))]
+pub fn escape_copy(src: &[u8], dst: &mut [u8], escape: u8, specials: &ByteSet) -> usize {
+    match try_escape_copy(src, dst, escape, specials) {
+        Ok(written) => written,
+        Err(_) => panic!("destination too small"),
+    }
+}
+
+/// Copy the escaped `src` into `dst`, restoring the escaped bytes, and return the number of
+/// bytes written.
+///
+/// Returns [`Error::Malformed`] if `src` ends with an escape byte, and
+/// [`Error::LimitExceeded`] if `dst` is too small. At most the length of `src` is required.
+/// When an error is returned the contents of `dst` are unspecified.
+pub fn try_unescape_copy(src: &[u8], dst: &mut [u8], escape: u8) -> Result<usize, Error> {
+    let (mut read, mut written) = (0, 0);
+    while read < src.len() {
+        let rest = &src[read..];
+        let run = unsafe { rep_scas(rest.as_ptr(), escape, rest.len()) }.unwrap_or(rest.len());
+        let escaped = usize::from(run < rest.len());
+        if escaped == 1 && run + 1 == rest.len() {
+            return Err(Error::Malformed { offset: read + run });
+        }
+        if written + run + escaped > dst.len() {
+            return Err(Error::LimitExceeded {
+                len: unescaped_len(src, escape),
+                limit: dst.len(),
+            });
+        }
+        unsafe { rep_movs(rest.as_ptr(), dst.as_mut_ptr().add(written), run) };
+        written += run;
+        if escaped == 1 {
+            dst[written] = rest[run + 1] ^ FLIP;
+            written += 1;
+        }
+        read += run + 2 * escaped;
+    }
+    Ok(written)
+}
+
+/// Copy the escaped `src` into `dst`, restoring the escaped bytes, and return the number of
+/// bytes written.
+///
+/// # Panics
+///
+/// Panics if `src` ends with an escape byte or if `dst` is too small.
+#[cfg(not(feature = "no-panic"))]
+pub fn unescape_copy(src: &[u8], dst: &mut [u8], escape: u8) -> usize {
+    match try_unescape_copy(src, dst, escape) {
+        Ok(written) => written,
+        Err(Error::Malformed { .. }) => panic!("trailing escape byte"),
+        Err(_) => panic!("destination too small"),
+    }
+}
+
+/// Length of the well formed escaped `src` after unescaping.
+fn unescaped_len(src: &[u8], escape: u8) -> usize {
+    let mut len = 0;
+    let mut rest = src;
+    while let Some(index) = unsafe { rep_scas(rest.as_ptr(), escape, rest.len()) } {
+        len += index + 1;
+        rest = rest.get(index + 2..).unwrap_or_default();
+    }
+    len + rest.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAG: u8 = 0x7E;
+    const ESCAPE: u8 = 0x7D;
+    const SPECIALS: ByteSet = ByteSet::new(&[FLAG]);
+
+    fn escape(src: &[u8]) -> Vec<u8> {
+        let mut dst = vec![0; 2 * src.len()];
+        let written = try_escape_copy(src, &mut dst, ESCAPE, &SPECIALS).unwrap();
+        assert_eq!(written, escaped_len(src, ESCAPE, &SPECIALS));
+        dst.truncate(written);
+        dst
+    }
+
+    fn unescape(src: &[u8]) -> Vec<u8> {
+        let mut dst = vec![0; src.len()];
+        let written = try_unescape_copy(src, &mut dst, ESCAPE).unwrap();
+        assert_eq!(written, unescaped_len(src, ESCAPE));
+        dst.truncate(written);
+        dst
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(b""), b"");
+        assert_eq!(escape(b"abc"), b"abc");
+        assert_eq!(
+            escape(&[1, FLAG, 2, ESCAPE]),
+            [1, ESCAPE, 0x5E, 2, ESCAPE, 0x5D]
+        );
+        assert_eq!(escape(&[FLAG, FLAG]), [ESCAPE, 0x5E, ESCAPE, 0x5E]);
+        assert_eq!(
+            unescape(&[1, ESCAPE, 0x5E, 2, ESCAPE, 0x5D]),
+            [1, FLAG, 2, ESCAPE]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let src = (0..2000_u32)
+            .map(|i| [FLAG, ESCAPE, b'x', b'y', 0][(i * 7 % 11 % 5) as usize])
+            .collect::<Vec<_>>();
+        for len in [0, 1, 2, 17, 2000] {
+            let escaped = escape(&src[..len]);
+            assert!(!escaped.contains(&FLAG));
+            assert_eq!(unescape(&escaped), &src[..len]);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let src = [FLAG, b'a', FLAG];
+        assert_eq!(
+            try_escape_copy(&src, &mut [0; 4], ESCAPE, &SPECIALS),
+            Err(Error::LimitExceeded { len: 5, limit: 4 })
+        );
+        assert_eq!(
+            try_unescape_copy(&[ESCAPE, 0x5E, b'a', b'b'], &mut [0; 2], ESCAPE),
+            Err(Error::LimitExceeded { len: 3, limit: 2 })
+        );
+        assert_eq!(
+            try_unescape_copy(&[b'a', ESCAPE], &mut [0; 2], ESCAPE),
+            Err(Error::Malformed { offset: 1 })
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_panicking() {
+        let mut dst = [0; 8];
+        assert_eq!(escape_copy(b"a~b", &mut dst, ESCAPE, &SPECIALS), 4);
+        assert_eq!(&dst[..4], b"a}^b");
+        let mut unescaped = [0; 4];
+        assert_eq!(unescape_copy(&dst[..4], &mut unescaped, ESCAPE), 3);
+        assert_eq!(&unescaped[..3], b"a~b");
+    }
+
+    #[test]
+    #[should_panic(expected = "trailing escape byte")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_unescape_panic() {
+        unescape_copy(b"ab}", &mut [0; 3], ESCAPE);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::rep_scas;
+
+/// Return the number of bytes before the first occurrence of `sentinel` in the `max` bytes
+/// starting at `ptr`, or `None` if `sentinel` does not occur within these bytes.
+///
+/// With a `sentinel` of 0 this is a bounded `strlen`, similar to `strnlen` but distinguishing
+/// a missing terminator from a string of exactly `max` bytes. The scan uses `repne scasb`.
+///
+/// # Safety
+///
+/// `ptr` needs to be valid for reads of `max` bytes, even if the sentinel occurs earlier.
+#[inline]
+pub unsafe fn probe_len(ptr: *const u8, max: usize, sentinel: u8) -> Option<usize> {
+    rep_scas(ptr, sentinel, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_len() {
+        let buffer = b"hello\0world\n";
+        unsafe {
+            assert_eq!(probe_len(buffer.as_ptr(), buffer.len(), 0), Some(5));
+            assert_eq!(probe_len(buffer.as_ptr(), 5, 0), None);
+            assert_eq!(probe_len(buffer.as_ptr(), 6, 0), Some(5));
+            assert_eq!(probe_len(buffer.as_ptr(), buffer.len(), b'\n'), Some(11));
+            assert_eq!(probe_len(buffer.as_ptr(), 0, b'h'), None);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{rep_cmps, rep_scas};
+use std::sync::Arc;
+
+/// Heuristic frequency of `byte` in typical text and binary data, lower is rarer.
+const fn frequency_rank(byte: u8) -> u8 {
+    match byte {
+        b' ' => 255,
+        b'e' | b't' => 220,
+        b'a'..=b'z' => 200,
+        0 => 180,
+        b'A'..=b'Z' | b'0'..=b'9' => 150,
+        b'\t' | b'\n' | b'\r' | b',' | b'.' => 120,
+        _ => 50,
+    }
+}
+
+/// Precomputed plan for searching a needle.
+///
+/// Instead of the first byte of the needle, the scan with `repne scasb` looks for the byte that is
+/// expected to be rarest in the haystack, each candidate is then verified using `repe cmpsb`.
+/// The plan can be built in `const` context, for example for a `static`, and is `Copy`, so it can
+/// be built once and used from any number of threads. See [`SharedFinder`] for needles that are
+/// only known at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finder<'n> {
+    needle: &'n [u8],
+    rare: usize,
+}
+
+impl<'n> Finder<'n> {
+    pub const fn new(needle: &'n [u8]) -> Self {
+        let mut rare = 0;
+        let mut i = 1;
+        while i < needle.len() {
+            if frequency_rank(needle[i]) < frequency_rank(needle[rare]) {
+                rare = i;
+            }
+            i += 1;
+        }
+        Self { needle, rare }
+    }
+
+    #[inline]
+    pub const fn needle(&self) -> &'n [u8] {
+        self.needle
+    }
+
+    /// Return the start index of the first occurrence of the needle in `haystack`.
+    ///
+    /// An empty needle matches at index 0.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        let needle = self.needle;
+        if needle.is_empty() {
+            return Some(0);
+        }
+        let end = haystack.len().checked_sub(needle.len())? + 1;
+        let byte = needle[self.rare];
+        // candidates start at `start..end`, their rare byte is `rare` bytes later
+        let mut start = 0;
+        while start < end {
+            let scan = &haystack[start + self.rare..end + self.rare];
+            let candidate = start + unsafe { rep_scas(scan.as_ptr(), byte, scan.len()) }?;
+            let window = &haystack[candidate..candidate + needle.len()];
+            if unsafe { rep_cmps(window.as_ptr(), needle.as_ptr(), needle.len()) }.is_none() {
+                return Some(candidate);
+            }
+            start = candidate + 1;
+        }
+        None
+    }
+}
+
+/// Reference counted version of [`Finder`] that owns its needle.
+///
+/// Cloning only increments the reference count, so a finder built at startup can cheaply be
+/// handed to every worker thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedFinder {
+    needle: Arc<[u8]>,
+    rare: usize,
+}
+
+impl SharedFinder {
+    pub fn new(needle: &[u8]) -> Self {
+        Self {
+            needle: needle.into(),
+            rare: Finder::new(needle).rare,
+        }
+    }
+
+    #[inline]
+    pub fn as_finder(&self) -> Finder<'_> {
+        Finder {
+            needle: &self.needle,
+            rare: self.rare,
+        }
+    }
+
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// See [`Finder::find`].
+    #[inline]
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        self.as_finder().find(haystack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CRLF: Finder<'static> = Finder::new(b"\r\n\r\n");
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_finder() {
+        assert_send_sync::<Finder<'static>>();
+        assert_send_sync::<SharedFinder>();
+        assert_send_sync::<crate::ByteSet>();
+
+        let request = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\nbody";
+        assert_eq!(CRLF.find(request), Some(33));
+        assert_eq!(CRLF.find(b"\r\n\r"), None);
+        assert_eq!(Finder::new(b"").find(b"abc"), Some(0));
+        assert_eq!(Finder::new(b"abc").find(b""), None);
+        assert_eq!(Finder::new(b"x-y").rare, 1);
+        assert_eq!(Finder::new(b"Host:").find(request), Some(16));
+        assert_eq!(Finder::new(b"body").find(request), Some(37));
+    }
+
+    #[test]
+    fn test_finder_oracle() {
+        let haystack = (0..400_u32)
+            .map(|i| b"ab:c"[(i.wrapping_mul(2_654_435_761) >> 30) as usize])
+            .collect::<Vec<_>>();
+        for start in 0..50 {
+            for len in 1..6 {
+                let needle = &haystack[start * 7..start * 7 + len];
+                let expected = haystack.windows(len).position(|w| w == needle);
+                assert_eq!(Finder::new(needle).find(&haystack), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_finder() {
+        let finder = SharedFinder::new(b"needle");
+        let threads = (0..4)
+            .map(|i| {
+                let finder = finder.clone();
+                std::thread::spawn(move || {
+                    let haystack = [b"hay".repeat(i).as_slice(), b"needle"].concat();
+                    finder.find(&haystack)
+                })
+            })
+            .collect::<Vec<_>>();
+        for (i, thread) in threads.into_iter().enumerate() {
+            assert_eq!(thread.join().unwrap(), Some(3 * i));
+        }
+        assert_eq!(finder.needle(), b"needle");
+        assert_eq!(finder.as_finder(), Finder::new(b"needle"));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::IntegerType;
+
+/// Fill `dst` with the sequence `start, start + step, start + 2 * step, ...`.
+///
+/// There is no string instruction producing such a sequence, the loop is written so that
+/// llvm can vectorize it. Overflowing values wrap around.
+#[inline]
+pub fn fill_iota<T: IntegerType>(dst: &mut [T], start: T, step: T) {
+    let mut value = start;
+    for element in dst.iter_mut() {
+        *element = value;
+        value = value.wrapping_add(step);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_iota() {
+        let mut a = [0_u32; 5];
+        fill_iota(&mut a, 0, 1);
+        assert_eq!(a, [0, 1, 2, 3, 4]);
+
+        let mut a = [0_i64; 4];
+        fill_iota(&mut a, 10, -3);
+        assert_eq!(a, [10, 7, 4, 1]);
+
+        let mut a = [0_u8; 4];
+        fill_iota(&mut a, 254, 1);
+        assert_eq!(a, [254, 255, 0, 1]);
+
+        let mut a: [u16; 0] = [];
+        fill_iota(&mut a, 1, 1);
+    }
+}
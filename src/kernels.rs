@@ -0,0 +1,146 @@
+//! Additional kernels that are commonly used next to the string operations.
+//!
+//! These are not backed by string instructions and are only available with the `kernels`
+//! feature.
+
+use crate::Error;
+
+/// Count the occurrences of each byte value in `src`.
+///
+/// The counts are accumulated into four separate tables which are summed at the end, this
+/// avoids the store-to-load dependency when consecutive bytes have the same value.
+pub fn histogram(src: &[u8]) -> [u32; 256] {
+    let mut tables = [[0_u32; 256]; 4];
+    let mut chunks = src.chunks_exact(4);
+    for chunk in &mut chunks {
+        tables[0][chunk[0] as usize] += 1;
+        tables[1][chunk[1] as usize] += 1;
+        tables[2][chunk[2] as usize] += 1;
+        tables[3][chunk[3] as usize] += 1;
+    }
+    for &byte in chunks.remainder() {
+        tables[0][byte as usize] += 1;
+    }
+    let mut result = tables[0];
+    for table in &tables[1..] {
+        for (count, add) in result.iter_mut().zip(table) {
+            *count += add;
+        }
+    }
+    result
+}
+
+macro_rules! combine_into {
+    ($($(#[$attr:meta])* $name:ident, $try_name:ident: $op:tt;)*) => {
+        $($(#[$attr])*
+        ///
+        /// The body is processed in 16 byte chunks, which compile to SSE2 instructions, followed
+        /// by a scalar loop over the remaining bytes.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `dst` and `src` have different lengths.
+        #[cfg(not(feature = "no-panic"))]
+        pub fn $name(dst: &mut [u8], src: &[u8]) {
+            if $try_name(dst, src).is_err() {
+                panic!("length mismatch")
+            }
+        }
+
+        #[doc = concat!("Same as `", stringify!($name), "`, but returns [`Error::LenMismatch`]")]
+        /// instead of panicking if `dst` and `src` have different lengths.
+        pub fn $try_name(dst: &mut [u8], src: &[u8]) -> Result<(), Error> {
+            if dst.len() != src.len() {
+                return Err(Error::LenMismatch {
+                    src_len: src.len(),
+                    dst_len: dst.len(),
+                });
+            }
+            let mut dst_chunks = dst.chunks_exact_mut(16);
+            let mut src_chunks = src.chunks_exact(16);
+            for (d, s) in (&mut dst_chunks).zip(&mut src_chunks) {
+                // exact chunks always convert, the default is never used
+                let mut value = u128::from_ne_bytes((&*d).try_into().unwrap_or_default());
+                value $op u128::from_ne_bytes(s.try_into().unwrap_or_default());
+                d.copy_from_slice(&value.to_ne_bytes());
+            }
+            for (d, s) in dst_chunks.into_remainder().iter_mut().zip(src_chunks.remainder()) {
+                *d $op *s;
+            }
+            Ok(())
+        })*
+    };
+}
+
+combine_into! {
+    /// Combine each byte of `dst` with the corresponding byte of `src` using bitwise xor.
+    xor_into, try_xor_into: ^=;
+    /// Combine each byte of `dst` with the corresponding byte of `src` using bitwise and, for
+    /// example to intersect bitmaps or apply a mask.
+    and_into, try_and_into: &=;
+    /// Combine each byte of `dst` with the corresponding byte of `src` using bitwise or, for
+    /// example to compute the union of bitmaps.
+    or_into, try_or_into: |=;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let counts = histogram(b"abracadabra");
+        assert_eq!(counts[b'a' as usize], 5);
+        assert_eq!(counts[b'b' as usize], 2);
+        assert_eq!(counts[b'r' as usize], 2);
+        assert_eq!(counts[b'c' as usize], 1);
+        assert_eq!(counts[b'd' as usize], 1);
+        assert_eq!(counts.iter().sum::<u32>(), 11);
+
+        assert_eq!(histogram(&[]), [0; 256]);
+        assert_eq!(histogram(&[255; 1000])[255], 1000);
+    }
+
+    #[test]
+    fn test_combine_into() {
+        type CombineFn = fn(&mut [u8], &[u8]) -> Result<(), Error>;
+        type ScalarFn = fn(u8, u8) -> u8;
+        let kernels: [(CombineFn, ScalarFn); 3] = [
+            (try_xor_into, |a, b| a ^ b),
+            (try_and_into, |a, b| a & b),
+            (try_or_into, |a, b| a | b),
+        ];
+        let a = (0..100_u8).map(|i| i.wrapping_mul(37)).collect::<Vec<_>>();
+        let b = (0..100_u8)
+            .map(|i| i.wrapping_mul(91) ^ 0x5A)
+            .collect::<Vec<_>>();
+        for (kernel, op) in kernels {
+            for len in [0, 1, 15, 16, 17, 33, 100] {
+                let mut dst = a[..len].to_vec();
+                assert_eq!(kernel(&mut dst, &b[..len]), Ok(()));
+                let expected = a[..len].iter().zip(&b[..len]).map(|(&x, &y)| op(x, y));
+                assert!(dst.iter().copied().eq(expected));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_xor_into_panic() {
+        xor_into(&mut [0; 3], &[0; 4]);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let mut dst = [1_u8; 3];
+        assert_eq!(
+            try_or_into(&mut dst, &[2; 4]),
+            Err(Error::LenMismatch {
+                src_len: 4,
+                dst_len: 3
+            })
+        );
+        assert_eq!(dst, [1; 3]);
+    }
+}
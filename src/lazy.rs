@@ -0,0 +1,178 @@
+use crate::{rep_stos, Error};
+
+#[cfg(all(target_os = "linux", not(miri)))]
+mod os {
+    use core::ffi::{c_int, c_long, c_void};
+
+    const SC_PAGESIZE: c_int = 30;
+    const MADV_DONTNEED: c_int = 4;
+    const MADV_POPULATE_WRITE: c_int = 23;
+
+    extern "C" {
+        fn sysconf(name: c_int) -> c_long;
+        fn madvise(addr: *mut c_void, len: usize, advice: c_int) -> c_int;
+    }
+
+    /// Size of a memory page, or `None` if it can not be determined.
+    pub(super) fn page_size() -> Option<usize> {
+        let size = unsafe { sysconf(SC_PAGESIZE) };
+        usize::try_from(size)
+            .ok()
+            .filter(|size| size.is_power_of_two())
+    }
+
+    /// Release the pages at `addr` so that they read as zero on the next access, and return
+    /// whether that succeeded.
+    ///
+    /// # Safety
+    ///
+    /// `addr` needs to be page aligned and the range needs to be part of a private anonymous
+    /// mapping that is valid for writes.
+    pub(super) unsafe fn discard_pages(addr: *mut u8, len: usize) -> bool {
+        madvise(addr.cast(), len, MADV_DONTNEED) == 0
+    }
+
+    /// Fault in the pages at `addr` as writable without changing their contents, and return
+    /// whether that succeeded. Requires Linux 5.14 or later.
+    ///
+    /// # Safety
+    ///
+    /// `addr` needs to be page aligned and the range needs to be mapped.
+    pub(super) unsafe fn populate_pages(addr: *mut u8, len: usize) -> bool {
+        madvise(addr.cast(), len, MADV_POPULATE_WRITE) == 0
+    }
+}
+
+/// Fill `dst` with `value`, releasing whole pages back to the operating system instead of writing
+/// them if `value` is zero.
+///
+/// On Linux the page aligned whole pages of a zero fill are discarded using
+/// `madvise(MADV_DONTNEED)`, so that they are lazily replaced by zero pages on their next access,
+/// and only the partial pages at the start and end are written using `rep stosb`. This avoids
+/// spending memory bandwidth on large arenas that are mostly not touched again. Non-zero fills,
+/// ranges without a whole page, other operating systems and failing `madvise` calls use `rep
+/// stosb` for the whole range.
+///
+/// # Safety
+///
+/// Discarded pages only read as zero in private anonymous mappings, in file backed or shared
+/// mappings they would instead be reloaded from the underlying object. `dst` therefore needs to
+/// be part of a private anonymous mapping, as is the case for memory from the global allocator
+/// or from `mmap` with `MAP_PRIVATE | MAP_ANONYMOUS`.
+pub unsafe fn fill_lazy(dst: &mut [u8], value: u8) {
+    let len = dst.len();
+    let ptr = dst.as_mut_ptr();
+    #[cfg(all(target_os = "linux", not(miri)))]
+    if value == 0 {
+        if let Some(page) = os::page_size() {
+            let head = ptr.align_offset(page).min(len);
+            let pages = (len - head) & !(page - 1);
+            if pages > 0 && os::discard_pages(ptr.add(head), pages) {
+                rep_stos(0_u8, ptr, head);
+                let end = head + pages;
+                rep_stos(0_u8, ptr.add(end), len - end);
+                return;
+            }
+        }
+    }
+    rep_stos(value, ptr, len)
+}
+
+/// Fault in the pages of `dst` as writable without changing its contents, so that a following
+/// large copy into `dst` does not stall on page faults.
+///
+/// On Linux all pages are populated with a single `madvise(MADV_POPULATE_WRITE)` call. On older
+/// kernels and other operating systems one byte every `stride` bytes and the last byte are read
+/// and written back, `stride` should therefore be at most the page size.
+///
+/// # Panics
+///
+/// Panics if `stride` is zero.
+#[cfg(not(feature = "no-panic"))]
+pub fn prefault(dst: &mut [u8], stride: usize) {
+    if try_prefault(dst, stride).is_err() {
+        panic!("stride must be non-zero");
+    }
+}
+
+/// Same as `prefault`, but returns [`Error::InvalidArgument`] instead of panicking if `stride`
+/// is zero.
+pub fn try_prefault(dst: &mut [u8], stride: usize) -> Result<(), Error> {
+    if stride == 0 {
+        return Err(Error::InvalidArgument { name: "stride" });
+    }
+    let len = dst.len();
+    if len == 0 {
+        return Ok(());
+    }
+    let ptr = dst.as_mut_ptr();
+    #[cfg(all(target_os = "linux", not(miri)))]
+    if let Some(page) = os::page_size() {
+        let offset = ptr as usize & (page - 1);
+        // The start of the first page may lie outside of `dst`, so `sub` can not be used here.
+        if unsafe { os::populate_pages(ptr.wrapping_sub(offset), offset + len) } {
+            return Ok(());
+        }
+    }
+    for index in (0..len).step_by(stride).chain([len - 1]) {
+        unsafe {
+            let byte = ptr.add(index);
+            byte.write_volatile(byte.read_volatile());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::alloc::{alloc, dealloc, Layout};
+
+    #[test]
+    fn test_fill_lazy() {
+        let layout = Layout::from_size_align(64 << 10, 4096).unwrap();
+        let base = unsafe { alloc(layout) };
+        assert!(!base.is_null());
+        let buf = unsafe { core::slice::from_raw_parts_mut(base, layout.size()) };
+        for (start, end) in [(0, 64 << 10), (100, 40000), (4096, 8192), (10, 20), (0, 0)] {
+            buf.fill(0xAB);
+            unsafe { fill_lazy(&mut buf[start..end], 0) };
+            assert!(buf[start..end].iter().all(|&b| b == 0), "{start}..{end}");
+            assert!(buf[..start].iter().all(|&b| b == 0xAB));
+            assert!(buf[end..].iter().all(|&b| b == 0xAB));
+        }
+        unsafe { fill_lazy(&mut buf[1..], 0x42) };
+        assert!(buf[0] == 0xAB && buf[1..].iter().all(|&b| b == 0x42));
+        unsafe { dealloc(base, layout) };
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_prefault() {
+        let expected = (0..100_000).map(|i| i as u8).collect::<Vec<_>>();
+        let mut buf = expected.clone();
+        for (start, stride) in [(0, 4096), (1, 4096), (3, 7), (99_999, 1)] {
+            prefault(&mut buf[start..], stride);
+            assert_eq!(buf, expected);
+        }
+        prefault(&mut [], 4096);
+    }
+
+    #[test]
+    #[should_panic(expected = "stride must be non-zero")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_prefault_panic() {
+        prefault(&mut [0; 4], 0);
+    }
+
+    #[test]
+    fn test_try_prefault() {
+        let mut buf = [7_u8; 10];
+        assert_eq!(
+            try_prefault(&mut buf, 0),
+            Err(Error::InvalidArgument { name: "stride" })
+        );
+        assert_eq!(try_prefault(&mut buf, 3), Ok(()));
+        assert_eq!(buf, [7; 10]);
+    }
+}
@@ -1,7 +1,134 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+mod align;
+#[cfg(feature = "search")]
+mod ascii;
 mod assembly;
+#[cfg(feature = "audio")]
+pub mod audio;
+#[cfg(feature = "dispatch")]
+pub mod backend;
+#[cfg(feature = "search")]
+mod binary;
+mod bitmap;
+pub mod bits;
+#[cfg(feature = "search")]
+mod block;
+#[cfg(feature = "simd")]
+mod bswap;
+mod byteset;
+#[cfg(feature = "dispatch")]
+mod capabilities;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "dispatch")]
+mod config;
+mod copy;
+#[cfg(feature = "dispatch")]
+mod cpuid;
+#[cfg(feature = "search")]
+mod deep;
+#[cfg(feature = "search")]
+mod diff;
+mod error;
+#[cfg(feature = "io")]
+mod escape;
+mod ffi;
+#[cfg(feature = "search")]
+mod finder;
+mod iota;
+#[cfg(feature = "kernels")]
+pub mod kernels;
+#[cfg(feature = "mem")]
+mod lazy;
+mod masked;
+#[cfg(feature = "measure")]
+pub mod measure;
+mod nontemporal;
+mod ordered;
+#[cfg(feature = "std")]
+mod outbuf;
+mod overlap;
+#[cfg(feature = "io")]
+mod packet;
+mod pod;
+pub mod prelude;
+#[cfg(feature = "io")]
+mod publish;
+#[cfg(feature = "io")]
+mod ring;
+mod rows;
+#[cfg(feature = "search")]
+mod run;
+#[cfg(feature = "std")]
+mod scratch;
+#[cfg(feature = "io")]
+mod seqlock;
+#[cfg(all(feature = "io", target_os = "linux"))]
+pub mod shm;
 mod slice;
+mod strided;
+#[cfg(any(test, not(any(target_arch = "x86_64", miri))))]
+mod swar;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod tier;
+mod tile;
 mod types;
 
+pub use align::*;
+#[cfg(feature = "search")]
+pub use ascii::*;
 pub use assembly::*;
+#[cfg(feature = "search")]
+pub use binary::*;
+pub use bitmap::*;
+#[cfg(feature = "search")]
+pub use block::*;
+#[cfg(feature = "simd")]
+pub use bswap::*;
+pub use byteset::*;
+#[cfg(feature = "dispatch")]
+pub use capabilities::*;
+#[cfg(feature = "dispatch")]
+pub use config::*;
+pub use copy::*;
+#[cfg(feature = "dispatch")]
+pub use cpuid::*;
+#[cfg(feature = "search")]
+pub use deep::*;
+#[cfg(feature = "search")]
+pub use diff::*;
+pub use error::*;
+#[cfg(feature = "io")]
+pub use escape::*;
+pub use ffi::*;
+#[cfg(feature = "search")]
+pub use finder::*;
+pub use iota::*;
+#[cfg(feature = "mem")]
+pub use lazy::*;
+pub use nontemporal::*;
+pub use ordered::*;
+#[cfg(feature = "std")]
+pub use outbuf::*;
+pub use overlap::*;
+#[cfg(feature = "io")]
+pub use packet::*;
+pub use pod::*;
+#[cfg(feature = "io")]
+pub use publish::*;
+#[cfg(feature = "io")]
+pub use ring::*;
+pub use rows::*;
+#[cfg(feature = "search")]
+pub use run::*;
+#[cfg(feature = "std")]
+pub use scratch::*;
+#[cfg(feature = "io")]
+pub use seqlock::*;
 pub use slice::*;
+pub use strided::*;
+pub use tier::*;
+pub use tile::*;
 pub use types::*;
@@ -0,0 +1,76 @@
+/// Return the index of the first byte for which `(a ^ b) & mask` is not zero.
+///
+/// On x86_64 blocks of 16 bytes are compared using SSE2, on other architectures and for the
+/// remaining bytes the comparison uses 8 byte words.
+///
+/// # Safety
+///
+/// `a`, `b` and `mask` need to be valid for reads of `len` bytes.
+#[inline]
+pub(crate) unsafe fn mismatch_masked(
+    a: *const u8,
+    b: *const u8,
+    mask: *const u8,
+    len: usize,
+) -> Option<usize> {
+    let mut offset = 0;
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use core::arch::x86_64::{
+            __m128i, _mm_and_si128, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8,
+            _mm_setzero_si128, _mm_xor_si128,
+        };
+
+        let load = |ptr: *const u8| _mm_loadu_si128(ptr.cast::<__m128i>());
+        while offset + 16 <= len {
+            let diff = _mm_xor_si128(load(a.add(offset)), load(b.add(offset)));
+            let masked = _mm_and_si128(diff, load(mask.add(offset)));
+            let equal = _mm_movemask_epi8(_mm_cmpeq_epi8(masked, _mm_setzero_si128())) as u32;
+            if equal != 0xFFFF {
+                return Some(offset + (!equal).trailing_zeros() as usize);
+            }
+            offset += 16;
+        }
+    }
+    let load = |ptr: *const u8| u64::from_le_bytes(ptr.cast::<[u8; 8]>().read_unaligned());
+    while offset + 8 <= len {
+        let masked = (load(a.add(offset)) ^ load(b.add(offset))) & load(mask.add(offset));
+        if masked != 0 {
+            return Some(offset + masked.trailing_zeros() as usize / 8);
+        }
+        offset += 8;
+    }
+    (offset..len).find(|&i| (*a.add(i) ^ *b.add(i)) & *mask.add(i) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(a: &[u8], b: &[u8], mask: &[u8]) -> Option<usize> {
+        (0..a.len()).find(|&i| (a[i] ^ b[i]) & mask[i] != 0)
+    }
+
+    #[test]
+    fn test_mismatch_masked() {
+        let a = (0..100).map(|i| i as u8).collect::<Vec<_>>();
+        let mask = (0..100)
+            .map(|i| if i % 3 == 0 { 0 } else { 0x0F })
+            .collect::<Vec<_>>();
+        for i in 0..100 {
+            for flip in [0x01, 0x10] {
+                let mut b = a.clone();
+                b[i] ^= flip;
+                for len in [i, i + 1, 100] {
+                    let actual =
+                        unsafe { mismatch_masked(a.as_ptr(), b.as_ptr(), mask.as_ptr(), len) };
+                    assert_eq!(
+                        actual,
+                        reference(&a[..len], &b[..len], &mask[..len]),
+                        "{i} {flip} {len}"
+                    );
+                }
+            }
+        }
+    }
+}
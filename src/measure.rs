@@ -0,0 +1,119 @@
+//! Lightweight throughput measurements for startup diagnostics and performance checks in CI,
+//! without depending on a benchmark framework.
+
+use crate::{rep_movs, rep_stos};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Minimum duration of a timed batch, batches are repeated with twice as many iterations until
+/// they take at least this long.
+const BATCH_DURATION: Duration = Duration::from_micros(200);
+
+/// Number of timed batches per size, the fastest one is reported.
+const BATCHES: usize = 3;
+
+/// Measure the throughput of `op` for each of `sizes`, in bytes per nanosecond, which equals
+/// gigabytes per second.
+///
+/// `op` is called repeatedly with a source and a destination buffer of the measured size. Each
+/// size is measured in a few batches of at least 200 µs, the fastest batch is reported to reduce
+/// the influence of interrupts and frequency changes. The results are returned in the order of
+/// `sizes`.
+pub fn measure_throughput(
+    sizes: &[usize],
+    mut op: impl FnMut(&[u8], &mut [u8]),
+) -> Vec<(usize, f64)> {
+    let max_size = sizes.iter().copied().max().unwrap_or(0);
+    let src = vec![0x5A_u8; max_size];
+    let mut dst = vec![0_u8; max_size];
+    sizes
+        .iter()
+        .map(|&size| {
+            let (src, dst) = (&src[..size], &mut dst[..size]);
+            let mut iterations = 1_u32;
+            let mut best = f64::INFINITY;
+            let mut batches = 0;
+            while batches < BATCHES {
+                let start = Instant::now();
+                for _ in 0..iterations {
+                    op(black_box(src), black_box(&mut *dst));
+                }
+                let elapsed = start.elapsed();
+                if elapsed < BATCH_DURATION && iterations < u32::MAX / 2 {
+                    iterations *= 2;
+                    continue;
+                }
+                best = best.min(elapsed.as_nanos() as f64 / f64::from(iterations));
+                batches += 1;
+            }
+            (size, size as f64 / best.max(f64::MIN_POSITIVE))
+        })
+        .collect()
+}
+
+/// Measure the throughput of [`rep_movs`] copies of `sizes` bytes, see [`measure_throughput`].
+pub fn measure_copy_throughput(sizes: &[usize]) -> Vec<(usize, f64)> {
+    measure_throughput(sizes, |src, dst| unsafe {
+        rep_movs(src.as_ptr(), dst.as_mut_ptr(), dst.len())
+    })
+}
+
+/// Measure the throughput of [`rep_stos`] fills of `sizes` bytes, see [`measure_throughput`].
+pub fn measure_fill_throughput(sizes: &[usize]) -> Vec<(usize, f64)> {
+    measure_throughput(sizes, |_, dst| unsafe {
+        rep_stos(0xA5_u8, dst.as_mut_ptr(), dst.len())
+    })
+}
+
+/// Return the smallest of the ascending `sizes` from which on `candidate` was at least as fast
+/// as `baseline` for all larger sizes, or `None` if it was slower at the largest size.
+#[cfg(feature = "dispatch")]
+pub(crate) fn crossover(
+    sizes: &[usize],
+    candidate: impl FnMut(&[u8], &mut [u8]),
+    baseline: impl FnMut(&[u8], &mut [u8]),
+) -> Option<usize> {
+    let candidate = measure_throughput(sizes, candidate);
+    let baseline = measure_throughput(sizes, baseline);
+    candidate
+        .iter()
+        .zip(&baseline)
+        .rev()
+        .take_while(|((_, candidate), (_, baseline))| candidate >= baseline)
+        .last()
+        .map(|((size, _), _)| *size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_throughput() {
+        let sizes = [0, 64, 4096];
+        for results in [
+            measure_copy_throughput(&sizes),
+            measure_fill_throughput(&sizes),
+        ] {
+            assert_eq!(
+                results.iter().map(|(size, _)| *size).collect::<Vec<_>>(),
+                sizes
+            );
+            assert!(results
+                .iter()
+                .all(|(_, throughput)| throughput.is_finite() && *throughput >= 0.0));
+            assert!(results[2].1 > 0.0);
+        }
+        assert!(measure_throughput(&[], |_, _| {}).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "dispatch")]
+    fn test_crossover() {
+        let sizes = [16, 256, 4096];
+        let fast = |_: &[u8], _: &mut [u8]| {};
+        let slow = |src: &[u8], dst: &mut [u8]| dst.copy_from_slice(src);
+        assert_eq!(crossover(&sizes, fast, slow), Some(16));
+        assert_eq!(crossover(&sizes, slow, fast), None);
+    }
+}
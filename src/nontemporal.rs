@@ -0,0 +1,267 @@
+/// Copy `len` bytes from `src` to `dst` using non-temporal stores.
+///
+/// On x86_64 the 8 byte aligned part of `dst` is written using `movnti` instructions, which
+/// bypass the cache. The unaligned head and tail are copied using `rep movsb`. An `sfence` is
+/// issued at the end so that the non-temporal stores are ordered before any later stores.
+///
+/// On other architectures this will fall back to `copy_nonoverlapping`.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::copy_nonoverlapping`] apply:
+///
+///  - `src` and `dst` need to be valid for the given `len`
+///  - `src` and `dst` memory regions must not overlap
+#[inline]
+pub unsafe fn copy_nontemporal(src: *const u8, dst: *mut u8, len: usize) {
+    copy_nontemporal_unfenced(src, dst, len);
+    store_fence();
+}
+
+/// Same as [`copy_nontemporal`], but without the final `sfence`.
+///
+/// This allows issuing multiple copies and only fencing once at the end using [`store_fence`].
+#[inline]
+pub(crate) unsafe fn copy_nontemporal_unfenced(src: *const u8, dst: *mut u8, len: usize) {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use crate::rep_movs;
+        use core::arch::x86_64::_mm_stream_si64;
+
+        let head = dst.align_offset(8).min(len);
+        rep_movs(src, dst, head);
+        let body = (len - head) / 8;
+        let src_body = src.add(head);
+        let dst_body = dst.add(head).cast::<i64>();
+        for i in 0..body {
+            _mm_stream_si64(
+                dst_body.add(i),
+                src_body.add(i * 8).cast::<i64>().read_unaligned(),
+            );
+        }
+        let copied = head + body * 8;
+        rep_movs(src.add(copied), dst.add(copied), len - copied);
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        core::ptr::copy_nonoverlapping(src, dst, len)
+    }
+}
+
+/// Fill `len` bytes at `dst` with `value` using non-temporal stores.
+///
+/// Like [`copy_nontemporal`], the 8 byte aligned part of `dst` is written using `movnti`
+/// instructions and the unaligned head and tail using `rep stosb`, followed by an `sfence`.
+///
+/// On other architectures this will fall back to `write_bytes`.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::write_bytes`] apply, `dst` needs to be
+/// valid for writes of `len` bytes.
+#[inline]
+pub unsafe fn fill_nontemporal(value: u8, dst: *mut u8, len: usize) {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use crate::rep_stos;
+        use core::arch::x86_64::_mm_stream_si64;
+
+        let head = dst.align_offset(8).min(len);
+        rep_stos(value, dst, head);
+        let body = (len - head) / 8;
+        let dst_body = dst.add(head).cast::<i64>();
+        let pattern = i64::from_ne_bytes([value; 8]);
+        for i in 0..body {
+            _mm_stream_si64(dst_body.add(i), pattern);
+        }
+        let filled = head + body * 8;
+        rep_stos(value, dst.add(filled), len - filled);
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        core::ptr::write_bytes(dst, value, len)
+    }
+    store_fence();
+}
+
+/// Copy `len` bytes from `src` to write-combining memory at `dst_wc`.
+///
+/// On x86_64 all stores to `dst_wc` are 16 byte wide non-temporal stores in ascending address
+/// order. The 16 byte aligned body is written using `movntdq`, the unaligned head and tail using
+/// a single `maskmovdqu` each, so the destination never sees byte-granular stores like the tail
+/// of a `rep movsb`. The masked stores address the aligned 16 byte blocks containing the head
+/// and tail, so no store reaches past the aligned block containing the last byte of `dst_wc`.
+/// An `sfence` is issued at the end.
+///
+/// On other architectures this will fall back to `copy_nonoverlapping`.
+///
+/// # Safety
+///
+/// The same safety considerations as for [`core::ptr::copy_nonoverlapping`] apply:
+///
+///  - `src` and `dst_wc` need to be valid for the given `len`
+///  - `src` and `dst_wc` memory regions must not overlap
+#[inline]
+pub unsafe fn copy_wc(src: *const u8, dst_wc: *mut u8, len: usize) {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        use crate::rep_movs;
+        use core::arch::x86_64::{
+            __m128i, _mm_and_si128, _mm_cmpgt_epi8, _mm_loadu_si128, _mm_maskmoveu_si128,
+            _mm_set1_epi8, _mm_setr_epi8, _mm_stream_si128,
+        };
+
+        // Store the `count` bytes at `src` to `dst` using a masked store to the aligned 16 byte
+        // block containing `dst`, which also needs to contain the last byte. Masked-out bytes
+        // may still be checked for faults, so the store must not reach past that block, for
+        // example into the next page at the end of a mapped device region. The source is staged
+        // on the stack at the offset of `dst` so that no bytes outside of `src` are read.
+        let store_partial = |src: *const u8, dst: *mut u8, count: usize| {
+            let shift = dst as usize % 16;
+            debug_assert!(shift + count <= 16);
+            let block = dst.sub(shift);
+            let mut staged = [0_u8; 16];
+            rep_movs(src, staged.as_mut_ptr().add(shift), count);
+            let lanes = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+            let mask = _mm_and_si128(
+                _mm_cmpgt_epi8(lanes, _mm_set1_epi8(shift as i8 - 1)),
+                _mm_cmpgt_epi8(_mm_set1_epi8((shift + count) as i8), lanes),
+            );
+            let data = _mm_loadu_si128(staged.as_ptr().cast::<__m128i>());
+            _mm_maskmoveu_si128(data, mask, block.cast::<i8>());
+        };
+
+        let head = dst_wc.align_offset(16).min(len);
+        if head > 0 {
+            store_partial(src, dst_wc, head);
+        }
+        let body = (len - head) / 16;
+        for i in 0..body {
+            let offset = head + i * 16;
+            let data = _mm_loadu_si128(src.add(offset).cast::<__m128i>());
+            _mm_stream_si128(dst_wc.add(offset).cast::<__m128i>(), data);
+        }
+        let copied = head + body * 16;
+        if copied < len {
+            store_partial(src.add(copied), dst_wc.add(copied), len - copied);
+        }
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        core::ptr::copy_nonoverlapping(src, dst_wc, len)
+    }
+    store_fence();
+}
+
+/// Order all previous stores, including non-temporal stores, before any later stores.
+#[inline]
+pub(crate) fn store_fence() {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    unsafe {
+        core::arch::x86_64::_mm_sfence()
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    core::sync::atomic::fence(core::sync::atomic::Ordering::Release)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_nontemporal() {
+        let src = (0..100_u8).collect::<Vec<_>>();
+        for offset in 0..8 {
+            for len in 0..src.len() - offset {
+                let mut dst = vec![0xFF_u8; 120];
+                unsafe { copy_nontemporal(src.as_ptr(), dst.as_mut_ptr().add(offset), len) };
+                assert!(dst[..offset].iter().all(|&b| b == 0xFF));
+                assert_eq!(&dst[offset..offset + len], &src[..len]);
+                assert!(dst[offset + len..].iter().all(|&b| b == 0xFF));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_nontemporal() {
+        for offset in 0..8 {
+            for len in 0..100 {
+                let mut dst = vec![0xFF_u8; 120];
+                unsafe { fill_nontemporal(0x5A, dst.as_mut_ptr().add(offset), len) };
+                assert!(dst[..offset].iter().all(|&b| b == 0xFF));
+                assert!(dst[offset..offset + len].iter().all(|&b| b == 0x5A));
+                assert!(dst[offset + len..].iter().all(|&b| b == 0xFF));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", not(miri)))]
+    fn test_copy_wc_end_of_page() {
+        use core::ffi::{c_int, c_long, c_void};
+
+        const PROT_NONE: c_int = 0;
+        const PROT_READ_WRITE: c_int = 3;
+        const MAP_PRIVATE_ANONYMOUS: c_int = 0x22;
+        const SC_PAGESIZE: c_int = 30;
+        extern "C" {
+            fn sysconf(name: c_int) -> c_long;
+            fn mmap(
+                addr: *mut c_void,
+                len: usize,
+                prot: c_int,
+                flags: c_int,
+                fd: c_int,
+                offset: i64,
+            ) -> *mut c_void;
+            fn mprotect(addr: *mut c_void, len: usize, prot: c_int) -> c_int;
+            fn munmap(addr: *mut c_void, len: usize) -> c_int;
+        }
+
+        // a writable page followed by an inaccessible one
+        let page = unsafe { sysconf(SC_PAGESIZE) } as usize;
+        let map = unsafe {
+            mmap(
+                core::ptr::null_mut(),
+                2 * page,
+                PROT_READ_WRITE,
+                MAP_PRIVATE_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(map as isize, -1);
+        assert_eq!(
+            unsafe { mprotect(map.cast::<u8>().add(page).cast(), page, PROT_NONE) },
+            0
+        );
+        let end = unsafe { map.cast::<u8>().add(page) };
+
+        let src = (0..40_u8).collect::<Vec<_>>();
+        for len in 0..src.len() {
+            for gap in 0..16 {
+                let dst = unsafe { end.sub(len + gap) };
+                unsafe { copy_wc(src.as_ptr(), dst, len) };
+                assert_eq!(
+                    unsafe { core::slice::from_raw_parts(dst, len) },
+                    &src[..len]
+                );
+            }
+        }
+        unsafe { munmap(map, 2 * page) };
+    }
+
+    #[test]
+    fn test_copy_wc() {
+        let src = (0..100_u8).collect::<Vec<_>>();
+        for offset in 0..16 {
+            for len in 0..src.len() - offset {
+                let mut dst = vec![0xFF_u8; 128];
+                unsafe { copy_wc(src.as_ptr(), dst.as_mut_ptr().add(offset), len) };
+                assert!(dst[..offset].iter().all(|&b| b == 0xFF));
+                assert_eq!(&dst[offset..offset + len], &src[..len]);
+                assert!(dst[offset + len..].iter().all(|&b| b == 0xFF));
+            }
+        }
+    }
+}
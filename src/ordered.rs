@@ -0,0 +1,233 @@
+use crate::RegisterType;
+use core::cmp::Ordering;
+
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+use core::arch::x86_64::*;
+
+/// Element types with a total order, supported by [`position_gt`] and [`position_lt`].
+///
+/// Integers use their usual order. Floats use the total order of [`f32::total_cmp`], which
+/// orders `-NaN < -inf < ... < -0.0 < +0.0 < ... < +inf < +NaN`, so that a threshold of `0.0`
+/// does not match `-0.0` and NaNs are greater or less than every number depending on their sign.
+///
+/// Elements of up to 4 bytes are compared 16 bytes at a time using SSE2 on x86_64, 8 byte
+/// elements, for which SSE2 has no compare, use a scalar loop.
+pub trait OrderedType: RegisterType {
+    /// Compare `self` to `other` using the total order described above.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Return the index of the first element of `src` that compares as `ordering` to `value`.
+    #[doc(hidden)]
+    fn position_ordered(src: &[Self], value: Self, ordering: Ordering) -> Option<usize> {
+        position_scalar(src, value, ordering)
+    }
+}
+
+#[inline]
+fn position_scalar<T: OrderedType>(src: &[T], value: T, ordering: Ordering) -> Option<usize> {
+    src.iter()
+        .position(|element| element.total_cmp(&value) == ordering)
+}
+
+/// Scan `src` for the first element whose key compares as `ordering` to `threshold`, the key of
+/// `value`. Keys are the elements transformed by `key` so that they order correctly under the
+/// signed lane compare `cmpgt`. Blocks of 16 bytes are compared using SSE2, the remaining
+/// elements using a scalar loop.
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+#[inline(always)]
+unsafe fn position_sse2<T: OrderedType>(
+    src: &[T],
+    value: T,
+    ordering: Ordering,
+    threshold: __m128i,
+    key: impl Fn(__m128i) -> __m128i,
+    cmpgt: impl Fn(__m128i, __m128i) -> __m128i,
+) -> Option<usize> {
+    let size = core::mem::size_of::<T>();
+    let lanes = 16 / size;
+    let mut offset = 0;
+    while offset + lanes <= src.len() {
+        let keys = key(_mm_loadu_si128(src.as_ptr().add(offset).cast::<__m128i>()));
+        let matches = match ordering {
+            Ordering::Greater => cmpgt(keys, threshold),
+            _ => cmpgt(threshold, keys),
+        };
+        let bits = _mm_movemask_epi8(matches) as u32;
+        if bits != 0 {
+            return Some(offset + bits.trailing_zeros() as usize / size);
+        }
+        offset += lanes;
+    }
+    position_scalar(&src[offset..], value, ordering).map(|index| offset + index)
+}
+
+macro_rules! impl_ordered_int {
+    ($($ty:ty => $set1:ident, $cmpgt:ident, $bias:expr;)*) => {
+        $(
+            impl OrderedType for $ty {
+                #[inline]
+                fn total_cmp(&self, other: &Self) -> Ordering {
+                    self.cmp(other)
+                }
+
+                #[inline]
+                fn position_ordered(src: &[Self], value: Self, ordering: Ordering) -> Option<usize> {
+                    #[cfg(all(target_arch = "x86_64", not(miri)))]
+                    unsafe {
+                        // flipping the sign bit maps unsigned integers to signed integers of the
+                        // same order
+                        let bias = $set1($bias);
+                        let key = |x| _mm_xor_si128(x, bias);
+                        let threshold = key($set1(value as _));
+                        position_sse2(src, value, ordering, threshold, key, |a, b| $cmpgt(a, b))
+                    }
+                    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+                    position_scalar(src, value, ordering)
+                }
+            }
+        )*
+    };
+}
+
+impl_ordered_int! {
+    i8 => _mm_set1_epi8, _mm_cmpgt_epi8, 0;
+    u8 => _mm_set1_epi8, _mm_cmpgt_epi8, i8::MIN;
+    i16 => _mm_set1_epi16, _mm_cmpgt_epi16, 0;
+    u16 => _mm_set1_epi16, _mm_cmpgt_epi16, i16::MIN;
+    i32 => _mm_set1_epi32, _mm_cmpgt_epi32, 0;
+    u32 => _mm_set1_epi32, _mm_cmpgt_epi32, i32::MIN;
+}
+
+impl OrderedType for i64 {
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+}
+
+impl OrderedType for u64 {
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+}
+
+impl OrderedType for f32 {
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn position_ordered(src: &[Self], value: Self, ordering: Ordering) -> Option<usize> {
+        #[cfg(all(target_arch = "x86_64", not(miri)))]
+        unsafe {
+            // flipping all bits except the sign of negative floats gives signed integers of the
+            // same total order, like in `f32::total_cmp`
+            let key = |x| _mm_xor_si128(x, _mm_srli_epi32::<1>(_mm_srai_epi32::<31>(x)));
+            let threshold = key(_mm_set1_epi32(value.to_bits() as i32));
+            position_sse2(src, value, ordering, threshold, key, |a, b| {
+                _mm_cmpgt_epi32(a, b)
+            })
+        }
+        #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+        position_scalar(src, value, ordering)
+    }
+}
+
+impl OrderedType for f64 {
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+}
+
+/// Return the index of the first element of `src` that is greater than `value`.
+///
+/// See [`OrderedType`] for the order of floats.
+#[inline]
+pub fn position_gt<T: OrderedType>(src: &[T], value: T) -> Option<usize> {
+    T::position_ordered(src, value, Ordering::Greater)
+}
+
+/// Return the index of the first element of `src` that is less than `value`.
+///
+/// See [`OrderedType`] for the order of floats.
+#[inline]
+pub fn position_lt<T: OrderedType>(src: &[T], value: T) -> Option<usize> {
+    T::position_ordered(src, value, Ordering::Less)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<T: OrderedType + core::fmt::Debug>(src: &[T]) {
+        for value in src {
+            for start in 0..src.len().min(20) {
+                let src = &src[start..];
+                assert_eq!(
+                    position_gt(src, *value),
+                    position_scalar(src, *value, Ordering::Greater),
+                    "{value:?}"
+                );
+                assert_eq!(
+                    position_lt(src, *value),
+                    position_scalar(src, *value, Ordering::Less),
+                    "{value:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_integers() {
+        let values = (0..70_u64)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_left(i as u32))
+            .collect::<Vec<_>>();
+        check(&values.iter().map(|&x| x as u8).collect::<Vec<_>>());
+        check(&values.iter().map(|&x| x as i8).collect::<Vec<_>>());
+        check(&values.iter().map(|&x| x as u16).collect::<Vec<_>>());
+        check(&values.iter().map(|&x| x as i16).collect::<Vec<_>>());
+        check(&values.iter().map(|&x| x as u32).collect::<Vec<_>>());
+        check(&values.iter().map(|&x| x as i32).collect::<Vec<_>>());
+        check(&values);
+        check(&values.iter().map(|&x| x as i64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_floats() {
+        let special = [
+            f32::NAN,
+            -f32::NAN,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            0.0,
+            -0.0,
+            f32::MIN_POSITIVE,
+            -1.5,
+            1.5,
+            f32::MAX,
+        ];
+        let values = special.iter().cycle().take(45).copied().collect::<Vec<_>>();
+        check(&values);
+        check(&values.iter().map(|&x| x as f64).collect::<Vec<_>>());
+
+        assert_eq!(position_gt(&[-0.0_f32, 1.0], 0.0), Some(1));
+        assert_eq!(position_lt(&[0.0_f32; 8], 0.0), None);
+        assert_eq!(position_lt(&[0.0_f32, 0.0, 0.0, 0.0, -0.0], 0.0), Some(4));
+        assert_eq!(
+            position_gt(&[1.0_f32, f32::INFINITY, f32::NAN], f32::INFINITY),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let samples = [3_u16, 5, 9, 60000, 2, 40000, 7, 7, 7];
+        assert_eq!(position_gt(&samples, 8), Some(2));
+        assert_eq!(position_gt(&samples, 60000), None);
+        assert_eq!(position_lt(&samples, 3), Some(4));
+        assert_eq!(position_lt::<u8>(&[], 3), None);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{rep_movs, rep_stos, Error};
+use core::ops::{Deref, Range};
+
+/// Growable byte buffer whose appends use the copy and fill kernels of this crate.
+///
+/// Meant as the output of serializers and of pipelines that alternate searching the input with
+/// appending runs of it. Every append reserves the required space once, growing the buffer
+/// geometrically like [`Vec`], and then writes it using a single `rep` instruction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutBuf {
+    buf: Vec<u8>,
+}
+
+impl OutBuf {
+    /// Create an empty buffer without allocating.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    /// Create an empty buffer with space for at least `capacity` bytes.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Remove all bytes, keeping the allocated capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buf.clear()
+    }
+
+    #[inline]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// Reserve `additional` bytes and return a pointer to the first of them.
+    #[inline]
+    fn reserve_tail(&mut self, additional: usize) -> *mut u8 {
+        self.buf.reserve(additional);
+        unsafe { self.buf.as_mut_ptr().add(self.buf.len()) }
+    }
+
+    /// Append all bytes of `src`.
+    #[inline]
+    pub fn append(&mut self, src: &[u8]) {
+        let dst = self.reserve_tail(src.len());
+        unsafe {
+            rep_movs(src.as_ptr(), dst, src.len());
+            self.buf.set_len(self.buf.len() + src.len());
+        }
+    }
+
+    /// Append the bytes of `src` in `range`, typically a run between two search results.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds of `src`.
+    #[cfg(not(feature = "no-panic"))]
+    #[inline]
+    pub fn append_run(&mut self, src: &[u8], range: Range<usize>) {
+        self.append(&src[range])
+    }
+
+    /// Same as `append_run`, but returns [`Error::LimitExceeded`] with the end of `range` and the
+    /// length of `src`, or [`Error::InvalidRange`] if `range` is reversed, instead of panicking.
+    #[inline]
+    pub fn try_append_run(&mut self, src: &[u8], range: Range<usize>) -> Result<(), Error> {
+        match src.get(range.clone()) {
+            Some(run) => {
+                self.append(run);
+                Ok(())
+            }
+            None if range.start > range.end => Err(Error::InvalidRange {
+                start: range.start,
+                end: range.end,
+            }),
+            None => Err(Error::LimitExceeded {
+                len: range.end,
+                limit: src.len(),
+            }),
+        }
+    }
+
+    /// Append `n` copies of `value`.
+    #[inline]
+    pub fn append_fill(&mut self, value: u8, n: usize) {
+        let dst = self.reserve_tail(n);
+        unsafe {
+            rep_stos(value, dst, n);
+            self.buf.set_len(self.buf.len() + n);
+        }
+    }
+
+    /// Append `src` with the bytes in `specials` and `escape` escaped as described in
+    /// [`try_escape_copy`](crate::try_escape_copy), and return the number of appended bytes.
+    ///
+    /// Twice the length of `src` is reserved, so that the input is only scanned once.
+    #[cfg(feature = "io")]
+    pub fn append_escaped(&mut self, src: &[u8], escape: u8, specials: &crate::ByteSet) -> usize {
+        // slices of bytes are at most `isize::MAX` long, so this does not overflow
+        let capacity = src.len() * 2;
+        let dst = self.reserve_tail(capacity);
+        unsafe {
+            // the escaped length is at most twice the input length
+            let written =
+                crate::escape::escape_into(src, dst, capacity, escape, specials).unwrap_unchecked();
+            self.buf.set_len(self.buf.len() + written);
+            written
+        }
+    }
+}
+
+impl Deref for OutBuf {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+impl AsRef<[u8]> for OutBuf {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+impl From<OutBuf> for Vec<u8> {
+    #[inline]
+    fn from(out: OutBuf) -> Self {
+        out.buf
+    }
+}
+
+impl From<Vec<u8>> for OutBuf {
+    /// Create a buffer that appends to the existing bytes of `buf`.
+    #[inline]
+    fn from(buf: Vec<u8>) -> Self {
+        Self { buf }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_append() {
+        let mut out = OutBuf::new();
+        assert!(out.is_empty());
+        out.append(b"key");
+        out.append_fill(b' ', 3);
+        out.append_run(b"= value;", 0..1);
+        out.append_run(b"= value;", 1..7);
+        assert_eq!(out.as_slice(), b"key   = value");
+        assert_eq!(out.len(), 13);
+
+        out.clear();
+        for i in 0..1000 {
+            out.append_fill(i as u8, i % 7);
+        }
+        let expected = (0..1000)
+            .flat_map(|i| core::iter::repeat_n(i as u8, i % 7))
+            .collect::<Vec<_>>();
+        assert_eq!(out.into_vec(), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_append_run_out_of_bounds() {
+        OutBuf::new().append_run(b"abc", 2..4);
+    }
+
+    #[test]
+    fn test_try_append_run() {
+        let mut out = OutBuf::new();
+        assert_eq!(out.try_append_run(b"abc", 1..3), Ok(()));
+        assert_eq!(
+            out.try_append_run(b"abc", 2..4),
+            Err(Error::LimitExceeded { len: 4, limit: 3 })
+        );
+        assert_eq!(
+            out.try_append_run(b"abc", Range { start: 2, end: 1 }),
+            Err(Error::InvalidRange { start: 2, end: 1 })
+        );
+        assert_eq!(out.as_slice(), b"bc");
+    }
+
+    #[test]
+    #[cfg(feature = "io")]
+    fn test_append_escaped() {
+        let specials = crate::ByteSet::new(b"\"");
+        let mut out = OutBuf::from(b"[".to_vec());
+        assert_eq!(out.append_escaped(br#"say "hi""#, b'\\', &specials), 10);
+        assert_eq!(out.append_escaped(b"", b'\\', &specials), 0);
+        out.append(b"]");
+        assert_eq!(Vec::from(out), b"[say \\\x02hi\\\x02]");
+    }
+}
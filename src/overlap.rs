@@ -0,0 +1,140 @@
+/// Relation between a source and a destination range of the same length.
+///
+/// Returned by [`ranges_relation`] to decide in which direction overlapping ranges can be
+/// copied without overwriting source elements before they were read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangesRelation {
+    /// The ranges do not share any byte. Empty ranges are always disjoint.
+    Disjoint,
+    /// `dst` starts after the start of `src` but inside of it, only a backward copy is correct.
+    ForwardOverlap,
+    /// `dst` starts before the start of `src` and ends inside of it, only a forward copy is
+    /// correct.
+    BackwardOverlap,
+    /// Both ranges start at the same address, copying does not change any element.
+    Identical,
+}
+
+impl RangesRelation {
+    /// Whether copying from the first to the last element reads every source element before
+    /// it is overwritten.
+    #[inline]
+    pub fn is_forward_copy_safe(self) -> bool {
+        !matches!(self, RangesRelation::ForwardOverlap)
+    }
+
+    /// Whether copying from the last to the first element reads every source element before
+    /// it is overwritten.
+    #[inline]
+    pub fn is_backward_copy_safe(self) -> bool {
+        !matches!(self, RangesRelation::BackwardOverlap)
+    }
+}
+
+/// Classify how the `len` elements starting at `src` and at `dst` overlap.
+///
+/// Only the addresses are compared, the pointers are never dereferenced.
+#[inline]
+pub fn ranges_relation<T>(src: *const T, dst: *const T, len: usize) -> RangesRelation {
+    let bytes = len.saturating_mul(core::mem::size_of::<T>());
+    let (src, dst) = (src as usize, dst as usize);
+    if bytes == 0 {
+        RangesRelation::Disjoint
+    } else if src == dst {
+        RangesRelation::Identical
+    } else if dst > src && dst - src < bytes {
+        RangesRelation::ForwardOverlap
+    } else if src > dst && src - dst < bytes {
+        RangesRelation::BackwardOverlap
+    } else {
+        RangesRelation::Disjoint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Classify by comparing the element indices covered by both ranges.
+    fn reference_relation(src: usize, dst: usize, len: usize) -> RangesRelation {
+        let shared = (src..src + len).any(|i| (dst..dst + len).contains(&i));
+        if !shared {
+            RangesRelation::Disjoint
+        } else if src == dst {
+            RangesRelation::Identical
+        } else if dst > src {
+            RangesRelation::ForwardOverlap
+        } else {
+            RangesRelation::BackwardOverlap
+        }
+    }
+
+    #[test]
+    fn test_ranges_relation_exhaustive() {
+        let buffer = [0_u32; 24];
+        let ptr = buffer.as_ptr();
+        for src in 0..12 {
+            for dst in 0..12 {
+                for len in 0..=12 {
+                    let relation = unsafe { ranges_relation(ptr.add(src), ptr.add(dst), len) };
+                    assert_eq!(
+                        relation,
+                        reference_relation(src, dst, len),
+                        "src {src}, dst {dst}, len {len}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ranges_relation_copy_direction() {
+        let mut buffer = [0_u8; 16];
+        for src in 0..8 {
+            for dst in 0..8 {
+                for len in 0..=8 {
+                    for (i, value) in buffer.iter_mut().enumerate() {
+                        *value = i as u8;
+                    }
+                    let ptr = buffer.as_mut_ptr();
+                    let relation = unsafe { ranges_relation(ptr.add(src), ptr.add(dst), len) };
+                    let expected = (src..src + len).map(|i| i as u8).collect::<Vec<_>>();
+                    if relation.is_forward_copy_safe() {
+                        for i in 0..len {
+                            buffer[dst + i] = buffer[src + i];
+                        }
+                    } else {
+                        assert!(relation.is_backward_copy_safe());
+                        for i in (0..len).rev() {
+                            buffer[dst + i] = buffer[src + i];
+                        }
+                    }
+                    assert_eq!(&buffer[dst..dst + len], expected, "src {src}, dst {dst}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ranges_relation_unaligned_bytes() {
+        let buffer = [0_u8; 16];
+        let ptr = buffer.as_ptr();
+        let wide = |offset: usize| ptr.wrapping_add(offset).cast::<u64>();
+        assert_eq!(
+            ranges_relation(wide(0), wide(7), 1),
+            RangesRelation::ForwardOverlap
+        );
+        assert_eq!(
+            ranges_relation(wide(0), wide(8), 1),
+            RangesRelation::Disjoint
+        );
+        assert_eq!(
+            ranges_relation(wide(3), wide(0), 1),
+            RangesRelation::BackwardOverlap
+        );
+        assert_eq!(
+            ranges_relation::<()>(ptr.cast(), ptr.cast(), 4),
+            RangesRelation::Disjoint
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{rep_movs, Error};
+use core::num::NonZeroUsize;
+
+/// Copy a batch of variable length `packets` back to back into `arena`.
+///
+/// The start offset of each copied packet within `arena` is stored at the same index in
+/// `offsets`. Copying stops at the first packet that does not fit into the remaining space
+/// of `arena`, the number of copied packets is returned.
+///
+/// # Panics
+///
+/// Panics if `offsets` and `packets` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_packets(packets: &[&[u8]], arena: &mut [u8], offsets: &mut [usize]) -> usize {
+    match try_copy_packets(packets, arena, offsets) {
+        Ok(copied) => copied,
+        Err(_) => panic!("length mismatch"),
+    }
+}
+
+/// Same as `copy_packets`, but returns [`Error::LenMismatch`] without copying any packets
+/// instead of panicking if `offsets` and `packets` have different lengths.
+#[inline]
+pub fn try_copy_packets(
+    packets: &[&[u8]],
+    arena: &mut [u8],
+    offsets: &mut [usize],
+) -> Result<usize, Error> {
+    if packets.len() != offsets.len() {
+        return Err(Error::LenMismatch {
+            src_len: packets.len(),
+            dst_len: offsets.len(),
+        });
+    }
+    let mut used = 0;
+    for (i, (packet, offset)) in packets.iter().zip(offsets.iter_mut()).enumerate() {
+        if packet.len() > arena.len() - used {
+            return Ok(i);
+        }
+        unsafe { rep_movs(packet.as_ptr(), arena.as_mut_ptr().add(used), packet.len()) };
+        *offset = used;
+        used += packet.len();
+    }
+    Ok(packets.len())
+}
+
+/// Iterator over consecutive chunks of at most `mtu` bytes, see [`try_chunked_copy_iter`].
+#[derive(Debug, Clone)]
+pub struct ChunkedCopy<'a> {
+    remaining: &'a [u8],
+    mtu: NonZeroUsize,
+}
+
+/// Split `src` into consecutive chunks of `mtu` bytes, the last chunk might be shorter.
+///
+/// The chunks can either be consumed as slices using the [`Iterator`] implementation, or copied
+/// into frame buffers using [`ChunkedCopy::copy_next`].
+///
+/// # Panics
+///
+/// Panics if `mtu` is zero.
+#[cfg(not(feature = "no-panic"))]
+pub fn chunked_copy_iter(src: &[u8], mtu: usize) -> ChunkedCopy<'_> {
+    match try_chunked_copy_iter(src, mtu) {
+        Ok(chunks) => chunks,
+        Err(_) => panic!("mtu must not be zero"),
+    }
+}
+
+/// Same as `chunked_copy_iter`, but returns [`Error::InvalidArgument`] instead of panicking if
+/// `mtu` is zero, as not even a single byte fits into a chunk.
+#[inline]
+pub fn try_chunked_copy_iter(src: &[u8], mtu: usize) -> Result<ChunkedCopy<'_>, Error> {
+    let mtu = NonZeroUsize::new(mtu).ok_or(Error::InvalidArgument { name: "mtu" })?;
+    Ok(ChunkedCopy {
+        remaining: src,
+        mtu,
+    })
+}
+
+impl ChunkedCopy<'_> {
+    /// Copy the next chunk to the start of `frame` and return its length, or return `None`
+    /// if all chunks have been consumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` is shorter than the next chunk.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn copy_next(&mut self, frame: &mut [u8]) -> Option<usize> {
+        match self.try_copy_next(frame) {
+            Ok(copied) => copied,
+            Err(_) => panic!("frame too small"),
+        }
+    }
+
+    /// Same as `copy_next`, but returns [`Error::LimitExceeded`]
+    /// without consuming the chunk instead of panicking if `frame` is shorter than the next
+    /// chunk.
+    #[inline]
+    pub fn try_copy_next(&mut self, frame: &mut [u8]) -> Result<Option<usize>, Error> {
+        let len = self.mtu.get().min(self.remaining.len());
+        if len > frame.len() {
+            return Err(Error::LimitExceeded {
+                len,
+                limit: frame.len(),
+            });
+        }
+        let Some(chunk) = self.next() else {
+            return Ok(None);
+        };
+        unsafe { rep_movs(chunk.as_ptr(), frame.as_mut_ptr(), chunk.len()) };
+        Ok(Some(chunk.len()))
+    }
+}
+
+impl<'a> Iterator for ChunkedCopy<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let (chunk, rest) = self
+            .remaining
+            .split_at(self.mtu.get().min(self.remaining.len()));
+        self.remaining = rest;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining.len().div_ceil(self.mtu.get());
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for ChunkedCopy<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_packets() {
+        let mut arena = [0_u8; 10];
+        let mut offsets = [usize::MAX; 4];
+        let packets: [&[u8]; 4] = [b"abc", b"", b"defg", b"hijk"];
+        assert_eq!(copy_packets(&packets, &mut arena, &mut offsets), 3);
+        assert_eq!(offsets, [0, 3, 3, usize::MAX]);
+        assert_eq!(&arena[..7], b"abcdefg");
+
+        assert_eq!(
+            copy_packets(&packets[3..], &mut arena, &mut offsets[..1]),
+            1
+        );
+        assert_eq!(&arena[..4], b"hijk");
+        assert_eq!(copy_packets(&[], &mut [], &mut []), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_packets_panic() {
+        copy_packets(&[b"abc"], &mut [0; 4], &mut []);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_chunked_copy_iter() {
+        let chunks = chunked_copy_iter(b"abcdefgh", 3).collect::<Vec<_>>();
+        assert_eq!(chunks, [b"abc".as_slice(), b"def", b"gh"]);
+        assert_eq!(chunked_copy_iter(b"abcdef", 3).len(), 2);
+        assert_eq!(chunked_copy_iter(b"", 3).next(), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_chunked_copy_next() {
+        let mut frames = chunked_copy_iter(b"abcdefgh", 4);
+        let mut frame = [0_u8; 5];
+        assert_eq!(frames.copy_next(&mut frame), Some(4));
+        assert_eq!(&frame, b"abcd\0");
+        assert_eq!(frames.copy_next(&mut frame[1..]), Some(4));
+        assert_eq!(&frame, b"aefgh");
+        assert_eq!(frames.copy_next(&mut frame), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "frame too small")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_chunked_copy_next_panic() {
+        chunked_copy_iter(b"abcdefgh", 4).copy_next(&mut [0; 3]);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let mut arena = [0_u8; 4];
+        assert_eq!(
+            try_copy_packets(&[b"abc"], &mut arena, &mut []),
+            Err(Error::LenMismatch {
+                src_len: 1,
+                dst_len: 0
+            })
+        );
+        assert_eq!(try_copy_packets(&[b"abc"], &mut arena, &mut [9]), Ok(1));
+        assert_eq!(
+            try_chunked_copy_iter(b"abc", 0).err(),
+            Some(Error::InvalidArgument { name: "mtu" })
+        );
+
+        let mut frames = try_chunked_copy_iter(b"abcdefgh", 4).unwrap();
+        assert_eq!(
+            frames.try_copy_next(&mut arena[..3]),
+            Err(Error::LimitExceeded { len: 4, limit: 3 })
+        );
+        assert_eq!(&arena, b"abc\0");
+        assert_eq!(frames.try_copy_next(&mut arena), Ok(Some(4)));
+        assert_eq!(frames.try_copy_next(&mut arena), Ok(Some(4)));
+        assert_eq!(&arena, b"efgh");
+        assert_eq!(frames.try_copy_next(&mut []), Ok(None));
+    }
+}
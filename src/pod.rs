@@ -0,0 +1,224 @@
+use crate::tier::copy_small_raw;
+use crate::{ensure_aligned, rep_movs, Error, SMALL_MAX};
+use core::mem::MaybeUninit;
+
+/// Types that can be read from and written to arbitrary bytes.
+///
+/// # Safety
+///
+/// Implementing types must not contain padding bytes, every bit pattern of their size must be a
+/// valid value, and they must not contain pointers or references. This is for example the case
+/// for `#[repr(C)]` structs consisting only of fields that are `Pod`, without any padding.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl Pod for $ty {})*
+    };
+}
+
+impl_pod!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// Check that a value of type `T` fits into a buffer of `len` bytes at `offset`.
+///
+/// Otherwise returns [`Error::LimitExceeded`] with the end offset of the value as the length
+/// and the buffer length as the limit.
+#[inline]
+fn check_bounds<T>(len: usize, offset: usize) -> Result<(), Error> {
+    match offset.saturating_add(core::mem::size_of::<T>()) {
+        end if end <= len => Ok(()),
+        end => Err(Error::LimitExceeded {
+            len: end,
+            limit: len,
+        }),
+    }
+}
+
+/// Copy `len` bytes, using straight-line code for values of up to [`SMALL_MAX`] bytes.
+///
+/// # Safety
+///
+/// `src` and `dst` need to be valid for `len` bytes and must not overlap.
+#[inline(always)]
+unsafe fn copy_value(src: *const u8, dst: *mut u8, len: usize) {
+    if len <= SMALL_MAX {
+        copy_small_raw(src, dst, len)
+    } else {
+        rep_movs(src, dst, len)
+    }
+}
+
+/// Read a value of type `T` from the bytes of `buf` starting at `offset`.
+///
+/// The bytes do not need to be aligned. Since the size of `T` is known at compile time, values of
+/// up to [`SMALL_MAX`] bytes are read using a few unaligned loads. Returns
+/// [`Error::LimitExceeded`] with the end offset of the value if it does not fit into `buf`.
+#[inline]
+pub fn read_pod<T: Pod>(buf: &[u8], offset: usize) -> Result<T, Error> {
+    check_bounds::<T>(buf.len(), offset)?;
+    let mut value = MaybeUninit::<T>::uninit();
+    unsafe {
+        copy_value(
+            buf.as_ptr().add(offset),
+            value.as_mut_ptr().cast::<u8>(),
+            core::mem::size_of::<T>(),
+        );
+        Ok(value.assume_init())
+    }
+}
+
+/// Write the bytes of `value` into `buf` starting at `offset`.
+///
+/// Returns [`Error::LimitExceeded`] like [`read_pod`] and leaves `buf` unchanged if the value
+/// does not fit.
+#[inline]
+pub fn write_pod<T: Pod>(buf: &mut [u8], offset: usize, value: T) -> Result<(), Error> {
+    check_bounds::<T>(buf.len(), offset)?;
+    unsafe {
+        copy_value(
+            (&value as *const T).cast::<u8>(),
+            buf.as_mut_ptr().add(offset),
+            core::mem::size_of::<T>(),
+        )
+    };
+    Ok(())
+}
+
+/// View `bytes` as a slice of `T`.
+///
+/// Returns [`Error::Misaligned`] if `bytes` is not aligned for `T` and [`Error::LenMismatch`] if
+/// its length is not a multiple of the size of `T`, with `dst_len` being the largest multiple
+/// that would fit. Zero sized types are [`Error::Unsupported`].
+#[inline]
+pub fn cast_checked<T: Pod>(bytes: &[u8]) -> Result<&[T], Error> {
+    let size = core::mem::size_of::<T>();
+    if size == 0 {
+        return Err(Error::Unsupported);
+    }
+    ensure_aligned(bytes.as_ptr().cast::<T>())?;
+    if !bytes.len().is_multiple_of(size) {
+        return Err(Error::LenMismatch {
+            src_len: bytes.len(),
+            dst_len: bytes.len() - bytes.len() % size,
+        });
+    }
+    Ok(unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast(), bytes.len() / size) })
+}
+
+/// View `bytes` as a slice of `src.len()` elements of `T` using [`cast_checked`] and copy it
+/// into `dst` using `rep movs` of the element size.
+///
+/// In addition to the errors of [`cast_checked`], returns [`Error::LenMismatch`] if the number of
+/// elements differs from the length of `dst`. `dst` is left unchanged on errors.
+#[inline]
+pub fn copy_into_cast<T: Pod>(bytes: &[u8], dst: &mut [T]) -> Result<(), Error> {
+    let src = cast_checked::<T>(bytes)?;
+    if src.len() != dst.len() {
+        return Err(Error::LenMismatch {
+            src_len: src.len(),
+            dst_len: dst.len(),
+        });
+    }
+    unsafe { rep_movs(src.as_ptr(), dst.as_mut_ptr(), dst.len()) };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[repr(C)]
+    struct Header {
+        magic: u32,
+        flags: u16,
+        kind: u16,
+        length: u64,
+    }
+
+    unsafe impl Pod for Header {}
+
+    #[test]
+    fn test_read_write_pod() {
+        let mut buf = [0_u8; 64];
+        write_pod(&mut buf, 3, 0x1122_3344_u32).unwrap();
+        assert_eq!(&buf[3..7], &0x1122_3344_u32.to_ne_bytes());
+        assert_eq!(read_pod::<u32>(&buf, 3), Ok(0x1122_3344));
+        assert_eq!(read_pod::<u16>(&buf, 62), Ok(0));
+
+        let header = Header {
+            magic: 0xCAFE_BABE,
+            flags: 1,
+            kind: 2,
+            length: 1 << 40,
+        };
+        write_pod(&mut buf, 1, header).unwrap();
+        assert_eq!(read_pod::<Header>(&buf, 1), Ok(header));
+
+        let large = [7_u64; 6];
+        write_pod(&mut buf, 5, large).unwrap();
+        assert_eq!(read_pod::<[u64; 6]>(&buf, 5), Ok(large));
+        assert_eq!(read_pod::<[u8; 0]>(&buf, 64), Ok([]));
+    }
+
+    #[test]
+    fn test_pod_out_of_bounds() {
+        let mut buf = [0xAA_u8; 8];
+        assert_eq!(
+            read_pod::<u64>(&buf, 1),
+            Err(Error::LimitExceeded { len: 9, limit: 8 })
+        );
+        assert_eq!(
+            read_pod::<u16>(&buf, usize::MAX),
+            Err(Error::LimitExceeded {
+                len: usize::MAX,
+                limit: 8
+            })
+        );
+        assert!(write_pod(&mut buf, 6, 0_u32).is_err());
+        assert_eq!(buf, [0xAA; 8]);
+    }
+
+    #[test]
+    fn test_cast_checked() {
+        let words = [0x0102_0304_u32, 0x0506_0708, 0x090A_0B0C];
+        let bytes = unsafe { core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), 12) };
+        assert_eq!(cast_checked::<u32>(bytes), Ok(&words[..]));
+        assert_eq!(cast_checked::<u16>(&bytes[2..]).map(<[u16]>::len), Ok(5));
+        assert_eq!(
+            cast_checked::<u32>(&bytes[1..5]),
+            Err(Error::Misaligned {
+                align: 4,
+                offset: 1
+            })
+        );
+        assert_eq!(
+            cast_checked::<u32>(&bytes[..10]),
+            Err(Error::LenMismatch {
+                src_len: 10,
+                dst_len: 8
+            })
+        );
+        assert_eq!(cast_checked::<[u8; 0]>(bytes), Err(Error::Unsupported));
+    }
+
+    #[test]
+    fn test_copy_into_cast() {
+        let words = [7_u64, 8, 9];
+        let bytes = unsafe { core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), 24) };
+        let mut dst = [0_u64; 3];
+        assert_eq!(copy_into_cast(bytes, &mut dst), Ok(()));
+        assert_eq!(dst, words);
+        let mut short = [0_u64; 2];
+        assert_eq!(
+            copy_into_cast(bytes, &mut short),
+            Err(Error::LenMismatch {
+                src_len: 3,
+                dst_len: 2
+            })
+        );
+        assert_eq!(short, [0; 2]);
+    }
+}
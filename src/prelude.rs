@@ -0,0 +1,10 @@
+//! Convenience re-exports of the most commonly used items.
+//!
+//! Importing `x86_strings_ops::prelude::*` brings the [`SliceExt`] methods into scope together
+//! with the equivalent free functions.
+
+#[cfg(not(feature = "no-panic"))]
+pub use crate::{copy, mismatch};
+pub use crate::{
+    fill, position, try_copy, try_mismatch, BoundedSearch, Error, FillWith, RegisterType, SliceExt,
+};
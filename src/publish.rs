@@ -0,0 +1,82 @@
+//! Copies that publish data to, or receive data from, another thread.
+//!
+//! On x86_64 the stores of a single `rep movs` may become visible in any order among
+//! themselves, but all of them become visible before any store of a later instruction, and loads
+//! are never reordered with older loads. Regular copies therefore need no fence instructions, only
+//! the compiler must be prevented from moving memory accesses across the copy. This is different
+//! for non-temporal stores, like in [`copy_nontemporal`](crate::copy_nontemporal), which require
+//! an `sfence` before the data is published.
+
+use crate::rep_movs;
+use core::sync::atomic::{fence, Ordering};
+
+/// Copy `src` to `dst` and issue a release fence, so that a following store of a flag or index,
+/// even with relaxed ordering, publishes the copied data.
+///
+/// The fence is `fence(Ordering::Release)`, which on x86_64 does not emit any instruction and
+/// only prevents the compiler from moving the copy after the following stores.
+///
+/// # Safety
+///
+/// `dst` needs to be valid for writes of `src.len()` elements, must be properly aligned and must
+/// not overlap `src`. Other threads must not access `dst` until it was published.
+#[inline]
+pub unsafe fn copy_release<T: Copy>(src: &[T], dst: *mut T) {
+    rep_movs(src.as_ptr(), dst, src.len());
+    fence(Ordering::Release);
+}
+
+/// Issue an acquire fence and copy `dst.len()` elements from `src` to `dst`, so that data
+/// published using [`copy_release`] is visible after observing the flag or index, even if that
+/// was loaded with relaxed ordering.
+///
+/// The fence is `fence(Ordering::Acquire)`, which on x86_64 does not emit any instruction and
+/// only prevents the compiler from moving the copy before the preceding loads.
+///
+/// # Safety
+///
+/// `src` needs to be valid for reads of `dst.len()` elements, must be properly aligned and must
+/// not be modified during the copy.
+#[inline]
+pub unsafe fn load_acquire_copy<T: Copy>(src: *const T, dst: &mut [T]) {
+    fence(Ordering::Acquire);
+    rep_movs(src, dst.as_mut_ptr(), dst.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::UnsafeCell;
+    use core::sync::atomic::AtomicBool;
+
+    struct Mailbox {
+        ready: AtomicBool,
+        data: UnsafeCell<[u64; 64]>,
+    }
+
+    unsafe impl Sync for Mailbox {}
+
+    #[test]
+    fn test_copy_release_message_passing() {
+        for round in 0..100_u64 {
+            let mailbox = Mailbox {
+                ready: AtomicBool::new(false),
+                data: UnsafeCell::new([0; 64]),
+            };
+            let message = [round; 64];
+            let mailbox = &mailbox;
+            std::thread::scope(|scope| {
+                scope.spawn(move || {
+                    unsafe { copy_release(&message, mailbox.data.get().cast::<u64>()) };
+                    mailbox.ready.store(true, Ordering::Relaxed);
+                });
+                while !mailbox.ready.load(Ordering::Relaxed) {
+                    std::thread::yield_now();
+                }
+                let mut received = [u64::MAX; 64];
+                unsafe { load_acquire_copy(mailbox.data.get().cast::<u64>(), &mut received) };
+                assert_eq!(received, message);
+            });
+        }
+    }
+}
@@ -0,0 +1,507 @@
+use crate::{rep_movs, Error};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Copy `len` elements from `src` into the ring buffer `ring` of `capacity` elements, starting at
+/// index `start` and continuing at the beginning of the ring when reaching its end.
+///
+/// # Safety
+///
+/// `ring` needs to be valid for writes of `capacity` elements, `src` needs to be valid for reads
+/// of `len` elements, `start < capacity` and `len <= capacity`.
+#[inline]
+unsafe fn copy_to_wrapping_raw<T: Copy>(
+    ring: *mut T,
+    capacity: usize,
+    start: usize,
+    src: *const T,
+    len: usize,
+) {
+    let first = len.min(capacity - start);
+    rep_movs(src, ring.add(start), first);
+    rep_movs(src.add(first), ring, len - first);
+}
+
+/// Copy `len` elements out of the ring buffer `ring` of `capacity` elements into `dst`, starting at
+/// index `start` and continuing at the beginning of the ring when reaching its end.
+///
+/// # Safety
+///
+/// `ring` needs to be valid for reads of `capacity` elements, `dst` needs to be valid for writes
+/// of `len` elements, `start < capacity` and `len <= capacity`.
+#[inline]
+unsafe fn copy_from_wrapping_raw<T: Copy>(
+    ring: *const T,
+    capacity: usize,
+    start: usize,
+    dst: *mut T,
+    len: usize,
+) {
+    let first = len.min(capacity - start);
+    rep_movs(ring.add(start), dst, first);
+    rep_movs(ring, dst.add(first), len - first);
+}
+
+/// Validate a wrapping copy of `len` elements starting at `start` in a ring of `capacity`
+/// elements, and return whether there is anything to copy.
+///
+/// Returns [`Error::LimitExceeded`] with the length and the capacity if the copy is longer than
+/// the ring, or with the start and the capacity if the copy is not empty and `start` is not a
+/// valid index into the ring.
+#[inline]
+fn check_wrapping(capacity: usize, start: usize, len: usize) -> Result<bool, Error> {
+    if len > capacity {
+        return Err(Error::LimitExceeded {
+            len,
+            limit: capacity,
+        });
+    }
+    if len == 0 {
+        return Ok(false);
+    }
+    if start >= capacity {
+        return Err(Error::LimitExceeded {
+            len: start,
+            limit: capacity,
+        });
+    }
+    Ok(true)
+}
+
+/// Panic with the message matching an error of [`check_wrapping`].
+#[cfg(not(feature = "no-panic"))]
+#[cold]
+fn wrapping_panic(err: Error, capacity: usize) -> ! {
+    match err {
+        Error::LimitExceeded { len, .. } if len > capacity => {
+            panic!("length exceeds ring capacity")
+        }
+        _ => panic!("start out of bounds"),
+    }
+}
+
+/// Copy `src` into `ring` starting at index `start`, continuing at the beginning of `ring` when
+/// reaching its end.
+///
+/// The copy is done using at most two `rep movs` instructions.
+///
+/// # Panics
+///
+/// Panics if `src` is longer than `ring` or if `start` is not a valid index into `ring`.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_to_wrapping<T: Copy>(ring: &mut [T], start: usize, src: &[T]) {
+    if let Err(err) = try_copy_to_wrapping(ring, start, src) {
+        wrapping_panic(err, ring.len())
+    }
+}
+
+/// Same as `copy_to_wrapping`, but returns [`Error::LimitExceeded`] without modifying `ring`
+/// instead of panicking if `src` is longer than `ring` or if `start` is not a valid index into
+/// `ring`.
+#[inline]
+pub fn try_copy_to_wrapping<T: Copy>(ring: &mut [T], start: usize, src: &[T]) -> Result<(), Error> {
+    if !check_wrapping(ring.len(), start, src.len())? {
+        return Ok(());
+    }
+    unsafe {
+        copy_to_wrapping_raw(
+            ring.as_mut_ptr(),
+            ring.len(),
+            start,
+            src.as_ptr(),
+            src.len(),
+        )
+    }
+    Ok(())
+}
+
+/// Fill `dst` with elements of `ring` starting at index `start`, continuing at the beginning of
+/// `ring` when reaching its end.
+///
+/// The copy is done using at most two `rep movs` instructions.
+///
+/// # Panics
+///
+/// Panics if `dst` is longer than `ring` or if `start` is not a valid index into `ring`.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_from_wrapping<T: Copy>(ring: &[T], start: usize, dst: &mut [T]) {
+    if let Err(err) = try_copy_from_wrapping(ring, start, dst) {
+        wrapping_panic(err, ring.len())
+    }
+}
+
+/// Same as `copy_from_wrapping`, but returns [`Error::LimitExceeded`] without modifying `dst`
+/// instead of panicking if `dst` is longer than `ring` or if `start` is not a valid index into
+/// `ring`.
+#[inline]
+pub fn try_copy_from_wrapping<T: Copy>(
+    ring: &[T],
+    start: usize,
+    dst: &mut [T],
+) -> Result<(), Error> {
+    if !check_wrapping(ring.len(), start, dst.len())? {
+        return Ok(());
+    }
+    unsafe {
+        copy_from_wrapping_raw(
+            ring.as_ptr(),
+            ring.len(),
+            start,
+            dst.as_mut_ptr(),
+            dst.len(),
+        )
+    }
+    Ok(())
+}
+
+#[repr(C, align(64))]
+struct CachePadded(AtomicUsize);
+
+/// Control block at the start of the memory region of a ring.
+///
+/// The positions count the total number of bytes written and read, modulo `usize::MAX + 1`,
+/// and are kept in separate cache lines to avoid false sharing between producer and consumer.
+#[repr(C)]
+struct RingHeader {
+    write: CachePadded,
+    read: CachePadded,
+}
+
+/// Size in bytes of the control block at the start of the memory region of a ring.
+pub const RING_HEADER_SIZE: usize = core::mem::size_of::<RingHeader>();
+
+/// Split `region` into the header and the data area of a ring.
+///
+/// # Safety
+///
+/// `region` needs to be valid for reads and writes of `len` bytes.
+unsafe fn ring_parts(
+    region: *mut u8,
+    len: usize,
+) -> Result<(*const RingHeader, *mut u8, usize), Error> {
+    let align = core::mem::align_of::<RingHeader>();
+    let offset = region as usize % align;
+    if offset != 0 {
+        return Err(Error::Misaligned { align, offset });
+    }
+    if len <= RING_HEADER_SIZE {
+        return Err(Error::LimitExceeded {
+            len: RING_HEADER_SIZE + 1,
+            limit: len,
+        });
+    }
+    Ok((
+        region.cast::<RingHeader>(),
+        region.add(RING_HEADER_SIZE),
+        len - RING_HEADER_SIZE,
+    ))
+}
+
+/// Panic with the message matching an error of [`ring_parts`].
+#[cfg(not(feature = "no-panic"))]
+#[cold]
+fn ring_parts_panic(err: Error) -> ! {
+    match err {
+        Error::Misaligned { .. } => panic!("region is not aligned to 64 bytes"),
+        _ => panic!("region too small"),
+    }
+}
+
+/// Writing side of a single producer, single consumer byte ring.
+///
+/// The ring lives in a memory region supplied by the caller, which can for example be mapped
+/// into the address spaces of two processes. The region starts with a control block of
+/// [`RING_HEADER_SIZE`] bytes, followed by the data area.
+#[derive(Debug)]
+pub struct RingProducer {
+    header: *const RingHeader,
+    data: *mut u8,
+    capacity: usize,
+}
+
+impl RingProducer {
+    /// Initialize an empty ring in the `len` bytes at `region` and return its producer.
+    ///
+    /// # Safety
+    ///
+    /// See [`try_create`](RingProducer::try_create).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `region` is not aligned to 64 bytes or if `len` is not larger than
+    /// [`RING_HEADER_SIZE`].
+    #[cfg(not(feature = "no-panic"))]
+    pub unsafe fn create(region: *mut u8, len: usize) -> Self {
+        match Self::try_create(region, len) {
+            Ok(producer) => producer,
+            Err(err) => ring_parts_panic(err),
+        }
+    }
+
+    /// Same as `create`, but returns [`Error::Misaligned`] if `region`
+    /// is not aligned to 64 bytes and [`Error::LimitExceeded`] if `len` is not larger than
+    /// [`RING_HEADER_SIZE`] instead of panicking.
+    ///
+    /// # Safety
+    ///
+    /// `region` needs to be valid for reads and writes of `len` bytes for the lifetime of the
+    /// producer and must not be accessed other than through a single [`RingProducer`] and a
+    /// single [`RingConsumer`]. The consumer must only be attached after the ring was created.
+    pub unsafe fn try_create(region: *mut u8, len: usize) -> Result<Self, Error> {
+        let (header, data, capacity) = ring_parts(region, len)?;
+        header.cast_mut().write(RingHeader {
+            write: CachePadded(AtomicUsize::new(0)),
+            read: CachePadded(AtomicUsize::new(0)),
+        });
+        Ok(Self {
+            header,
+            data,
+            capacity,
+        })
+    }
+
+    /// Number of bytes of the data area.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of bytes that can currently be written without overwriting unread data.
+    pub fn available(&self) -> usize {
+        let header = unsafe { &*self.header };
+        let write = header.write.0.load(Ordering::Relaxed);
+        let read = header.read.0.load(Ordering::Acquire);
+        self.capacity - write.wrapping_sub(read)
+    }
+
+    /// Write as much of `src` as currently fits into the ring and return the number of bytes
+    /// that were written.
+    pub fn write(&mut self, src: &[u8]) -> usize {
+        let header = unsafe { &*self.header };
+        let write = header.write.0.load(Ordering::Relaxed);
+        let read = header.read.0.load(Ordering::Acquire);
+        let len = src.len().min(self.capacity - write.wrapping_sub(read));
+        if len > 0 {
+            let start = write % self.capacity;
+            unsafe { copy_to_wrapping_raw(self.data, self.capacity, start, src.as_ptr(), len) };
+            header
+                .write
+                .0
+                .store(write.wrapping_add(len), Ordering::Release);
+        }
+        len
+    }
+}
+
+/// Reading side of a single producer, single consumer byte ring.
+///
+/// See [`RingProducer`] for the layout of the memory region.
+#[derive(Debug)]
+pub struct RingConsumer {
+    header: *const RingHeader,
+    data: *const u8,
+    capacity: usize,
+}
+
+impl RingConsumer {
+    /// Attach to a ring that was initialized using [`RingProducer::try_create`].
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`RingProducer::try_create`] apply, and the ring must have
+    /// been created in the same region with the same length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `region` is not aligned to 64 bytes or if `len` is not larger than
+    /// [`RING_HEADER_SIZE`].
+    #[cfg(not(feature = "no-panic"))]
+    pub unsafe fn attach(region: *mut u8, len: usize) -> Self {
+        match Self::try_attach(region, len) {
+            Ok(consumer) => consumer,
+            Err(err) => ring_parts_panic(err),
+        }
+    }
+
+    /// Same as `attach`, but returns an error instead of panicking, see
+    /// [`try_create`](RingProducer::try_create).
+    ///
+    /// # Safety
+    ///
+    /// See [`try_create`](RingProducer::try_create), the ring must have been created in the
+    /// same region with the same length.
+    pub unsafe fn try_attach(region: *mut u8, len: usize) -> Result<Self, Error> {
+        let (header, data, capacity) = ring_parts(region, len)?;
+        Ok(Self {
+            header,
+            data,
+            capacity,
+        })
+    }
+
+    /// Number of bytes of the data area.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of bytes that are currently available for reading.
+    pub fn len(&self) -> usize {
+        let header = unsafe { &*self.header };
+        let read = header.read.0.load(Ordering::Relaxed);
+        let write = header.write.0.load(Ordering::Acquire);
+        write.wrapping_sub(read)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read as many bytes as are available, up to the length of `dst`, and return the number
+    /// of bytes that were read.
+    pub fn read(&mut self, dst: &mut [u8]) -> usize {
+        let header = unsafe { &*self.header };
+        let read = header.read.0.load(Ordering::Relaxed);
+        let write = header.write.0.load(Ordering::Acquire);
+        let len = dst.len().min(write.wrapping_sub(read));
+        if len > 0 {
+            let start = read % self.capacity;
+            unsafe {
+                copy_from_wrapping_raw(self.data, self.capacity, start, dst.as_mut_ptr(), len)
+            };
+            header
+                .read
+                .0
+                .store(read.wrapping_add(len), Ordering::Release);
+        }
+        len
+    }
+}
+
+unsafe impl Send for RingProducer {}
+unsafe impl Send for RingConsumer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C, align(64))]
+    struct Region([u8; RING_HEADER_SIZE + 10]);
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_wrapping() {
+        let mut ring = [0_u16; 5];
+        copy_to_wrapping(&mut ring, 3, &[1, 2, 3, 4]);
+        assert_eq!(ring, [3, 4, 0, 1, 2]);
+        copy_to_wrapping(&mut ring, 0, &[]);
+
+        let mut dst = [0_u16; 5];
+        copy_from_wrapping(&ring, 3, &mut dst);
+        assert_eq!(dst, [1, 2, 3, 4, 0]);
+        copy_from_wrapping(&ring, 1, &mut dst[..2]);
+        assert_eq!(dst, [4, 0, 3, 4, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "length exceeds ring capacity")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_to_wrapping_panic() {
+        copy_to_wrapping(&mut [0_u8; 2], 0, &[1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "start out of bounds")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_from_wrapping_panic() {
+        copy_from_wrapping(&[0_u8; 2], 2, &mut [0]);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let mut ring = [0_u8; 2];
+        assert_eq!(
+            try_copy_to_wrapping(&mut ring, 0, &[1, 2, 3]),
+            Err(Error::LimitExceeded { len: 3, limit: 2 })
+        );
+        assert_eq!(
+            try_copy_from_wrapping(&ring, 2, &mut [0]),
+            Err(Error::LimitExceeded { len: 2, limit: 2 })
+        );
+        assert_eq!(try_copy_from_wrapping(&ring, 2, &mut []), Ok(()));
+        assert_eq!(try_copy_to_wrapping(&mut ring, 1, &[1, 2]), Ok(()));
+        assert_eq!(ring, [2, 1]);
+
+        let mut region = Region([0; RING_HEADER_SIZE + 10]);
+        let ptr = region.0.as_mut_ptr();
+        assert_eq!(
+            unsafe { RingProducer::try_create(ptr.add(1), 64) }.err(),
+            Some(Error::Misaligned {
+                align: 64,
+                offset: 1
+            })
+        );
+        assert_eq!(
+            unsafe { RingConsumer::try_attach(ptr, RING_HEADER_SIZE) }.err(),
+            Some(Error::LimitExceeded {
+                len: RING_HEADER_SIZE + 1,
+                limit: RING_HEADER_SIZE
+            })
+        );
+    }
+
+    #[test]
+    fn test_ring() {
+        let mut region = Region([0; RING_HEADER_SIZE + 10]);
+        let ptr = region.0.as_mut_ptr();
+        let len = region.0.len();
+        let mut producer = unsafe { RingProducer::try_create(ptr, len) }.unwrap();
+        let mut consumer = unsafe { RingConsumer::try_attach(ptr, len) }.unwrap();
+        assert_eq!(producer.capacity(), 10);
+        assert!(consumer.is_empty());
+
+        assert_eq!(producer.write(b"abcdefgh"), 8);
+        assert_eq!(producer.write(b"ijkl"), 2);
+        assert_eq!(producer.available(), 0);
+
+        let mut dst = [0_u8; 6];
+        assert_eq!(consumer.read(&mut dst), 6);
+        assert_eq!(&dst, b"abcdef");
+        assert_eq!(producer.write(b"klmnop"), 6);
+        assert_eq!(consumer.len(), 10);
+
+        let mut dst = [0_u8; 16];
+        assert_eq!(consumer.read(&mut dst), 10);
+        assert_eq!(&dst[..10], b"ghijklmnop");
+        assert_eq!(consumer.read(&mut dst), 0);
+    }
+
+    #[test]
+    fn test_ring_threads() {
+        let mut region = Region([0; RING_HEADER_SIZE + 10]);
+        let ptr = region.0.as_mut_ptr();
+        let len = region.0.len();
+        let mut producer = unsafe { RingProducer::try_create(ptr, len) }.unwrap();
+        let mut consumer = unsafe { RingConsumer::try_attach(ptr, len) }.unwrap();
+        let expected = (0..10_000_u32).map(|i| i as u8).collect::<Vec<_>>();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut remaining = expected.as_slice();
+                while !remaining.is_empty() {
+                    let written = producer.write(&remaining[..remaining.len().min(7)]);
+                    if written == 0 {
+                        std::thread::yield_now();
+                    }
+                    remaining = &remaining[written..];
+                }
+            });
+            let mut received = Vec::new();
+            let mut buffer = [0_u8; 3];
+            while received.len() < expected.len() {
+                let read = consumer.read(&mut buffer);
+                if read == 0 {
+                    std::thread::yield_now();
+                }
+                received.extend_from_slice(&buffer[..read]);
+            }
+            assert_eq!(received, expected);
+        });
+    }
+}
@@ -0,0 +1,290 @@
+use crate::nontemporal::{copy_nontemporal_unfenced, store_fence};
+use crate::{rep_movs, Error};
+
+/// Check that a buffer of `len` bytes contains `rows` rows with the given `pitch` and `row_len`.
+///
+/// Returns [`Error::LimitExceeded`] with the row length and the pitch if the rows overlap, or
+/// with the required and the actual length if the buffer is too short.
+#[inline]
+fn check_rows(len: usize, pitch: usize, row_len: usize, rows: usize) -> Result<(), Error> {
+    let required = match rows {
+        0 => 0,
+        1 => row_len,
+        _ if row_len > pitch => {
+            return Err(Error::LimitExceeded {
+                len: row_len,
+                limit: pitch,
+            })
+        }
+        _ => (rows - 1).saturating_mul(pitch).saturating_add(row_len),
+    };
+    if required > len {
+        return Err(Error::LimitExceeded {
+            len: required,
+            limit: len,
+        });
+    }
+    Ok(())
+}
+
+/// Panic with the message matching an error of [`check_rows`].
+#[cfg(not(feature = "no-panic"))]
+#[cold]
+fn rows_panic(pitches: [usize; 2], row_len: usize, rows: usize) -> ! {
+    if rows > 1 && pitches.iter().any(|&pitch| row_len > pitch) {
+        panic!("row length exceeds pitch")
+    }
+    panic!("buffer too short for rows")
+}
+
+/// Copy a `width` by `height` bytes rectangle from `src` into a write-combining mapping at `dst`,
+/// for example a memory mapped framebuffer.
+///
+/// Consecutive rows start `src_pitch` bytes apart in `src` and `dst_pitch` bytes apart in `dst`.
+/// Every row is written using non-temporal stores, which combine into full cache line writes and
+/// do not require reading the destination. A single `sfence` after the last row makes all
+/// stores visible before any later stores, for example to a register that triggers scanout.
+///
+/// # Safety
+///
+/// See [`try_blit_to_mmio`].
+///
+/// # Panics
+///
+/// Panics if `width` exceeds one of the pitches or if `src` is too short to contain `height`
+/// rows.
+#[cfg(not(feature = "no-panic"))]
+pub unsafe fn blit_to_mmio(
+    dst: *mut u8,
+    dst_pitch: usize,
+    src: &[u8],
+    src_pitch: usize,
+    width: usize,
+    height: usize,
+) {
+    if try_blit_to_mmio(dst, dst_pitch, src, src_pitch, width, height).is_err() {
+        rows_panic([src_pitch, dst_pitch], width, height)
+    }
+}
+
+/// Same as `blit_to_mmio`, but returns [`Error::LimitExceeded`] without writing to `dst`
+/// instead of panicking if `width` exceeds one of the pitches or if `src` is too short to
+/// contain `height` rows.
+///
+/// # Safety
+///
+/// `dst` needs to be valid for writes of `(height - 1) * dst_pitch + width` bytes and must not
+/// overlap `src`.
+pub unsafe fn try_blit_to_mmio(
+    dst: *mut u8,
+    dst_pitch: usize,
+    src: &[u8],
+    src_pitch: usize,
+    width: usize,
+    height: usize,
+) -> Result<(), Error> {
+    check_rows(usize::MAX, dst_pitch, width, height)?;
+    check_rows(src.len(), src_pitch, width, height)?;
+    for row in 0..height {
+        copy_nontemporal_unfenced(
+            src.as_ptr().add(row * src_pitch),
+            dst.add(row * dst_pitch),
+            width,
+        );
+    }
+    store_fence();
+    Ok(())
+}
+
+/// Shared implementation of [`try_repack_rows`] and [`try_repack_rows_nt`].
+#[inline]
+fn repack(
+    src: &[u8],
+    src_pitch: usize,
+    dst: &mut [u8],
+    dst_pitch: usize,
+    row_bytes: usize,
+    rows: usize,
+    nontemporal: bool,
+) -> Result<(), Error> {
+    check_rows(src.len(), src_pitch, row_bytes, rows)?;
+    check_rows(dst.len(), dst_pitch, row_bytes, rows)?;
+    let (src, dst) = (src.as_ptr(), dst.as_mut_ptr());
+    let copy = |src: *const u8, dst: *mut u8, len: usize| unsafe {
+        if nontemporal {
+            copy_nontemporal_unfenced(src, dst, len)
+        } else {
+            rep_movs(src, dst, len)
+        }
+    };
+    if src_pitch == dst_pitch {
+        let len = match rows {
+            0 => 0,
+            _ => (rows - 1) * src_pitch + row_bytes,
+        };
+        copy(src, dst, len);
+    } else {
+        for row in 0..rows {
+            copy(
+                src.wrapping_add(row * src_pitch),
+                dst.wrapping_add(row * dst_pitch),
+                row_bytes,
+            );
+        }
+    }
+    if nontemporal {
+        store_fence();
+    }
+    Ok(())
+}
+
+/// Copy `rows` rows of `row_bytes` bytes from `src` into `dst`, where consecutive rows start
+/// `src_pitch` and `dst_pitch` bytes apart.
+///
+/// If both pitches are equal the whole block, including the padding between rows, is copied
+/// using a single `rep movs` instruction. Otherwise every row is copied separately.
+///
+/// # Panics
+///
+/// Panics if `row_bytes` exceeds one of the pitches or if `src` or `dst` are too short to
+/// contain `rows` rows.
+#[cfg(not(feature = "no-panic"))]
+pub fn repack_rows(
+    src: &[u8],
+    src_pitch: usize,
+    dst: &mut [u8],
+    dst_pitch: usize,
+    row_bytes: usize,
+    rows: usize,
+) {
+    if try_repack_rows(src, src_pitch, dst, dst_pitch, row_bytes, rows).is_err() {
+        rows_panic([src_pitch, dst_pitch], row_bytes, rows)
+    }
+}
+
+/// Same as `repack_rows`, but returns [`Error::LimitExceeded`] without modifying `dst` instead
+/// of panicking if `row_bytes` exceeds one of the pitches or if `src` or `dst` are too short to
+/// contain `rows` rows.
+#[inline]
+pub fn try_repack_rows(
+    src: &[u8],
+    src_pitch: usize,
+    dst: &mut [u8],
+    dst_pitch: usize,
+    row_bytes: usize,
+    rows: usize,
+) -> Result<(), Error> {
+    repack(src, src_pitch, dst, dst_pitch, row_bytes, rows, false)
+}
+
+/// Same as [`repack_rows`], but writes `dst` using non-temporal stores followed by an `sfence`.
+///
+/// This is preferable when `dst` is write-combining memory, for example a GPU upload heap, or
+/// when it will not be read again by the CPU.
+///
+/// # Panics
+///
+/// Panics if `row_bytes` exceeds one of the pitches or if `src` or `dst` are too short to
+/// contain `rows` rows.
+#[cfg(not(feature = "no-panic"))]
+pub fn repack_rows_nt(
+    src: &[u8],
+    src_pitch: usize,
+    dst: &mut [u8],
+    dst_pitch: usize,
+    row_bytes: usize,
+    rows: usize,
+) {
+    if try_repack_rows_nt(src, src_pitch, dst, dst_pitch, row_bytes, rows).is_err() {
+        rows_panic([src_pitch, dst_pitch], row_bytes, rows)
+    }
+}
+
+/// Same as `repack_rows_nt`, but returns [`Error::LimitExceeded`] without modifying `dst`
+/// instead of panicking, see [`try_repack_rows`].
+#[inline]
+pub fn try_repack_rows_nt(
+    src: &[u8],
+    src_pitch: usize,
+    dst: &mut [u8],
+    dst_pitch: usize,
+    row_bytes: usize,
+    rows: usize,
+) -> Result<(), Error> {
+    repack(src, src_pitch, dst, dst_pitch, row_bytes, rows, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_blit_to_mmio() {
+        let src = (0..30_u8).collect::<Vec<_>>();
+        let mut framebuffer = [0_u8; 32];
+        unsafe { blit_to_mmio(framebuffer.as_mut_ptr(), 16, &src, 10, 9, 2) };
+        assert_eq!(&framebuffer[..9], &src[..9]);
+        assert_eq!(&framebuffer[9..16], &[0; 7]);
+        assert_eq!(&framebuffer[16..25], &src[10..19]);
+        assert_eq!(&framebuffer[25..], &[0; 7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "row length exceeds pitch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_blit_to_mmio_panic() {
+        let mut framebuffer = [0_u8; 32];
+        unsafe { blit_to_mmio(framebuffer.as_mut_ptr(), 8, &[0; 32], 10, 9, 2) };
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_repack_rows() {
+        let src = (1..=12_u8).collect::<Vec<_>>();
+        let mut dst = [0_u8; 12];
+        repack_rows(&src, 4, &mut dst, 6, 3, 2);
+        assert_eq!(dst, [1, 2, 3, 0, 0, 0, 5, 6, 7, 0, 0, 0]);
+
+        let mut dst = [0_u8; 12];
+        repack_rows_nt(&src, 6, &mut dst, 3, 3, 2);
+        assert_eq!(dst, [1, 2, 3, 7, 8, 9, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_repack_rows_equal_pitch() {
+        let src = (1..=12_u8).collect::<Vec<_>>();
+        let mut dst = [0_u8; 12];
+        repack_rows(&src, 4, &mut dst, 4, 3, 3);
+        assert_eq!(dst, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 0]);
+        repack_rows(&src, 4, &mut dst, 4, 0, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_repack_rows_panic() {
+        repack_rows(&[0; 12], 4, &mut [0; 10], 4, 3, 3);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let mut dst = [0_u8; 10];
+        assert_eq!(
+            try_repack_rows(&[1; 12], 4, &mut dst, 4, 3, 3),
+            Err(Error::LimitExceeded { len: 11, limit: 10 })
+        );
+        assert_eq!(
+            try_repack_rows_nt(&[1; 12], 4, &mut dst, 2, 3, 3),
+            Err(Error::LimitExceeded { len: 3, limit: 2 })
+        );
+        assert_eq!(
+            unsafe { try_blit_to_mmio(dst.as_mut_ptr(), 4, &[1; 8], 4, 3, 3) },
+            Err(Error::LimitExceeded { len: 11, limit: 8 })
+        );
+        assert_eq!(dst, [0; 10]);
+        assert_eq!(try_repack_rows(&[1; 8], 4, &mut dst, 5, 3, 2), Ok(()));
+        assert_eq!(dst, [1, 1, 1, 0, 0, 1, 1, 1, 0, 0]);
+    }
+}
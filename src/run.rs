@@ -0,0 +1,398 @@
+use crate::{rep_movs, rep_scas, repe_scas, Error, RegisterType};
+use core::cmp::Ordering;
+
+/// Copy `src` into `dst`, collapsing each run of consecutive equal elements into a single element,
+/// and return the number of elements written to `dst`.
+///
+/// Stretches of unique elements are copied using `rep movs`, the remaining elements of each run
+/// are skipped using `repe scas`. Elements are compared bitwise.
+///
+/// # Panics
+///
+/// Panics if `dst` is shorter than `src`.
+#[cfg(not(feature = "no-panic"))]
+pub fn dedup_consecutive_into<T: RegisterType>(src: &[T], dst: &mut [T]) -> usize {
+    match try_dedup_consecutive_into(src, dst) {
+        Ok(written) => written,
+        Err(_) => panic!("destination too short"),
+    }
+}
+
+/// Same as `dedup_consecutive_into`, but returns [`Error::LimitExceeded`] without modifying
+/// `dst` instead of panicking if `dst` is shorter than `src`.
+pub fn try_dedup_consecutive_into<T: RegisterType>(
+    src: &[T],
+    dst: &mut [T],
+) -> Result<usize, Error> {
+    if dst.len() < src.len() {
+        return Err(Error::LimitExceeded {
+            len: src.len(),
+            limit: dst.len(),
+        });
+    }
+    let len = src.len();
+    let mut written = 0;
+    let mut index = 0;
+    while index < len {
+        let start = index;
+        while index + 1 < len && !src[index].bitwise_eq(&src[index + 1]) {
+            index += 1;
+        }
+        // src[index] is the last element of the unique stretch and the first element of a run
+        let count = index + 1 - start;
+        unsafe {
+            rep_movs(
+                src.as_ptr().add(start),
+                dst.as_mut_ptr().add(written),
+                count,
+            )
+        };
+        written += count;
+        let rest = len - index - 1;
+        let run = unsafe { repe_scas(src.as_ptr().add(index + 1), src[index], rest) };
+        index += 1 + run.unwrap_or(rest);
+    }
+    Ok(written)
+}
+
+/// Return the number of leading elements of `run` that satisfy `pred`, which has to be true for
+/// a prefix of `run` and false for the remainder.
+///
+/// The prefix is first bracketed by probing at exponentially growing distances and then located
+/// using a binary search, so long prefixes are found in logarithmic time.
+#[inline]
+fn gallop<T: Copy>(run: &[T], mut pred: impl FnMut(T) -> bool) -> usize {
+    let mut len = 0;
+    let mut step = 1;
+    while len + step <= run.len() && pred(run[len + step - 1]) {
+        len += step;
+        step *= 2;
+    }
+    let end = (len + step - 1).min(run.len());
+    len + run[len..end].partition_point(|&x| pred(x))
+}
+
+/// Merge the sorted slices `a` and `b` into `dst`.
+///
+/// The merge is stable, for equal elements the ones from `a` are written first. Instead of
+/// comparing element by element, the length of each run of elements that is taken from the same
+/// input is determined using a galloping search and the run is copied using `rep movs`, which is
+/// much faster when the inputs consist of long runs, as is typical in external sorting.
+///
+/// Elements are compared using [`PartialOrd`], the result for float inputs containing `NaN` is
+/// unspecified but still contains all input elements.
+///
+/// # Panics
+///
+/// Panics if the length of `dst` is not the sum of the lengths of `a` and `b`.
+#[cfg(not(feature = "no-panic"))]
+pub fn merge_sorted_runs<T: RegisterType + PartialOrd>(a: &[T], b: &[T], dst: &mut [T]) {
+    if try_merge_sorted_runs(a, b, dst).is_err() {
+        panic!("length mismatch");
+    }
+}
+
+/// Same as `merge_sorted_runs`, but returns [`Error::LenMismatch`] without modifying `dst`
+/// instead of panicking if the length of `dst` is not the sum of the lengths of `a` and `b`.
+pub fn try_merge_sorted_runs<T: RegisterType + PartialOrd>(
+    a: &[T],
+    b: &[T],
+    dst: &mut [T],
+) -> Result<(), Error> {
+    if a.len().checked_add(b.len()) != Some(dst.len()) {
+        return Err(Error::LenMismatch {
+            src_len: a.len().saturating_add(b.len()),
+            dst_len: dst.len(),
+        });
+    }
+    let mut written = 0;
+    let mut append = |run: &[T]| {
+        unsafe { rep_movs(run.as_ptr(), dst.as_mut_ptr().add(written), run.len()) };
+        written += run.len();
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let head = b[j];
+        let count = gallop(&a[i..], |x| head.partial_cmp(&x) != Some(Ordering::Less));
+        append(&a[i..i + count]);
+        i += count;
+        if i == a.len() {
+            break;
+        }
+        let head = a[i];
+        let count = gallop(&b[j..], |x| x < head);
+        append(&b[j..j + count]);
+        j += count;
+    }
+    append(&a[i..]);
+    append(&b[j..]);
+    Ok(())
+}
+
+/// Copy the elements of `src` that are less than `pivot` to the start of `lt_out` and all
+/// other elements to the start of `ge_out`, and return the number of elements written to each.
+///
+/// The relative order of the elements is preserved. Elements that are not comparable to
+/// `pivot`, like `NaN`, are written to `ge_out`. Runs of equal elements are measured using
+/// `repe scas` and copied using `rep movs`, mixed regions are partitioned element by element.
+///
+/// # Panics
+///
+/// Panics if `lt_out` or `ge_out` are too short for the elements partitioned into them. The
+/// contents of both outputs are unspecified in that case.
+#[cfg(not(feature = "no-panic"))]
+pub fn partition_copy<T: RegisterType + PartialOrd>(
+    src: &[T],
+    pivot: T,
+    lt_out: &mut [T],
+    ge_out: &mut [T],
+) -> (usize, usize) {
+    match try_partition_copy(src, pivot, lt_out, ge_out) {
+        Ok(written) => written,
+        Err(_) => panic!("output too short"),
+    }
+}
+
+/// Same as `partition_copy`, but returns [`Error::LimitExceeded`] instead of panicking if
+/// `lt_out` or `ge_out` are too short for the elements partitioned into them.
+///
+/// The error contains the number of elements that would have been written to the output
+/// including the run that did not fit, and the length of that output. The contents of both
+/// outputs are unspecified in that case.
+pub fn try_partition_copy<T: RegisterType + PartialOrd>(
+    src: &[T],
+    pivot: T,
+    lt_out: &mut [T],
+    ge_out: &mut [T],
+) -> Result<(usize, usize), Error> {
+    let len = src.len();
+    let (mut lt, mut ge) = (0, 0);
+    let mut index = 0;
+    while index < len {
+        let value = src[index];
+        let mut run = 1;
+        if index + 1 < len && src[index + 1].bitwise_eq(&value) {
+            let rest = len - index - 1;
+            run += unsafe { repe_scas(src.as_ptr().add(index + 1), value, rest) }.unwrap_or(rest);
+        }
+        let (out, written) = if value < pivot {
+            (&mut *lt_out, &mut lt)
+        } else {
+            (&mut *ge_out, &mut ge)
+        };
+        if out.len() - *written < run {
+            return Err(Error::LimitExceeded {
+                len: *written + run,
+                limit: out.len(),
+            });
+        }
+        if run == 1 {
+            out[*written] = value;
+        } else {
+            unsafe { rep_movs(src.as_ptr().add(index), out.as_mut_ptr().add(*written), run) };
+        }
+        *written += run;
+        index += run;
+    }
+    Ok((lt, ge))
+}
+
+/// Return the start index and length of the longest run of consecutive elements equal to
+/// `value` in `src`, or `(0, 0)` if `value` does not occur.
+///
+/// Runs are located using `repne scas` and measured using `repe scas` in a single pass, which
+/// stops as soon as the remainder of `src` is too short to contain a longer run. If there are
+/// multiple longest runs, the first one is returned. Elements are compared bitwise.
+pub fn longest_run_of<T: RegisterType>(src: &[T], value: T) -> (usize, usize) {
+    let len = src.len();
+    let ptr = src.as_ptr();
+    let mut longest = (0, 0);
+    let mut index = 0;
+    while len - index > longest.1 {
+        let Some(offset) = (unsafe { rep_scas(ptr.add(index), value, len - index) }) else {
+            break;
+        };
+        let start = index + offset;
+        let rest = len - start - 1;
+        let run = 1 + unsafe { repe_scas(ptr.add(start + 1), value, rest) }.unwrap_or(rest);
+        if run > longest.1 {
+            longest = (start, run);
+        }
+        index = start + run;
+    }
+    longest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(feature = "no-panic"))]
+    use crate::testing::GuardedBuf;
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_dedup_consecutive_into() {
+        let cases: [(&[u16], &[u16]); 6] = [
+            (&[], &[]),
+            (&[1], &[1]),
+            (&[1, 1, 1], &[1]),
+            (&[1, 2, 3], &[1, 2, 3]),
+            (&[1, 1, 2, 3, 3, 3, 4, 1, 1], &[1, 2, 3, 4, 1]),
+            (&[5, 6, 6, 7, 8, 9, 9], &[5, 6, 7, 8, 9]),
+        ];
+        for (src, expected) in cases {
+            let mut buf = GuardedBuf::new(src.len(), 0_u16, 0xDEAD);
+            let written = buf.run(|dst| dedup_consecutive_into(src, dst));
+            assert_eq!(&buf.as_slice()[..written], expected);
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_dedup_consecutive_into_bitwise() {
+        let src = [0.0_f64, -0.0, -0.0, f64::NAN, f64::NAN];
+        let mut dst = [1.0_f64; 5];
+        assert_eq!(dedup_consecutive_into(&src, &mut dst), 3);
+        assert!(dst[0].is_sign_positive() && dst[1].is_sign_negative() && dst[2].is_nan());
+    }
+
+    #[test]
+    #[should_panic(expected = "destination too short")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_dedup_consecutive_into_panic() {
+        dedup_consecutive_into(&[1_u8, 1, 2], &mut [0; 2]);
+    }
+
+    #[test]
+    fn test_gallop() {
+        let run = (0..100).collect::<Vec<u32>>();
+        for limit in 0..=101 {
+            assert_eq!(gallop(&run, |x| x < limit), limit.min(100) as usize);
+        }
+        assert_eq!(gallop::<u32>(&[], |_| true), 0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_merge_sorted_runs() {
+        let mut state = 0x2545_F491_u32;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        for _ in 0..200 {
+            let mut runs = [0, 0].map(|_| {
+                let len = next() as usize % 64;
+                let range = next() % 16 + 1;
+                let mut run = (0..len).map(|_| next() % range).collect::<Vec<_>>();
+                run.sort();
+                run
+            });
+            if next() % 2 == 0 {
+                runs[1].iter_mut().for_each(|x| *x += 8);
+            }
+            let [a, b] = &runs;
+            let mut expected = [a.as_slice(), b.as_slice()].concat();
+            expected.sort();
+            let mut buf = GuardedBuf::new(expected.len(), u32::MAX, 0xDEAD);
+            buf.run(|dst| merge_sorted_runs(a, b, dst));
+            assert_eq!(buf.as_slice(), expected, "a {a:?}, b {b:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_merge_sorted_runs_stable() {
+        let mut dst = [1.0_f64; 4];
+        merge_sorted_runs(&[-0.0, 1.0], &[0.0, 1.0], &mut dst);
+        assert!(dst[0].is_sign_negative() && dst[1].is_sign_positive());
+        merge_sorted_runs(&[0.0, 2.0], &[-0.0, 1.0], &mut dst);
+        assert!(dst[0].is_sign_positive() && dst[1].is_sign_negative());
+        assert_eq!(dst, [0.0, 0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_merge_sorted_runs_panic() {
+        merge_sorted_runs(&[1_u8], &[2], &mut [0; 3]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_partition_copy() {
+        let src = [5_i32, 1, 1, 1, 1, 9, 9, 9, 3, 7, 0, 5, 5];
+        let mut lt = GuardedBuf::new(src.len(), 0, -1);
+        let mut ge = GuardedBuf::new(src.len(), 0, -1);
+        let (lt_len, ge_len) =
+            lt.run(|lt_out| ge.run(|ge_out| partition_copy(&src, 5, lt_out, ge_out)));
+        assert_eq!(&lt.as_slice()[..lt_len], &[1, 1, 1, 1, 3, 0]);
+        assert_eq!(&ge.as_slice()[..ge_len], &[5, 9, 9, 9, 7, 5, 5]);
+
+        assert_eq!(partition_copy(&[], 0_u8, &mut [], &mut []), (0, 0));
+        let mut exact = [0_u8; 3];
+        assert_eq!(partition_copy(&[1, 1, 1], 0, &mut [], &mut exact), (0, 3));
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_partition_copy_nan() {
+        let src = [1.0_f64, f64::NAN, f64::NAN, -1.0];
+        let (mut lt, mut ge) = ([0.0; 4], [0.0; 4]);
+        assert_eq!(partition_copy(&src, 0.0, &mut lt, &mut ge), (1, 3));
+        assert_eq!(lt[0], -1.0);
+        assert!(ge[0] == 1.0 && ge[1].is_nan() && ge[2].is_nan());
+    }
+
+    #[test]
+    #[should_panic(expected = "output too short")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_partition_copy_panic() {
+        partition_copy(&[1_u16, 2, 2], 2, &mut [0; 3], &mut [0; 1]);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let mut dst = [0_u8; 2];
+        assert_eq!(
+            try_dedup_consecutive_into(&[1, 1, 2], &mut dst),
+            Err(Error::LimitExceeded { len: 3, limit: 2 })
+        );
+        assert_eq!(try_dedup_consecutive_into(&[1, 1], &mut dst), Ok(1));
+        assert_eq!(
+            try_merge_sorted_runs(&[1], &[2], &mut [0; 3]),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 3
+            })
+        );
+        assert_eq!(try_merge_sorted_runs(&[2], &[1], &mut dst), Ok(()));
+        assert_eq!(dst, [1, 2]);
+        assert_eq!(
+            try_partition_copy(&[1_u16, 2, 2], 2, &mut [0; 3], &mut [0; 1]),
+            Err(Error::LimitExceeded { len: 2, limit: 1 })
+        );
+        assert_eq!(
+            try_partition_copy(&[3, 1], 2, &mut dst, &mut [0; 1]),
+            Ok((1, 1))
+        );
+    }
+
+    #[test]
+    fn test_longest_run_of() {
+        let src = [0_u8, 1, 1, 0, 1, 1, 1, 0, 1, 1, 1];
+        assert_eq!(longest_run_of(&src, 1), (4, 3));
+        assert_eq!(longest_run_of(&src, 0), (0, 1));
+        assert_eq!(longest_run_of(&src, 2), (0, 0));
+        assert_eq!(longest_run_of::<u8>(&[], 0), (0, 0));
+        assert_eq!(longest_run_of(&[7_u64; 5], 7), (0, 5));
+
+        let mut sparse = vec![0_u32; 1000];
+        sparse[10] = 3;
+        sparse[500] = 3;
+        assert_eq!(longest_run_of(&sparse, 0), (501, 499));
+        assert_eq!(longest_run_of(&[0.0_f32, -0.0, -0.0], -0.0), (1, 2));
+    }
+}
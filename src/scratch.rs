@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+
+/// Default for [`Config::scratch_limit`](crate::Config::scratch_limit), and the limit used
+/// without the `dispatch` feature.
+pub const DEFAULT_SCRATCH_LIMIT: usize = 64 << 10;
+
+/// Maximum number of buffers retained per thread, enough for a few nested calls.
+const MAX_RETAINED: usize = 4;
+
+thread_local! {
+    static POOL: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[inline]
+pub(crate) fn scratch_limit() -> usize {
+    #[cfg(feature = "dispatch")]
+    return crate::Config::global().scratch_limit;
+    #[cfg(not(feature = "dispatch"))]
+    DEFAULT_SCRATCH_LIMIT
+}
+
+/// Call `f` with a temporary buffer of `len` bytes taken from a thread local pool.
+///
+/// Buffers are returned to the pool after `f` returns, so that repeated operations needing a
+/// bounce buffer do not allocate on every call. Nested calls receive distinct buffers. Buffers
+/// longer than the [scratch limit](crate::Config::scratch_limit) are allocated for the single
+/// call and not retained.
+///
+/// The initial contents of the buffer are unspecified, it may contain data of a previous use
+/// on the same thread.
+pub fn with_scratch<R>(len: usize, f: impl FnOnce(&mut [u8]) -> R) -> R {
+    if len > scratch_limit() {
+        return f(&mut vec![0; len]);
+    }
+    let mut buffer = POOL
+        .try_with(|pool| pool.borrow_mut().pop())
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    if buffer.len() < len {
+        buffer.resize(len, 0);
+    }
+    let result = f(&mut buffer[..len]);
+    let _ = POOL.try_with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.len() < MAX_RETAINED {
+            pool.push(buffer);
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_scratch_reuse() {
+        let first = with_scratch(100, |buf| {
+            assert_eq!(buf.len(), 100);
+            buf.fill(7);
+            buf.as_ptr()
+        });
+        let second = with_scratch(50, |buf| {
+            assert_eq!(buf.len(), 50);
+            assert!(buf.iter().all(|&b| b == 7));
+            buf.as_ptr()
+        });
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_with_scratch_nested() {
+        with_scratch(16, |outer| {
+            outer.fill(1);
+            with_scratch(16, |inner| {
+                assert_ne!(outer.as_ptr(), inner.as_ptr());
+                inner.fill(2);
+            });
+            assert!(outer.iter().all(|&b| b == 1));
+        });
+        assert_eq!(with_scratch(0, |buf| buf.len()), 0);
+    }
+
+    #[test]
+    fn test_with_scratch_over_limit() {
+        let len = scratch_limit() + 1;
+        with_scratch(len, |buf| {
+            assert_eq!(buf.len(), len);
+            buf.fill(3);
+        });
+        assert!(POOL.with_borrow(|pool| pool.iter().all(|buf| buf.len() < len)));
+    }
+}
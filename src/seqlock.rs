@@ -0,0 +1,92 @@
+use crate::rep_movs;
+use core::sync::atomic::{fence, AtomicU32, Ordering};
+
+/// Copy `dst.len()` bytes from `src`, which is protected by the sequence lock `seq`.
+///
+/// Returns `true` if the copy is consistent, meaning no writer was active before or during the
+/// copy. If `false` is returned the contents of `dst` are unspecified and the caller should
+/// retry the read.
+///
+/// The sequence number is loaded with acquire ordering before the copy, and an acquire fence
+/// separates the copy from loading the sequence number again.
+///
+/// # Safety
+///
+/// `src` needs to be valid for reads of `dst.len()` bytes. Concurrent modifications of `src`
+/// must only happen through [`seqlock_write_copy`] or an equivalent protocol.
+pub unsafe fn seqlock_read_copy(src: *const u8, dst: &mut [u8], seq: &AtomicU32) -> bool {
+    let before = seq.load(Ordering::Acquire);
+    if before & 1 != 0 {
+        return false;
+    }
+    rep_movs(src, dst.as_mut_ptr(), dst.len());
+    fence(Ordering::Acquire);
+    seq.load(Ordering::Relaxed) == before
+}
+
+/// Copy `src` into `dst`, which is protected by the sequence lock `seq`.
+///
+/// The sequence number is incremented to an odd value before the copy and to the next even
+/// value after the copy. A release fence separates the first increment from the copy, and the
+/// second increment uses release ordering.
+///
+/// # Safety
+///
+/// `dst` needs to be valid for writes of `src.len()` bytes and there must only be a single
+/// writer for `seq` at any time.
+pub unsafe fn seqlock_write_copy(src: &[u8], dst: *mut u8, seq: &AtomicU32) {
+    let current = seq.load(Ordering::Relaxed);
+    seq.store(current.wrapping_add(1), Ordering::Relaxed);
+    fence(Ordering::Release);
+    rep_movs(src.as_ptr(), dst, src.len());
+    seq.store(current.wrapping_add(2), Ordering::Release);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seqlock_copy() {
+        let seq = AtomicU32::new(0);
+        let mut shared = [0_u8; 8];
+        let mut dst = [0_u8; 8];
+        unsafe {
+            seqlock_write_copy(b"snapshot", shared.as_mut_ptr(), &seq);
+            assert_eq!(seq.load(Ordering::Relaxed), 2);
+            assert!(seqlock_read_copy(shared.as_ptr(), &mut dst, &seq));
+        }
+        assert_eq!(&dst, b"snapshot");
+    }
+
+    #[test]
+    fn test_seqlock_read_during_write() {
+        let seq = AtomicU32::new(5);
+        let shared = [1_u8; 4];
+        let mut dst = [0_u8; 4];
+        assert!(!unsafe { seqlock_read_copy(shared.as_ptr(), &mut dst, &seq) });
+    }
+
+    #[test]
+    fn test_seqlock_concurrent() {
+        let seq = AtomicU32::new(0);
+        let mut shared = [0_u8; 256];
+        let ptr = shared.as_mut_ptr() as usize;
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for i in 0..1000_u32 {
+                    let src = [i as u8; 256];
+                    unsafe { seqlock_write_copy(&src, ptr as *mut u8, &seq) };
+                }
+            });
+            let mut consistent = 0;
+            while consistent < 100 {
+                let mut dst = [0_u8; 256];
+                if unsafe { seqlock_read_copy(ptr as *const u8, &mut dst, &seq) } {
+                    assert!(dst.iter().all(|&b| b == dst[0]));
+                    consistent += 1;
+                }
+            }
+        });
+    }
+}
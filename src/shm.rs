@@ -0,0 +1,176 @@
+//! Copies into memory segments that are shared with other processes, for example mappings
+//! of a `memfd` or of a file in `/dev/shm`.
+//!
+//! On x86_64 the copies are done using inline assembly, which the compiler treats as having
+//! unknown side effects on memory. The stores can therefore not be elided or merged, the same
+//! guarantee that `write_volatile` gives for single values. On other architectures and under
+//! Miri every byte is written using `write_volatile` instead.
+
+use crate::Error;
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+use crate::{copy_nontemporal, rep_movs};
+use core::sync::atomic::{fence, Ordering};
+
+/// Writable view of a memory region shared with other processes.
+#[derive(Debug)]
+pub struct SharedSegment {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl SharedSegment {
+    /// Create a segment for the `len` bytes starting at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` needs to be valid for writes of `len` bytes for the lifetime of the segment.
+    /// The memory may be concurrently read by other processes, but must not be accessed through
+    /// Rust references while the segment exists.
+    pub unsafe fn from_raw_parts(ptr: *mut u8, len: usize) -> Self {
+        Self { ptr, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Return a pointer to `offset`, or [`Error::LimitExceeded`] if the `len` bytes starting
+    /// there are not within the segment.
+    #[inline]
+    fn range_ptr(&mut self, offset: usize, len: usize) -> Result<*mut u8, Error> {
+        if offset > self.len || len > self.len - offset {
+            return Err(Error::LimitExceeded {
+                len: offset.saturating_add(len),
+                limit: self.len,
+            });
+        }
+        Ok(unsafe { self.ptr.add(offset) })
+    }
+}
+
+/// Copy `len` bytes from `src` to `dst` without allowing the compiler to elide or merge the
+/// stores, using non-temporal stores if `nontemporal` is set.
+///
+/// # Safety
+///
+/// `src` and `dst` need to be valid for `len` bytes and must not overlap.
+#[inline]
+unsafe fn copy_unelided(src: *const u8, dst: *mut u8, len: usize, nontemporal: bool) {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    if nontemporal {
+        copy_nontemporal(src, dst, len)
+    } else {
+        rep_movs(src, dst, len)
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    {
+        let _ = nontemporal;
+        for i in 0..len {
+            dst.add(i).write_volatile(src.add(i).read());
+        }
+    }
+}
+
+/// Copy `src` into `segment` at byte `offset` and publish it.
+///
+/// After the copy a release fence is issued, so that a following store to a flag or sequence
+/// number, using at least relaxed ordering, makes the copied data visible to a consumer that
+/// reads this flag with acquire ordering.
+///
+/// # Panics
+///
+/// Panics if `offset + src.len()` exceeds the length of the segment.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_into_shared(segment: &mut SharedSegment, offset: usize, src: &[u8]) {
+    if try_copy_into_shared(segment, offset, src).is_err() {
+        panic!("range out of bounds")
+    }
+}
+
+/// Same as `copy_into_shared`, but returns [`Error::LimitExceeded`] without writing to
+/// `segment` instead of panicking if `offset + src.len()` exceeds the length of the segment.
+#[inline]
+pub fn try_copy_into_shared(
+    segment: &mut SharedSegment,
+    offset: usize,
+    src: &[u8],
+) -> Result<(), Error> {
+    let dst = segment.range_ptr(offset, src.len())?;
+    unsafe { copy_unelided(src.as_ptr(), dst, src.len(), false) };
+    fence(Ordering::Release);
+    Ok(())
+}
+
+/// Copy `src` into `segment` at byte `offset` using non-temporal stores and publish it.
+///
+/// Non-temporal stores are weakly ordered and are not covered by a release fence, this function
+/// therefore uses [`copy_nontemporal`](crate::copy_nontemporal), which ends with an `sfence`,
+/// before the release fence. This avoids polluting the cache of the producer for data it does
+/// not read again.
+///
+/// # Panics
+///
+/// Panics if `offset + src.len()` exceeds the length of the segment.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_into_shared_nt(segment: &mut SharedSegment, offset: usize, src: &[u8]) {
+    if try_copy_into_shared_nt(segment, offset, src).is_err() {
+        panic!("range out of bounds")
+    }
+}
+
+/// Same as `copy_into_shared_nt`, but returns [`Error::LimitExceeded`] without writing to
+/// `segment` instead of panicking if `offset + src.len()` exceeds the length of the segment.
+#[inline]
+pub fn try_copy_into_shared_nt(
+    segment: &mut SharedSegment,
+    offset: usize,
+    src: &[u8],
+) -> Result<(), Error> {
+    let dst = segment.range_ptr(offset, src.len())?;
+    unsafe { copy_unelided(src.as_ptr(), dst, src.len(), true) };
+    fence(Ordering::Release);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_into_shared() {
+        let mut memory = vec![0_u8; 64];
+        {
+            let mut segment = unsafe { SharedSegment::from_raw_parts(memory.as_mut_ptr(), 64) };
+            assert_eq!(try_copy_into_shared(&mut segment, 4, b"hello"), Ok(()));
+            assert_eq!(try_copy_into_shared_nt(&mut segment, 20, &[1; 40]), Ok(()));
+            assert_eq!(try_copy_into_shared(&mut segment, 64, &[]), Ok(()));
+            assert_eq!(
+                try_copy_into_shared_nt(&mut segment, 62, &[1; 4]),
+                Err(Error::LimitExceeded { len: 66, limit: 64 })
+            );
+            assert_eq!(
+                try_copy_into_shared(&mut segment, 65, &[]),
+                Err(Error::LimitExceeded { len: 65, limit: 64 })
+            );
+        }
+        assert_eq!(&memory[..10], b"\0\0\0\0hello\0");
+        assert_eq!(&memory[20..60], &[1; 40]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range out of bounds")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_into_shared_panic() {
+        let mut memory = vec![0_u8; 8];
+        let mut segment = unsafe { SharedSegment::from_raw_parts(memory.as_mut_ptr(), 8) };
+        copy_into_shared(&mut segment, 4, b"hello");
+    }
+}
@@ -1,10 +1,200 @@
-use crate::{rep_cmps, rep_movs, rep_scas, rep_stos, RegisterType};
+use crate::masked::mismatch_masked;
+use crate::{
+    position_gt, position_lt, position_not_any, rep_cmps, rep_cmps_reverse, rep_movs,
+    rep_movs_overlapping, rep_scas, rep_scas_reverse, rep_stos, repe_scas, repne_cmps, ByteSet,
+    CopyOutcome, Error, OrderedType, RegisterType,
+};
+use core::cmp::Ordering;
+use core::ops::Range;
+
+/// Result of a search that only looks at a limited number of elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundedSearch {
+    /// A match was found at the given index.
+    Found(usize),
+    /// No match was found before the limit, the remainder of the slice was not searched.
+    NotFoundWithinLimit,
+    /// The whole slice was searched without finding a match.
+    NotFound,
+}
+
+impl BoundedSearch {
+    #[inline]
+    fn new(position: Option<usize>, limited: bool) -> Self {
+        match position {
+            Some(index) => BoundedSearch::Found(index),
+            None if limited => BoundedSearch::NotFoundWithinLimit,
+            None => BoundedSearch::NotFound,
+        }
+    }
+}
+
+/// Source of the elements for [`SliceExt::inline_fill_with`].
+///
+/// Generic code that only sometimes produces a constant can pass both variants through the same
+/// call, constant fills still use `rep stos`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillWith<T, F> {
+    /// Every element is set to the same value.
+    Const(T),
+    /// Every element is set to the next value returned by the closure, in order.
+    With(F),
+}
+
+impl<T> FillWith<T, fn() -> T> {
+    /// Constant fill that does not need to name a closure type.
+    #[inline]
+    pub const fn constant(value: T) -> Self {
+        FillWith::Const(value)
+    }
+}
 
 pub trait SliceExt<T: RegisterType> {
     fn inline_fill(&mut self, value: T);
+    /// Fill the slice with elements from `source`, using `rep stos` for [`FillWith::Const`].
+    fn inline_fill_with<F: FnMut() -> T>(&mut self, source: FillWith<T, F>);
+    /// Fill the first `min(len, max)` elements with `value` and return their count.
+    ///
+    /// The fill counterpart of [`copy_truncated`](crate::copy_truncated), for writers with a
+    /// fixed budget that would otherwise slice the destination at every call site.
+    fn inline_fill_at_most(&mut self, value: T, max: usize) -> usize;
+    /// Same as `inline_fill_at_most`, but returns a [`CopyOutcome`] that also reports whether
+    /// the slice is longer than `max` and the fill was truncated.
+    fn try_inline_fill_at_most(&mut self, value: T, max: usize) -> CopyOutcome;
     fn inline_position(&self, value: T) -> Option<usize>;
+    fn inline_position_within(&self, value: T, max_len: usize) -> BoundedSearch;
+    /// Return the index of the first element that is not equal to `value`, using `repe scas`,
+    /// for example to skip padding or a run of repeated delimiters.
+    ///
+    /// This is the single implementation of the search, `inline_mismatch_value` is an alias and
+    /// `inline_count_leading` returns the result as a length.
+    fn inline_position_not(&self, value: T) -> Option<usize>;
+    /// Return the length of the leading run of `value`, for example to strip leading zeros or
+    /// spaces.
+    ///
+    /// Same as `inline_position_not`, but returns the length of the slice if all elements are
+    /// equal to `value`.
+    fn inline_count_leading(&self, value: T) -> usize;
+    /// Return the index of the first element that is not contained in `values`.
+    ///
+    /// A single value uses `repe scas`, byte sized elements are classified using a [`ByteSet`].
+    fn inline_position_not_any(&self, values: &[T]) -> Option<usize>;
+    /// Return the index of the first element greater than `value`, see [`position_gt`].
+    fn inline_position_gt(&self, value: T) -> Option<usize>
+    where
+        T: OrderedType;
+    /// Return the index of the first element less than `value`, see [`position_lt`].
+    fn inline_position_lt(&self, value: T) -> Option<usize>
+    where
+        T: OrderedType;
+    /// Return the index of the `n`th occurrence of `value`, counting from zero.
+    fn inline_position_nth(&self, value: T, n: usize) -> Option<usize>;
+    /// Return the index of the last occurrence of `value`, using a backward `repne scas`.
+    fn inline_rposition(&self, value: T) -> Option<usize>;
+    /// Return the index of the `n`th occurrence of `value` counting backwards from the end,
+    /// `n == 0` returns the last occurrence.
+    fn inline_rposition_nth(&self, value: T, n: usize) -> Option<usize>;
+    /// Return the start index of the first occurrence of `needle`.
+    ///
+    /// An empty `needle` matches at index 0.
+    fn inline_find_subslice(&self, needle: &[T]) -> Option<usize>;
+    /// Return the start index of the last occurrence of `needle`.
+    ///
+    /// An empty `needle` matches at the end of the slice.
+    fn inline_rfind_subslice(&self, needle: &[T]) -> Option<usize>;
+    #[cfg(not(feature = "no-panic"))]
     fn inline_copy_from(&mut self, other: &[T]);
+    /// Copy `other` into this slice, or return an error if the lengths differ.
+    fn try_inline_copy_from(&mut self, other: &[T]) -> Result<(), Error>;
+    /// Copy the elements in `src` to the range starting at `dst`, which may overlap, like
+    /// [`slice::copy_within`].
+    ///
+    /// Overlapping ranges with `dst` after the start of `src` are copied backwards using
+    /// [`rep_movs_backward`](crate::rep_movs_backward).
+    ///
+    /// # Panics
+    ///
+    /// Panics if either range is out of bounds of the slice, or if `src` starts after its end.
+    #[cfg(not(feature = "no-panic"))]
+    fn inline_copy_within(&mut self, src: Range<usize>, dst: usize);
+    /// Same as `inline_copy_within`, but returns [`Error::LimitExceeded`] instead of panicking
+    /// if the end of either range exceeds the length of the slice, or [`Error::InvalidRange`]
+    /// if `src` starts after its end.
+    fn try_inline_copy_within(&mut self, src: Range<usize>, dst: usize) -> Result<(), Error>;
+    #[cfg(not(feature = "no-panic"))]
     fn inline_mismatch(&self, other: &[T]) -> Option<usize>;
+    /// Same as `inline_mismatch`, but returns an error instead of panicking if the lengths
+    /// differ. The `src_len` of the error is the length of `other`.
+    fn try_inline_mismatch(&self, other: &[T]) -> Result<Option<usize>, Error>;
+    #[cfg(not(feature = "no-panic"))]
+    fn inline_mismatch_within(&self, other: &[T], max_len: usize) -> BoundedSearch;
+    /// Return the index of the first element that is not equal to `value`, like comparing
+    /// against a slice filled with `value` without materializing it.
+    ///
+    /// Alias of [`inline_position_not`](SliceExt::inline_position_not) named after the
+    /// comparison methods, for example to verify that a region still contains a poison value.
+    #[doc(alias = "inline_position_not")]
+    fn inline_mismatch_value(&self, value: T) -> Option<usize>;
+    /// Return the first index at which this slice and `other` contain equal elements, the
+    /// opposite of `inline_mismatch`.
+    ///
+    /// Only the common prefix of both slices is compared.
+    fn inline_match_position(&self, other: &[T]) -> Option<usize>;
+    /// Compare this slice lexicographically to `other`, like `memcmp` followed by comparing the
+    /// lengths.
+    ///
+    /// The first mismatch in the common prefix is found using `repe cmps`, only the two
+    /// mismatching elements are then compared, see [`OrderedType`] for the order of floats.
+    fn inline_cmp(&self, other: &[T]) -> Ordering
+    where
+        T: OrderedType;
+    /// Return whether this slice and `other` have the same length and contents, using
+    /// `repe cmps`.
+    ///
+    /// Unlike `inline_mismatch` this never panics. Elements are compared bitwise, so floats
+    /// differ from `==` for NaNs and signed zeros.
+    fn inline_eq(&self, other: &[T]) -> bool;
+    /// Return whether this slice starts with `prefix`, comparing only the first `prefix.len()`
+    /// elements using `repe cmps`. Returns `false` if `prefix` is longer than this slice.
+    fn inline_starts_with(&self, prefix: &[T]) -> bool;
+    /// Return whether this slice ends with `suffix`, comparing only the last `suffix.len()`
+    /// elements using `repe cmps`. Returns `false` if `suffix` is longer than this slice.
+    fn inline_ends_with(&self, suffix: &[T]) -> bool;
+    /// Return the length of the longest common prefix of this slice and `other`, which may have
+    /// different lengths, using `repe cmps` on the shorter length.
+    fn inline_common_prefix_len(&self, other: &[T]) -> usize;
+    /// Return the length of the longest common suffix of this slice and `other`, which may have
+    /// different lengths, using a backward `repe cmps` on the shorter length.
+    fn inline_common_suffix_len(&self, other: &[T]) -> usize;
+    /// Same as `inline_mismatch_within`, but returns an error instead of panicking if the
+    /// lengths differ.
+    fn try_inline_mismatch_within(
+        &self,
+        other: &[T],
+        max_len: usize,
+    ) -> Result<BoundedSearch, Error>;
+    /// Return the index of the first element for which `(self ^ other) & mask` is not zero, so
+    /// that bits cleared in `mask` are ignored in the comparison.
+    ///
+    /// The comparison is bitwise and uses SSE2 on x86_64.
+    #[cfg(not(feature = "no-panic"))]
+    fn inline_mismatch_masked(&self, other: &[T], mask: &[T]) -> Option<usize>;
+    /// Same as `inline_mismatch_masked`, but returns an error instead of panicking if the
+    /// lengths differ.
+    fn try_inline_mismatch_masked(&self, other: &[T], mask: &[T]) -> Result<Option<usize>, Error>;
+}
+
+/// Return the common length of `dst` and `src`, or the error describing their mismatch.
+#[inline]
+fn same_len<T>(src: &[T], dst: &[T]) -> Result<usize, Error> {
+    if src.len() == dst.len() {
+        Ok(dst.len())
+    } else {
+        Err(Error::LenMismatch {
+            src_len: src.len(),
+            dst_len: dst.len(),
+        })
+    }
 }
 
 impl<T: RegisterType> SliceExt<T> for [T] {
@@ -13,28 +203,380 @@ impl<T: RegisterType> SliceExt<T> for [T] {
         unsafe { rep_stos(value, self.as_mut_ptr(), self.len()) }
     }
 
+    #[inline]
+    fn inline_fill_at_most(&mut self, value: T, max: usize) -> usize {
+        let len = self.len().min(max);
+        unsafe { rep_stos(value, self.as_mut_ptr(), len) };
+        len
+    }
+
+    #[inline]
+    fn try_inline_fill_at_most(&mut self, value: T, max: usize) -> CopyOutcome {
+        let copied = self.inline_fill_at_most(value, max);
+        CopyOutcome {
+            copied,
+            truncated: copied < self.len(),
+        }
+    }
+
+    #[inline]
+    fn inline_fill_with<F: FnMut() -> T>(&mut self, source: FillWith<T, F>) {
+        match source {
+            FillWith::Const(value) => self.inline_fill(value),
+            FillWith::With(mut f) => self.iter_mut().for_each(|element| *element = f()),
+        }
+    }
+
     #[inline]
     fn inline_position(&self, value: T) -> Option<usize> {
         unsafe { rep_scas(self.as_ptr(), value, self.len()) }
     }
 
+    #[inline]
+    fn inline_position_within(&self, value: T, max_len: usize) -> BoundedSearch {
+        let len = self.len().min(max_len);
+        let position = unsafe { rep_scas(self.as_ptr(), value, len) };
+        BoundedSearch::new(position, len < self.len())
+    }
+
+    #[inline]
+    fn inline_position_not(&self, value: T) -> Option<usize> {
+        unsafe { repe_scas(self.as_ptr(), value, self.len()) }
+    }
+
+    #[inline]
+    fn inline_count_leading(&self, value: T) -> usize {
+        self.inline_position_not(value).unwrap_or(self.len())
+    }
+
+    #[inline]
+    fn inline_position_not_any(&self, values: &[T]) -> Option<usize> {
+        match values {
+            [value] => self.inline_position_not(*value),
+            _ if core::mem::size_of::<T>() == 1 => {
+                let as_bytes = |slice: &[T]| unsafe {
+                    core::slice::from_raw_parts(slice.as_ptr().cast::<u8>(), slice.len())
+                };
+                position_not_any(as_bytes(self), &ByteSet::new(as_bytes(values)))
+            }
+            _ => self
+                .iter()
+                .position(|x| !values.iter().any(|value| value.bitwise_eq(x))),
+        }
+    }
+
+    #[inline]
+    fn inline_position_gt(&self, value: T) -> Option<usize>
+    where
+        T: OrderedType,
+    {
+        position_gt(self, value)
+    }
+
+    #[inline]
+    fn inline_position_lt(&self, value: T) -> Option<usize>
+    where
+        T: OrderedType,
+    {
+        position_lt(self, value)
+    }
+
+    #[inline]
+    fn inline_position_nth(&self, value: T, n: usize) -> Option<usize> {
+        let mut start = 0;
+        for _ in 0..n {
+            start += self[start..].inline_position(value)? + 1;
+        }
+        self[start..]
+            .inline_position(value)
+            .map(|index| start + index)
+    }
+
+    #[inline]
+    fn inline_rposition(&self, value: T) -> Option<usize> {
+        unsafe { rep_scas_reverse(self.as_ptr(), value, self.len()) }
+    }
+
+    #[inline]
+    fn inline_rposition_nth(&self, value: T, n: usize) -> Option<usize> {
+        let mut end = self.len();
+        for _ in 0..=n {
+            end = self[..end].inline_rposition(value)?;
+        }
+        Some(end)
+    }
+
+    fn inline_find_subslice(&self, needle: &[T]) -> Option<usize> {
+        let Some(&first) = needle.first() else {
+            return Some(0);
+        };
+        let end = self.len().checked_sub(needle.len())? + 1;
+        let mut start = 0;
+        while let Some(index) = self[start..end].inline_position(first) {
+            let index = start + index;
+            let candidate = &self[index..index + needle.len()];
+            if unsafe { rep_cmps(candidate.as_ptr(), needle.as_ptr(), needle.len()) }.is_none() {
+                return Some(index);
+            }
+            start = index + 1;
+        }
+        None
+    }
+
+    fn inline_rfind_subslice(&self, needle: &[T]) -> Option<usize> {
+        let Some(&first) = needle.first() else {
+            return Some(self.len());
+        };
+        let mut end = self.len().checked_sub(needle.len())? + 1;
+        while let Some(index) = self[..end].inline_rposition(first) {
+            let candidate = &self[index..index + needle.len()];
+            if unsafe { rep_cmps(candidate.as_ptr(), needle.as_ptr(), needle.len()) }.is_none() {
+                return Some(index);
+            }
+            end = index;
+        }
+        None
+    }
+
+    #[cfg(not(feature = "no-panic"))]
     fn inline_copy_from(&mut self, other: &[T]) {
-        let len = self.len();
-        assert_eq!(len, other.len(), "length mismatch");
-        unsafe { rep_movs(other.as_ptr(), self.as_mut_ptr(), len) }
+        assert!(self.try_inline_copy_from(other).is_ok(), "length mismatch");
     }
 
+    #[inline]
+    fn try_inline_copy_from(&mut self, other: &[T]) -> Result<(), Error> {
+        let len = same_len(other, self)?;
+        unsafe { rep_movs(other.as_ptr(), self.as_mut_ptr(), len) };
+        Ok(())
+    }
+
+    #[cfg(not(feature = "no-panic"))]
+    #[inline]
+    fn inline_copy_within(&mut self, src: Range<usize>, dst: usize) {
+        if let Err(error) = self.try_inline_copy_within(src, dst) {
+            panic!("{error}");
+        }
+    }
+
+    #[inline]
+    fn try_inline_copy_within(&mut self, src: Range<usize>, dst: usize) -> Result<(), Error> {
+        let Range { start, end } = src;
+        if start > end {
+            return Err(Error::InvalidRange { start, end });
+        }
+        let count = end - start;
+        let dst_end = dst.saturating_add(count);
+        if end.max(dst_end) > self.len() {
+            return Err(Error::LimitExceeded {
+                len: end.max(dst_end),
+                limit: self.len(),
+            });
+        }
+        let ptr = self.as_mut_ptr();
+        unsafe { rep_movs_overlapping(ptr.add(start), ptr.add(dst), count) };
+        Ok(())
+    }
+
+    #[cfg(not(feature = "no-panic"))]
     #[inline]
     fn inline_mismatch(&self, other: &[T]) -> Option<usize> {
-        let len = self.len();
-        assert_eq!(len, other.len(), "length mismatch");
-        unsafe { rep_cmps(self.as_ptr(), other.as_ptr(), len) }
+        match self.try_inline_mismatch(other) {
+            Ok(position) => position,
+            Err(_) => panic!("length mismatch"),
+        }
+    }
+
+    #[inline]
+    fn try_inline_mismatch(&self, other: &[T]) -> Result<Option<usize>, Error> {
+        let len = same_len(other, self)?;
+        Ok(unsafe { rep_cmps(self.as_ptr(), other.as_ptr(), len) })
+    }
+
+    #[cfg(not(feature = "no-panic"))]
+    #[inline]
+    fn inline_mismatch_within(&self, other: &[T], max_len: usize) -> BoundedSearch {
+        match self.try_inline_mismatch_within(other, max_len) {
+            Ok(search) => search,
+            Err(_) => panic!("length mismatch"),
+        }
+    }
+
+    #[inline]
+    fn inline_mismatch_value(&self, value: T) -> Option<usize> {
+        self.inline_position_not(value)
+    }
+
+    #[inline]
+    fn inline_match_position(&self, other: &[T]) -> Option<usize> {
+        let len = self.len().min(other.len());
+        unsafe { repne_cmps(self.as_ptr(), other.as_ptr(), len) }
+    }
+
+    #[inline]
+    fn inline_cmp(&self, other: &[T]) -> Ordering
+    where
+        T: OrderedType,
+    {
+        let len = self.len().min(other.len());
+        match unsafe { rep_cmps(self.as_ptr(), other.as_ptr(), len) } {
+            Some(index) => self[index].total_cmp(&other[index]),
+            None => self.len().cmp(&other.len()),
+        }
+    }
+
+    #[inline]
+    fn inline_eq(&self, other: &[T]) -> bool {
+        self.len() == other.len()
+            && unsafe { rep_cmps(self.as_ptr(), other.as_ptr(), self.len()) }.is_none()
+    }
+
+    #[inline]
+    fn inline_starts_with(&self, prefix: &[T]) -> bool {
+        self.get(..prefix.len())
+            .is_some_and(|head| head.inline_eq(prefix))
+    }
+
+    #[inline]
+    fn inline_ends_with(&self, suffix: &[T]) -> bool {
+        self.len()
+            .checked_sub(suffix.len())
+            .and_then(|start| self.get(start..))
+            .is_some_and(|tail| tail.inline_eq(suffix))
+    }
+
+    #[inline]
+    fn inline_common_prefix_len(&self, other: &[T]) -> usize {
+        let len = self.len().min(other.len());
+        unsafe { rep_cmps(self.as_ptr(), other.as_ptr(), len) }.unwrap_or(len)
+    }
+
+    #[inline]
+    fn inline_common_suffix_len(&self, other: &[T]) -> usize {
+        let len = self.len().min(other.len());
+        let (a, b) = (&self[self.len() - len..], &other[other.len() - len..]);
+        match unsafe { rep_cmps_reverse(a.as_ptr(), b.as_ptr(), len) } {
+            Some(index) => len - index - 1,
+            None => len,
+        }
+    }
+
+    #[inline]
+    fn try_inline_mismatch_within(
+        &self,
+        other: &[T],
+        max_len: usize,
+    ) -> Result<BoundedSearch, Error> {
+        let len = same_len(other, self)?.min(max_len);
+        let position = unsafe { rep_cmps(self.as_ptr(), other.as_ptr(), len) };
+        Ok(BoundedSearch::new(position, len < self.len()))
+    }
+
+    #[cfg(not(feature = "no-panic"))]
+    #[inline]
+    fn inline_mismatch_masked(&self, other: &[T], mask: &[T]) -> Option<usize> {
+        match self.try_inline_mismatch_masked(other, mask) {
+            Ok(position) => position,
+            Err(_) => panic!("length mismatch"),
+        }
+    }
+
+    #[inline]
+    fn try_inline_mismatch_masked(&self, other: &[T], mask: &[T]) -> Result<Option<usize>, Error> {
+        same_len(other, self)?;
+        same_len(mask, self)?;
+        let position = unsafe {
+            mismatch_masked(
+                self.as_ptr().cast(),
+                other.as_ptr().cast(),
+                mask.as_ptr().cast(),
+                core::mem::size_of_val(self),
+            )
+        };
+        Ok(position.map(|byte| byte / core::mem::size_of::<T>()))
     }
 }
 
+/// Return the length of the longest prefix shared by all `slices`.
+///
+/// Every slice is only compared against the prefix shared by all previous slices, so the
+/// amount of compared elements shrinks as the common prefix gets shorter. Returns 0 if
+/// `slices` is empty.
+pub fn common_prefix_len<T: RegisterType>(slices: &[&[T]]) -> usize {
+    let Some((first, rest)) = slices.split_first() else {
+        return 0;
+    };
+    let mut prefix = first.len();
+    for slice in rest {
+        if prefix == 0 {
+            break;
+        }
+        prefix = first[..prefix].inline_common_prefix_len(slice);
+    }
+    prefix
+}
+
+/// Define free functions that forward to the methods of [`SliceExt`], converting their slice
+/// arguments using [`AsRef`] and [`AsMut`].
+///
+/// Parameters are declared as `slice`, `slice_mut` or `value`, the latter being passed through
+/// as an element of type `T`.
+macro_rules! free_functions {
+    ($(
+        $(#[$attr:meta])*
+        pub fn $name:ident($($param:ident: $kind:ident),*) -> $ret:ty = $method:ident($($arg:ident),*);
+    )*) => {$(
+        $(#[$attr])*
+        #[inline]
+        pub fn $name<T: RegisterType>($($param: free_functions!(@type $kind)),*) -> $ret {
+            $(free_functions!(@bind $param $kind);)*
+            SliceExt::$method($($arg),*)
+        }
+    )*};
+    (@type slice) => { impl AsRef<[T]> };
+    (@type slice_mut) => { impl AsMut<[T]> };
+    (@type value) => { T };
+    (@bind $param:ident slice) => { let $param = $param.as_ref(); };
+    (@bind $param:ident slice_mut) => { let mut $param = $param; let $param = $param.as_mut(); };
+    (@bind $param:ident value) => {};
+}
+
+free_functions! {
+    /// Fill `dst` with `value`, see [`SliceExt::inline_fill`].
+    pub fn fill(dst: slice_mut, value: value) -> () = inline_fill(dst, value);
+    /// Return the index of the first occurrence of `value` in `src`, see
+    /// [`SliceExt::inline_position`].
+    pub fn position(src: slice, value: value) -> Option<usize> = inline_position(src, value);
+    /// Copy `src` into `dst`, see [`SliceExt::inline_copy_from`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` and `dst` have different lengths.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn copy(src: slice, dst: slice_mut) -> () = inline_copy_from(dst, src);
+    /// Copy `src` into `dst`, or return an error if their lengths differ.
+    pub fn try_copy(src: slice, dst: slice_mut) -> Result<(), Error> =
+        try_inline_copy_from(dst, src);
+    /// Return the index of the first element that differs between `a` and `b`, see
+    /// [`SliceExt::inline_mismatch`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` and `b` have different lengths.
+    #[cfg(not(feature = "no-panic"))]
+    pub fn mismatch(a: slice, b: slice) -> Option<usize> = inline_mismatch(a, b);
+    /// Same as `mismatch`, but returns an error if the lengths of `a` and `b` differ, see
+    /// [`SliceExt::try_inline_mismatch`].
+    pub fn try_mismatch(a: slice, b: slice) -> Result<Option<usize>, Error> =
+        try_inline_mismatch(a, b);
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::SliceExt;
+    use crate::testing::GuardedBuf;
+    use crate::{
+        common_prefix_len, BoundedSearch, CopyOutcome, Error, FillWith, RegisterType, SliceExt,
+    };
+    use core::cmp::Ordering;
 
     #[test]
     fn test_fill() {
@@ -42,6 +584,73 @@ mod tests {
         a.inline_fill(42);
         assert_eq!(a, &[42_u8; 5])
     }
+
+    #[test]
+    fn test_fill_at_most() {
+        let a = &mut [0_u32; 5];
+        assert_eq!(a.inline_fill_at_most(7, 3), 3);
+        assert_eq!(a, &[7, 7, 7, 0, 0]);
+        assert_eq!(a.inline_fill_at_most(8, 10), 5);
+        assert_eq!(a, &[8; 5]);
+        assert_eq!(a[..0].inline_fill_at_most(9, 1), 0);
+
+        assert_eq!(
+            a.try_inline_fill_at_most(1, 5),
+            CopyOutcome {
+                copied: 5,
+                truncated: false
+            }
+        );
+        assert_eq!(
+            a.try_inline_fill_at_most(2, 4),
+            CopyOutcome {
+                copied: 4,
+                truncated: true
+            }
+        );
+        assert_eq!(a, &[2, 2, 2, 2, 1]);
+    }
+
+    #[test]
+    fn test_fill_guarded() {
+        for len in 0..40 {
+            let mut buf = GuardedBuf::new(len, 0_u16, 0xFFFF);
+            buf.run(|dst| dst.inline_fill(42));
+            assert_eq!(buf.as_slice(), vec![42; len]);
+        }
+    }
+    #[test]
+    fn test_fill_with() {
+        let mut buf = GuardedBuf::new(5, 0_i32, -1);
+        buf.run(|dst| dst.inline_fill_with(FillWith::constant(7)));
+        assert_eq!(buf.as_slice(), &[7; 5]);
+
+        let mut next = 0;
+        buf.run(|dst| {
+            dst.inline_fill_with(FillWith::With(|| {
+                next += 2;
+                next
+            }))
+        });
+        assert_eq!(buf.as_slice(), &[2, 4, 6, 8, 10]);
+    }
+
+    #[test]
+    fn test_fill_with_generic() {
+        fn fill_generic<T: RegisterType>(dst: &mut [T], constant: Option<T>, f: impl FnMut() -> T) {
+            let source = match constant {
+                Some(value) => FillWith::Const(value),
+                None => FillWith::With(f),
+            };
+            dst.inline_fill_with(source);
+        }
+        let mut dst = [0_u8; 4];
+        fill_generic(&mut dst, Some(3), || unreachable!());
+        assert_eq!(dst, [3; 4]);
+        fill_generic(&mut dst, None, || 9);
+        assert_eq!(dst, [9; 4]);
+    }
+
     #[test]
     fn test_position() {
         let a = &[1_u8, 2, 3, 4, 5];
@@ -51,8 +660,200 @@ mod tests {
         assert_eq!(a.inline_position(6), None);
     }
 
+    #[test]
+    fn test_position_nth() {
+        let a = b"a,b,,c,d";
+        assert_eq!(a.inline_position_nth(b',', 0), Some(1));
+        assert_eq!(a.inline_position_nth(b',', 1), Some(3));
+        assert_eq!(a.inline_position_nth(b',', 2), Some(4));
+        assert_eq!(a.inline_position_nth(b',', 3), Some(6));
+        assert_eq!(a.inline_position_nth(b',', 4), None);
+        assert_eq!(a.inline_position_nth(b'd', 0), Some(7));
+        assert_eq!(a.inline_position_nth(b'd', 1), None);
+    }
+
+    #[test]
+    fn test_rposition() {
+        let a = b"a,b,,c,d";
+        assert_eq!(a.inline_rposition(b','), Some(6));
+        assert_eq!(a.inline_rposition(b'a'), Some(0));
+        assert_eq!(a.inline_rposition(b'x'), None);
+        assert_eq!([1_u32, 2, 1, 3].inline_rposition(1), Some(2));
+        assert_eq!([0_u16; 0].inline_rposition(0), None);
+    }
+
+    #[test]
+    fn test_rposition_nth() {
+        let a = b"a,b,,c,d";
+        assert_eq!(a.inline_rposition_nth(b',', 0), Some(6));
+        assert_eq!(a.inline_rposition_nth(b',', 1), Some(4));
+        assert_eq!(a.inline_rposition_nth(b',', 2), Some(3));
+        assert_eq!(a.inline_rposition_nth(b',', 3), Some(1));
+        assert_eq!(a.inline_rposition_nth(b',', 4), None);
+        assert_eq!(a.inline_rposition_nth(b'a', 0), Some(0));
+        assert_eq!(a.inline_rposition_nth(b'a', 1), None);
+    }
+
+    #[test]
+    fn test_find_subslice() {
+        let a = b"crate::slice::SliceExt";
+        assert_eq!(a.inline_find_subslice(b"::"), Some(5));
+        assert_eq!(a.inline_find_subslice(b"crate"), Some(0));
+        assert_eq!(a.inline_find_subslice(b"Ext"), Some(19));
+        assert_eq!(a.inline_find_subslice(b"::x"), None);
+        assert_eq!(a.inline_find_subslice(b""), Some(0));
+        assert_eq!(b"ab".inline_find_subslice(b"abc"), None);
+        assert_eq!(b"aaab".inline_find_subslice(b"aab"), Some(1));
+        assert_eq!([1_u32, 2, 1, 2, 3].inline_find_subslice(&[2, 3]), Some(3));
+    }
+
+    #[test]
+    fn test_rfind_subslice() {
+        let a = b"crate::slice::SliceExt";
+        assert_eq!(a.inline_rfind_subslice(b"::"), Some(12));
+        assert_eq!(a.inline_rfind_subslice(b"crate"), Some(0));
+        assert_eq!(a.inline_rfind_subslice(b"Ext"), Some(19));
+        assert_eq!(a.inline_rfind_subslice(b"::x"), None);
+        assert_eq!(a.inline_rfind_subslice(b""), Some(a.len()));
+        assert_eq!(b"ab".inline_rfind_subslice(b"abc"), None);
+        assert_eq!(b"aaaa".inline_rfind_subslice(b"aa"), Some(2));
+        assert_eq!([1_u32, 2, 1, 2, 3].inline_rfind_subslice(&[1, 2]), Some(2));
+    }
+
+    #[test]
+    fn test_position_within() {
+        let a = &[1_u8, 2, 3, 4, 5];
+        assert_eq!(a.inline_position_within(2, 3), BoundedSearch::Found(1));
+        assert_eq!(a.inline_position_within(3, 3), BoundedSearch::Found(2));
+        assert_eq!(
+            a.inline_position_within(4, 3),
+            BoundedSearch::NotFoundWithinLimit
+        );
+        assert_eq!(a.inline_position_within(6, 5), BoundedSearch::NotFound);
+        assert_eq!(a.inline_position_within(6, 10), BoundedSearch::NotFound);
+        assert_eq!(
+            a.inline_position_within(1, 0),
+            BoundedSearch::NotFoundWithinLimit
+        );
+    }
+
+    #[test]
+    fn test_cmp() {
+        let words: [&[u8]; 7] = [b"", b"a", b"ab", b"abc", b"abd", b"b", b"\xff"];
+        for a in words {
+            for b in words {
+                assert_eq!(a.inline_cmp(b), a.cmp(b), "{a:?} {b:?}");
+            }
+        }
+        assert_eq!([1_i32, -5].inline_cmp(&[1, 3]), Ordering::Less);
+        assert_eq!([1_u16, 300].inline_cmp(&[1, 2, 3]), Ordering::Greater);
+        assert_eq!([0.0_f64].inline_cmp(&[-0.0]), Ordering::Greater);
+        assert_eq!([f32::NAN].inline_cmp(&[f32::NAN]), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_eq() {
+        assert!(b"".inline_eq(b""));
+        assert!(b"key".inline_eq(b"key"));
+        assert!(!b"key".inline_eq(b"kex"));
+        assert!(!b"key".inline_eq(b"keys"));
+        assert!(![1_u64, 2].inline_eq(&[1]));
+        assert!([f32::NAN].inline_eq(&[f32::NAN]));
+        assert!(![0.0_f32].inline_eq(&[-0.0]));
+    }
+
+    #[test]
+    fn test_starts_with() {
+        let request = b"GET /index.html HTTP/1.1";
+        assert!(request.inline_starts_with(b"GET "));
+        assert!(request.inline_starts_with(b""));
+        assert!(request.inline_starts_with(request));
+        assert!(!request.inline_starts_with(b"POST "));
+        assert!(!b"GE".inline_starts_with(b"GET"));
+        assert!([3_u32, 4, 5].inline_starts_with(&[3, 4]));
+    }
+
+    #[test]
+    fn test_ends_with() {
+        let archive = b"PK\x03\x04 ... PK\x05\x06";
+        assert!(archive.inline_ends_with(b"PK\x05\x06"));
+        assert!(archive.inline_ends_with(b""));
+        assert!(archive.inline_ends_with(archive));
+        assert!(!archive.inline_ends_with(b"PK\x03\x04"));
+        assert!(!b"\x05\x06".inline_ends_with(b"PK\x05\x06"));
+        assert!([3_i64, 4, 5].inline_ends_with(&[4, 5]));
+    }
+
+    #[test]
+    fn test_common_prefix_len_method() {
+        assert_eq!(b"interstellar".inline_common_prefix_len(b"internet"), 5);
+        assert_eq!(b"inter".inline_common_prefix_len(b"internet"), 5);
+        assert_eq!(b"internet".inline_common_prefix_len(b"inter"), 5);
+        assert_eq!(b"abc".inline_common_prefix_len(b"xbc"), 0);
+        assert_eq!(b"".inline_common_prefix_len(b"abc"), 0);
+        assert_eq!([1_u16, 2, 3].inline_common_prefix_len(&[1, 2, 3]), 3);
+    }
+
+    #[test]
+    fn test_common_suffix_len() {
+        assert_eq!(b"reading".inline_common_suffix_len(b"writing"), 3);
+        assert_eq!(b"ing".inline_common_suffix_len(b"writing"), 3);
+        assert_eq!(b"writing".inline_common_suffix_len(b"ing"), 3);
+        assert_eq!(b"abc".inline_common_suffix_len(b"abx"), 0);
+        assert_eq!(b"abc".inline_common_suffix_len(b""), 0);
+        assert_eq!([1_u32, 2, 3].inline_common_suffix_len(&[0, 1, 2, 3]), 3);
+
+        let old = b"fn main() {\n    let x = 1;\n}\n";
+        let new = b"fn main() {\n    let y = 1;\n}\n";
+        let prefix = old.inline_common_prefix_len(new);
+        let suffix = old[prefix..].inline_common_suffix_len(&new[prefix..]);
+        assert_eq!(&old[prefix..old.len() - suffix], b"x");
+    }
+
+    #[test]
+    fn test_position_gt_lt() {
+        let samples = [0.5_f32, 0.25, 1.5, -2.0];
+        assert_eq!(samples.inline_position_gt(1.0), Some(2));
+        assert_eq!(samples.inline_position_lt(0.0), Some(3));
+        assert_eq!([1_u64, 2].inline_position_gt(2), None);
+    }
+
+    #[test]
+    fn test_position_not() {
+        let record = b"    42;;;7";
+        assert_eq!(record.inline_position_not(b' '), Some(4));
+        assert_eq!(record[6..].inline_position_not(b';'), Some(3));
+        assert_eq!(b"    ".inline_position_not(b' '), None);
+        assert_eq!(b"".inline_position_not(b' '), None);
+        assert_eq!([0_u32, 0, 0, 9].inline_position_not(0), Some(3));
+    }
+
+    #[test]
+    fn test_count_leading() {
+        let big_endian = [0_u8, 0, 0, 1, 0, 255];
+        let zeros = big_endian.inline_count_leading(0);
+        assert_eq!(zeros, 3);
+        assert_eq!(&big_endian[zeros..], [1, 0, 255]);
+        assert_eq!(b"  name  ".inline_count_leading(b' '), 2);
+        assert_eq!(b"    ".inline_count_leading(b' '), 4);
+        assert_eq!(b"".inline_count_leading(b' '), 0);
+        assert_eq!([7_u64, 7, 1].inline_count_leading(7), 2);
+    }
+
+    #[test]
+    fn test_position_not_any() {
+        assert_eq!(b"aaab".inline_position_not_any(b"a"), Some(3));
+        assert_eq!(b"abcd-e".inline_position_not_any(b"abcd"), Some(4));
+        assert_eq!(b"abcd".inline_position_not_any(b"dcba"), None);
+        assert_eq!([-1_i8, 1, 0].inline_position_not_any(&[1, -1]), Some(2));
+        assert_eq!([1_u32, 2, 1, 3].inline_position_not_any(&[1, 2]), Some(3));
+        assert_eq!([1_u32, 2].inline_position_not_any(&[]), Some(0));
+        assert_eq!([0.0_f64, -0.0].inline_position_not_any(&[0.0]), Some(1));
+    }
+
     #[test]
     #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
     fn test_copy_from_panic() {
         let a = &mut [0_u8; 3];
         let b = &[1, 2, 3, 4];
@@ -60,6 +861,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "no-panic"))]
     fn test_copy_from() {
         let a = &mut [0_u8; 5];
         let b = &[1, 2, 3, 4, 5];
@@ -67,8 +869,20 @@ mod tests {
         assert_eq!(a, b)
     }
 
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_from_guarded() {
+        let src = (0..40_u32).collect::<Vec<_>>();
+        for len in 0..src.len() {
+            let mut buf = GuardedBuf::new(len, 0, u32::MAX);
+            buf.run(|dst| dst.inline_copy_from(&src[..len]));
+            assert_eq!(buf.as_slice(), &src[..len]);
+        }
+    }
+
     #[test]
     #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
     fn test_mismatch_panic() {
         let a = &mut [1_u8, 2, 3];
         let b = &[1_u8, 2];
@@ -76,6 +890,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "no-panic"))]
     fn test_mismatch() {
         let empty: [u8; 0] = [];
         assert_eq!(empty.inline_mismatch(&empty), None);
@@ -84,4 +899,199 @@ mod tests {
         assert_eq!([1_u8, 2, 3].inline_mismatch(&[1_u8, 5, 6]), Some(1));
         assert_eq!([1_u8, 2, 3].inline_mismatch(&[1_u8, 2, 4]), Some(2));
     }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_mismatch_within() {
+        let a = [1_u16, 2, 3, 4];
+        assert_eq!(
+            a.inline_mismatch_within(&[1, 5, 3, 4], 2),
+            BoundedSearch::Found(1)
+        );
+        assert_eq!(
+            a.inline_mismatch_within(&[1, 2, 5, 4], 2),
+            BoundedSearch::NotFoundWithinLimit
+        );
+        assert_eq!(
+            a.inline_mismatch_within(&[1, 2, 3, 4], 4),
+            BoundedSearch::NotFound
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_mismatch_within_panic() {
+        [1_u8, 2, 3].inline_mismatch_within(&[1, 2], 1);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_mismatch_masked() {
+        let a = [0x1234_u16, 0x5678, 0x9ABC, 0xDEF0];
+        let b = [0x1234_u16, 0x5600, 0x9ABD, 0xDEF0];
+        assert_eq!(a.inline_mismatch_masked(&b, &[0xFFFF; 4]), Some(1));
+        assert_eq!(
+            a.inline_mismatch_masked(&b, &[0xFFFF, 0xFF00, 0xFFFF, 0xFFFF]),
+            Some(2)
+        );
+        assert_eq!(
+            a.inline_mismatch_masked(&b, &[0xFFFF, 0xFF00, 0xFFFE, 0]),
+            None
+        );
+        let rows = (0..40_u64).collect::<Vec<_>>();
+        let mut other = rows.clone();
+        // differences in an ignored timestamp field of every row
+        other.iter_mut().for_each(|row| *row |= 0xFFFF << 48);
+        let mask = [u64::MAX >> 16; 40];
+        assert_eq!(rows.inline_mismatch_masked(&other, &mask), None);
+        other[33] ^= 1;
+        assert_eq!(rows.inline_mismatch_masked(&other, &mask), Some(33));
+    }
+
+    #[test]
+    fn test_try_mismatch_masked() {
+        let a = [1_u8, 2, 3];
+        assert_eq!(
+            a.try_inline_mismatch_masked(&[1, 2, 7], &[0xFF, 0xFF, 0xF0]),
+            Ok(None)
+        );
+        assert_eq!(
+            a.try_inline_mismatch_masked(&[1, 2, 3], &[0xFF, 0xFF]),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 3
+            })
+        );
+        assert!(a.try_inline_mismatch_masked(&[1, 2], &[0xFF; 3]).is_err());
+    }
+
+    #[test]
+    fn test_mismatch_value() {
+        let mut poison = [0xAA_u8; 100];
+        assert_eq!(poison.inline_mismatch_value(0xAA), None);
+        poison[77] = 0;
+        assert_eq!(poison.inline_mismatch_value(0xAA), Some(77));
+        assert_eq!([1.0_f32, -0.0].inline_mismatch_value(1.0), Some(1));
+        assert_eq!([0_u64; 0].inline_mismatch_value(1), None);
+    }
+
+    #[test]
+    fn test_match_position() {
+        let a = [1_u16, 2, 3, 4, 5];
+        assert_eq!(a.inline_match_position(&[0, 0, 3, 0, 5]), Some(2));
+        assert_eq!(a.inline_match_position(&[0, 0, 0, 4]), Some(3));
+        assert_eq!(a.inline_match_position(&[0, 0, 0]), None);
+        assert_eq!(a.inline_match_position(&a), Some(0));
+        assert_eq!([0.0_f64].inline_match_position(&[-0.0]), None);
+    }
+
+    #[test]
+    fn test_common_prefix_len() {
+        assert_eq!(common_prefix_len::<u8>(&[]), 0);
+        assert_eq!(common_prefix_len(&[b"abc".as_slice()]), 3);
+        assert_eq!(common_prefix_len(&[b"abcd".as_slice(), b"abce", b"abx"]), 2);
+        assert_eq!(common_prefix_len(&[b"abcd".as_slice(), b"ab", b"abcd"]), 2);
+        assert_eq!(common_prefix_len(&[b"abcd".as_slice(), b"", b"abcd"]), 0);
+        assert_eq!(common_prefix_len(&[b"x".as_slice(), b"abcd", b"abcd"]), 0);
+        assert_eq!(
+            common_prefix_len(&[[1_u64, 2, 3].as_slice(), &[1, 2, 3]]),
+            3
+        );
+    }
+
+    #[test]
+    fn test_free_functions() {
+        let mut buf = vec![0_u16; 4];
+        crate::fill(&mut buf, 7);
+        assert_eq!(buf, [7; 4]);
+        crate::try_copy([1, 2, 3, 4], &mut buf[..]).unwrap();
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(crate::position(&buf, 3), Some(2));
+        assert_eq!(crate::try_mismatch(&buf, [1, 2, 0, 4]), Ok(Some(2)));
+        assert_eq!(crate::try_mismatch(&buf[..2], &[1, 2][..]), Ok(None));
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_free_functions_panicking() {
+        let mut buf = [0_u8; 3];
+        crate::copy(b"abc", &mut buf);
+        assert_eq!(&buf, b"abc");
+        assert_eq!(crate::mismatch(buf, b"abd"), Some(2));
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_within() {
+        let mut buf = *b"0123456789";
+        buf.inline_copy_within(0..4, 2);
+        assert_eq!(&buf, b"0101236789");
+        buf.inline_copy_within(6..10, 5);
+        assert_eq!(&buf, b"0101267899");
+        buf.inline_copy_within(3..3, 10);
+        assert_eq!(&buf, b"0101267899");
+
+        let mut ring = (0..16_u64).collect::<Vec<_>>();
+        ring.inline_copy_within(4..16, 0);
+        assert_eq!(ring[..12], (4..16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "length 11 exceeds the limit of 10")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_within_panic() {
+        [0_u8; 10].inline_copy_within(5..8, 8);
+    }
+
+    #[test]
+    fn test_try_copy_within() {
+        let mut buf = [1_u16, 2, 3, 4];
+        assert_eq!(buf.try_inline_copy_within(1..4, 0), Ok(()));
+        assert_eq!(buf, [2, 3, 4, 4]);
+        assert_eq!(
+            buf.try_inline_copy_within(2..5, 0),
+            Err(Error::LimitExceeded { len: 5, limit: 4 })
+        );
+        assert_eq!(
+            buf.try_inline_copy_within(core::ops::Range { start: 3, end: 2 }, 0),
+            Err(Error::InvalidRange { start: 3, end: 2 })
+        );
+        assert!(buf.try_inline_copy_within(0..1, usize::MAX).is_err());
+        assert_eq!(buf, [2, 3, 4, 4]);
+    }
+
+    #[test]
+    fn test_try_copy_from() {
+        let mut buf = GuardedBuf::new(3, 0_u32, u32::MAX);
+        assert_eq!(buf.run(|dst| dst.try_inline_copy_from(&[1, 2, 3])), Ok(()));
+        assert_eq!(buf.as_slice(), &[1, 2, 3]);
+        assert_eq!(
+            buf.run(|dst| dst.try_inline_copy_from(&[4, 5])),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 3
+            })
+        );
+        assert_eq!(buf.as_slice(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_try_mismatch() {
+        let a = [1_u16, 2, 3, 4];
+        assert_eq!(a.try_inline_mismatch(&[1, 2, 0, 4]), Ok(Some(2)));
+        assert_eq!(a.try_inline_mismatch(&a), Ok(None));
+        assert_eq!(
+            a.try_inline_mismatch(&[1, 2]),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 4
+            })
+        );
+        assert_eq!(
+            a.try_inline_mismatch_within(&[1, 2, 0, 4], 2),
+            Ok(BoundedSearch::NotFoundWithinLimit)
+        );
+        assert!(a.try_inline_mismatch_within(&[], 0).is_err());
+    }
 }
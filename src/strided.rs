@@ -0,0 +1,447 @@
+use crate::{rep_movs, Error};
+
+/// Number of elements a strided buffer of `count` records needs to contain, saturating at
+/// `usize::MAX`.
+///
+/// Returns [`Error::LimitExceeded`] with the width and the stride if the records overlap.
+#[inline]
+fn strided_len(stride: usize, count: usize, width: usize) -> Result<usize, Error> {
+    match count {
+        0 => Ok(0),
+        1 => Ok(width),
+        _ if width > stride => Err(Error::LimitExceeded {
+            len: width,
+            limit: stride,
+        }),
+        _ => Ok((count - 1).saturating_mul(stride).saturating_add(width)),
+    }
+}
+
+/// Panic with the message matching an error of the strided functions.
+#[cfg(not(feature = "no-panic"))]
+#[cold]
+fn strided_panic(err: Error) -> ! {
+    match err {
+        Error::LenMismatch { .. } => panic!("length mismatch"),
+        _ => panic!("strided buffer too short"),
+    }
+}
+
+/// Gather `count` records of `width` elements each from `src` into the contiguous `dst`.
+///
+/// Consecutive records in `src` start `stride` elements apart. This can for example be used
+/// to extract a single column out of row-major records.
+///
+/// When `stride` equals `width` the records are contiguous and the whole copy is done using
+/// a single `rep movs` instruction, otherwise every record is copied separately.
+///
+/// # Panics
+///
+/// Panics if `width` is larger than `stride`, if `src` is too short to contain `count` records
+/// or if `dst` does not have a length of exactly `count * width`.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_strided<T: Copy>(src: &[T], stride: usize, dst: &mut [T], count: usize, width: usize) {
+    assert!(width <= stride || count <= 1, "width exceeds stride");
+    if let Err(err) = try_copy_strided(src, stride, dst, count, width) {
+        strided_panic(err)
+    }
+}
+
+/// Same as `copy_strided`, but returns an error instead of panicking.
+///
+/// Returns [`Error::LimitExceeded`] if `width` is larger than `stride` or if `src` is too short
+/// to contain `count` records, and [`Error::LenMismatch`] if `dst` does not have a length of
+/// exactly `count * width`.
+#[inline]
+pub fn try_copy_strided<T: Copy>(
+    src: &[T],
+    stride: usize,
+    dst: &mut [T],
+    count: usize,
+    width: usize,
+) -> Result<(), Error> {
+    let len = strided_len(stride, count, width)?;
+    if len > src.len() {
+        return Err(Error::LimitExceeded {
+            len,
+            limit: src.len(),
+        });
+    }
+    if dst.len() != count.saturating_mul(width) {
+        return Err(Error::LenMismatch {
+            src_len: count.saturating_mul(width),
+            dst_len: dst.len(),
+        });
+    }
+    let (src, dst) = (src.as_ptr(), dst.as_mut_ptr());
+    if stride == width {
+        unsafe { rep_movs(src, dst, count * width) }
+    } else {
+        for i in 0..count {
+            unsafe {
+                core::ptr::copy_nonoverlapping(src.add(i * stride), dst.add(i * width), width)
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Scatter `count` records of `width` elements each from the contiguous `src` into `dst`.
+///
+/// Consecutive records in `dst` start `stride` elements apart, elements between the records
+/// are left unchanged. This is the inverse operation of [`copy_strided`].
+///
+/// When `stride` equals `width` the records are contiguous and the whole copy is done using
+/// a single `rep movs` instruction, otherwise every record is copied separately.
+///
+/// # Panics
+///
+/// Panics if `width` is larger than `stride`, if `dst` is too short to contain `count` records
+/// or if `src` does not have a length of exactly `count * width`.
+#[cfg(not(feature = "no-panic"))]
+pub fn copy_to_strided<T: Copy>(
+    src: &[T],
+    dst: &mut [T],
+    stride: usize,
+    count: usize,
+    width: usize,
+) {
+    assert!(width <= stride || count <= 1, "width exceeds stride");
+    if let Err(err) = try_copy_to_strided(src, dst, stride, count, width) {
+        strided_panic(err)
+    }
+}
+
+/// Same as `copy_to_strided`, but returns an error instead of panicking.
+///
+/// Returns [`Error::LimitExceeded`] if `width` is larger than `stride` or if `dst` is too short
+/// to contain `count` records, and [`Error::LenMismatch`] if `src` does not have a length of
+/// exactly `count * width`.
+#[inline]
+pub fn try_copy_to_strided<T: Copy>(
+    src: &[T],
+    dst: &mut [T],
+    stride: usize,
+    count: usize,
+    width: usize,
+) -> Result<(), Error> {
+    let len = strided_len(stride, count, width)?;
+    if len > dst.len() {
+        return Err(Error::LimitExceeded {
+            len,
+            limit: dst.len(),
+        });
+    }
+    if src.len() != count.saturating_mul(width) {
+        return Err(Error::LenMismatch {
+            src_len: src.len(),
+            dst_len: count.saturating_mul(width),
+        });
+    }
+    let (src, dst) = (src.as_ptr(), dst.as_mut_ptr());
+    if stride == width {
+        unsafe { rep_movs(src, dst, count * width) }
+    } else {
+        for i in 0..count {
+            unsafe {
+                core::ptr::copy_nonoverlapping(src.add(i * width), dst.add(i * stride), width)
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Return the number of frames of the `channels` planes with the lengths `plane_lens`, or
+/// [`Error::LenMismatch`] if the planes do not all have the same length or if the interleaved
+/// buffer of `interleaved_len` elements does not contain exactly one element per plane and frame.
+///
+/// The source is the interleaved buffer and the first plane, unless `swap` is set.
+#[inline]
+fn check_planes(
+    mut plane_lens: impl Iterator<Item = usize>,
+    channels: usize,
+    interleaved_len: usize,
+    swap: bool,
+) -> Result<usize, Error> {
+    let mismatch = |a, b| {
+        let (src_len, dst_len) = if swap { (b, a) } else { (a, b) };
+        Err(Error::LenMismatch { src_len, dst_len })
+    };
+    let frames = plane_lens.next().unwrap_or(0);
+    if interleaved_len != frames.saturating_mul(channels) {
+        return mismatch(interleaved_len, frames.saturating_mul(channels));
+    }
+    for len in plane_lens {
+        if len != frames {
+            return mismatch(frames, len);
+        }
+    }
+    Ok(frames)
+}
+
+/// Split the interleaved `src` into one planar buffer per channel.
+///
+/// `src` consists of frames containing one element for each of the `planes`, for example
+/// `LRLR...` stereo audio samples or `RGBARGBA...` pixels. Every plane is filled using
+/// [`copy_strided`] with records of a single element, which is a scalar gather of every
+/// `planes.len()`th element. A single plane is contiguous and copied using one `rep movs`.
+///
+/// # Panics
+///
+/// Panics if the planes do not all have the same length or if `src` does not contain exactly
+/// one element per plane and frame.
+#[cfg(not(feature = "no-panic"))]
+pub fn deinterleave<T: Copy>(src: &[T], planes: &mut [&mut [T]]) {
+    if try_deinterleave(src, planes).is_err() {
+        panic!("length mismatch");
+    }
+}
+
+/// Same as `deinterleave`, but returns [`Error::LenMismatch`] instead of panicking if the
+/// planes do not all have the same length or if `src` does not contain exactly one element per
+/// plane and frame. All lengths are checked before copying, the planes are left unchanged when
+/// an error is returned.
+#[inline]
+pub fn try_deinterleave<T: Copy>(src: &[T], planes: &mut [&mut [T]]) -> Result<(), Error> {
+    let channels = planes.len();
+    let frames = check_planes(
+        planes.iter().map(|plane| plane.len()),
+        channels,
+        src.len(),
+        false,
+    )?;
+    if let [plane] = planes {
+        unsafe { rep_movs(src.as_ptr(), plane.as_mut_ptr(), frames) };
+        return Ok(());
+    }
+    for (channel, plane) in planes.iter_mut().enumerate() {
+        let src = src.get(channel..).unwrap_or_default();
+        try_copy_strided(src, channels, plane, frames, 1)?;
+    }
+    Ok(())
+}
+
+/// Merge the planar channels in `planes` into the interleaved `dst`.
+///
+/// This is the inverse operation of [`deinterleave`], every frame in `dst` receives one element
+/// from each of the `planes`. Like in [`deinterleave`], the planes are scattered element by
+/// element using [`copy_to_strided`], unless there is only a single plane, which is copied using
+/// one `rep movs`.
+///
+/// # Panics
+///
+/// Panics if the planes do not all have the same length or if `dst` does not contain exactly
+/// one element per plane and frame.
+#[cfg(not(feature = "no-panic"))]
+pub fn interleave<T: Copy>(planes: &[&[T]], dst: &mut [T]) {
+    if try_interleave(planes, dst).is_err() {
+        panic!("length mismatch");
+    }
+}
+
+/// Same as `interleave`, but returns [`Error::LenMismatch`] instead of panicking if the planes
+/// do not all have the same length or if `dst` does not contain exactly one element per plane
+/// and frame. All lengths are checked before copying, `dst` is left unchanged when an error is
+/// returned.
+#[inline]
+pub fn try_interleave<T: Copy>(planes: &[&[T]], dst: &mut [T]) -> Result<(), Error> {
+    let channels = planes.len();
+    let frames = check_planes(
+        planes.iter().map(|plane| plane.len()),
+        channels,
+        dst.len(),
+        true,
+    )?;
+    if let [plane] = planes {
+        unsafe { rep_movs(plane.as_ptr(), dst.as_mut_ptr(), frames) };
+        return Ok(());
+    }
+    for (channel, plane) in planes.iter().enumerate() {
+        let dst = dst.get_mut(channel..).unwrap_or_default();
+        try_copy_to_strided(plane, dst, channels, frames, 1)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(feature = "no-panic"))]
+    use crate::testing::GuardedBuf;
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_strided() {
+        let src = [1_u16, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let mut dst = [0_u16; 3];
+        copy_strided(&src, 4, &mut dst, 3, 1);
+        assert_eq!(dst, [1, 5, 9]);
+
+        let mut dst = [0_u16; 6];
+        copy_strided(&src, 4, &mut dst, 3, 2);
+        assert_eq!(dst, [1, 2, 5, 6, 9, 10]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_strided_contiguous() {
+        let src = [1_u8, 2, 3, 4, 5, 6, 7];
+        let mut dst = [0_u8; 6];
+        copy_strided(&src, 3, &mut dst, 2, 3);
+        assert_eq!(dst, [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_strided_empty() {
+        let mut dst: [u32; 0] = [];
+        copy_strided(&[], 4, &mut dst, 0, 2);
+        copy_strided(&[1, 2, 3, 4, 5], 4, &mut dst, 2, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_strided_src_too_short() {
+        let mut dst = [0_u8; 3];
+        copy_strided(&[1, 2, 3, 4, 5, 6, 7, 8], 4, &mut dst, 3, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "width exceeds stride")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_strided_width_panic() {
+        let mut dst = [0_u8; 6];
+        copy_strided(&[0; 16], 2, &mut dst, 2, 3);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_to_strided() {
+        let mut dst = [0_i32; 8];
+        copy_to_strided(&[1, 2, 3, 4], &mut dst, 3, 2, 2);
+        assert_eq!(dst, [1, 2, 0, 3, 4, 0, 0, 0]);
+
+        let mut dst = [0_i32; 4];
+        copy_to_strided(&[1, 2, 3, 4], &mut dst, 2, 2, 2);
+        assert_eq!(dst, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_to_strided_guarded() {
+        let mut buf = GuardedBuf::new(7, 0_i32, -1);
+        buf.run(|dst| copy_to_strided(&[1, 2, 3, 4], dst, 3, 2, 2));
+        assert_eq!(buf.as_slice(), &[1, 2, 0, 3, 4, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_to_strided_panic() {
+        let mut dst = [0_i32; 8];
+        copy_to_strided(&[1, 2, 3], &mut dst, 3, 2, 2);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_deinterleave() {
+        let src = [1_u8, 2, 3, 4, 11, 12, 13, 14, 21, 22, 23, 24];
+        let (mut r, mut g, mut b, mut a) = ([0; 3], [0; 3], [0; 3], [0; 3]);
+        deinterleave(&src, &mut [&mut r, &mut g, &mut b, &mut a]);
+        assert_eq!(r, [1, 11, 21]);
+        assert_eq!(g, [2, 12, 22]);
+        assert_eq!(b, [3, 13, 23]);
+        assert_eq!(a, [4, 14, 24]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_deinterleave_single_plane() {
+        let mut mono = [0_f32; 3];
+        deinterleave(&[1.0, 2.0, 3.0], &mut [&mut mono]);
+        assert_eq!(mono, [1.0, 2.0, 3.0]);
+
+        let (mut left, mut right) = ([0_f32; 0], [0_f32; 0]);
+        deinterleave(&[], &mut [&mut left, &mut right]);
+        interleave(&[&left, &right], &mut []);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_deinterleave_panic() {
+        let (mut left, mut right) = ([0_i16; 2], [0_i16; 3]);
+        deinterleave(&[1, 2, 3, 4], &mut [&mut left, &mut right]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_interleave() {
+        let mut dst = [0_i16; 6];
+        interleave(&[&[1, 2, 3], &[-1, -2, -3]], &mut dst);
+        assert_eq!(dst, [1, -1, 2, -2, 3, -3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_interleave_panic() {
+        let mut dst = [0_i16; 6];
+        interleave(&[&[1, 2, 3], &[-1, -2]], &mut dst);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let mut dst = [0_u8; 3];
+        assert_eq!(
+            try_copy_strided(&[1, 2, 3, 4, 5, 6, 7, 8], 4, &mut dst, 3, 1),
+            Err(Error::LimitExceeded { len: 9, limit: 8 })
+        );
+        assert_eq!(
+            try_copy_strided(&[0; 16], 2, &mut dst, 2, 3),
+            Err(Error::LimitExceeded { len: 3, limit: 2 })
+        );
+        assert_eq!(
+            try_copy_to_strided(&[1, 2], &mut [0; 8], 3, 2, 2),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 4
+            })
+        );
+        let (mut left, mut right) = ([0_i16; 2], [0_i16; 3]);
+        assert_eq!(
+            try_deinterleave(&[1, 2, 3, 4], &mut [&mut left, &mut right]),
+            Err(Error::LenMismatch {
+                src_len: 2,
+                dst_len: 3
+            })
+        );
+        assert_eq!((left, right), ([0; 2], [0; 3]));
+        assert_eq!(
+            try_interleave(&[&[1, 2, 3], &[-1, -2, -3]], &mut [0_i16; 5]),
+            Err(Error::LenMismatch {
+                src_len: 6,
+                dst_len: 5
+            })
+        );
+        let mut dst = [0_i16; 6];
+        assert_eq!(
+            try_interleave(&[&[1, 2], &[-1, -2], &[7]], &mut dst),
+            Err(Error::LenMismatch {
+                src_len: 1,
+                dst_len: 2
+            })
+        );
+        assert_eq!(dst, [0; 6]);
+
+        let mut dst = [0_i16; 6];
+        assert_eq!(
+            try_interleave(&[&[1, 2, 3], &[-1, -2, -3]], &mut dst),
+            Ok(())
+        );
+        let (mut left, mut right) = ([0_i16; 3], [0_i16; 3]);
+        assert_eq!(try_deinterleave(&dst, &mut [&mut left, &mut right]), Ok(()));
+        assert_eq!((left, right), ([1, 2, 3], [-1, -2, -3]));
+    }
+}
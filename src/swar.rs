@@ -0,0 +1,212 @@
+//! Word at a time implementations of the scan and compare primitives, used as fallback on
+//! architectures without string instructions.
+//!
+//! Eight bytes are loaded into a `u64` in little endian order, so that the first element in
+//! memory occupies the least significant lane and the index of the first matching lane can be
+//! calculated from the number of trailing zeros. Element types wider than a word use a simple
+//! loop.
+
+use crate::RegisterType;
+use core::mem::size_of;
+
+const WORD: usize = size_of::<u64>();
+
+#[inline(always)]
+unsafe fn load<T>(ptr: *const T) -> u64 {
+    u64::from_le_bytes(ptr.cast::<[u8; WORD]>().read_unaligned())
+}
+
+/// Word with the lowest bit of each lane of `size` bytes set.
+#[inline(always)]
+const fn lane_lsb(size: usize) -> u64 {
+    match size {
+        1 => 0x0101_0101_0101_0101,
+        2 => 0x0001_0001_0001_0001,
+        4 => 0x0000_0001_0000_0001,
+        _ => 1,
+    }
+}
+
+/// Word with `value` repeated in each lane.
+#[inline(always)]
+fn splat<T: RegisterType>(value: T) -> u64 {
+    let mut bytes = [0_u8; WORD];
+    for lane in bytes.chunks_exact_mut(size_of::<T>()) {
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                (&value as *const T).cast(),
+                lane.as_mut_ptr(),
+                lane.len(),
+            )
+        };
+    }
+    u64::from_le_bytes(bytes)
+}
+
+/// Index of the first lane of `size` bytes that is zero in `word`.
+///
+/// The borrow of the subtraction can only mark lanes above a zero lane, so the lowest marked
+/// lane is exact.
+#[inline(always)]
+fn first_zero_lane(word: u64, size: usize) -> Option<usize> {
+    let lsb = lane_lsb(size);
+    let msb = lsb << (size * 8 - 1);
+    let zeros = word.wrapping_sub(lsb) & !word & msb;
+    (zeros != 0).then(|| zeros.trailing_zeros() as usize / (size * 8))
+}
+
+/// Index of the first lane of `size` bytes that is not zero in `word`.
+#[inline(always)]
+fn first_nonzero_lane(word: u64, size: usize) -> Option<usize> {
+    (word != 0).then(|| word.trailing_zeros() as usize / (size * 8))
+}
+
+/// Return the first index found by `word` for the words starting at each multiple of the lane
+/// count, falling back to `element` for the tail and for element types wider than a word.
+#[inline(always)]
+unsafe fn scan<T: RegisterType>(
+    len: usize,
+    word: impl Fn(usize) -> Option<usize>,
+    element: impl Fn(usize) -> bool,
+) -> Option<usize> {
+    let size = size_of::<T>();
+    let mut i = 0;
+    if size <= WORD {
+        let lanes = WORD / size;
+        while i + lanes <= len {
+            if let Some(lane) = word(i) {
+                return Some(i + lane);
+            }
+            i += lanes;
+        }
+    }
+    (i..len).find(|&i| element(i))
+}
+
+/// Word at a time equivalent of [`crate::rep_cmps`].
+#[inline]
+pub(crate) unsafe fn mismatch<T: RegisterType>(
+    a: *const T,
+    b: *const T,
+    len: usize,
+) -> Option<usize> {
+    let size = size_of::<T>();
+    scan::<T>(
+        len,
+        |i| first_nonzero_lane(load(a.add(i)) ^ load(b.add(i)), size),
+        |i| !(*a.add(i)).bitwise_eq(&*b.add(i)),
+    )
+}
+
+/// Word at a time equivalent of [`crate::repne_cmps`].
+#[inline]
+pub(crate) unsafe fn match_position<T: RegisterType>(
+    a: *const T,
+    b: *const T,
+    len: usize,
+) -> Option<usize> {
+    let size = size_of::<T>();
+    scan::<T>(
+        len,
+        |i| first_zero_lane(load(a.add(i)) ^ load(b.add(i)), size),
+        |i| (*a.add(i)).bitwise_eq(&*b.add(i)),
+    )
+}
+
+/// Word at a time equivalent of [`crate::rep_scas`].
+#[inline]
+pub(crate) unsafe fn position<T: RegisterType>(
+    src: *const T,
+    value: T,
+    len: usize,
+) -> Option<usize> {
+    let size = size_of::<T>();
+    let pattern = if size <= WORD { splat(value) } else { 0 };
+    scan::<T>(
+        len,
+        |i| first_zero_lane(load(src.add(i)) ^ pattern, size),
+        |i| (*src.add(i)).bitwise_eq(&value),
+    )
+}
+
+/// Word at a time equivalent of [`crate::repe_scas`].
+#[inline]
+pub(crate) unsafe fn position_not<T: RegisterType>(
+    src: *const T,
+    value: T,
+    len: usize,
+) -> Option<usize> {
+    let size = size_of::<T>();
+    let pattern = if size <= WORD { splat(value) } else { 0 };
+    scan::<T>(
+        len,
+        |i| first_nonzero_lane(load(src.add(i)) ^ pattern, size),
+        |i| !(*src.add(i)).bitwise_eq(&value),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<T: RegisterType + core::fmt::Debug>(a: &[T], b: &[T]) {
+        assert_eq!(a.len(), b.len(), "length mismatch");
+        let len = a.len();
+        unsafe {
+            assert_eq!(
+                mismatch(a.as_ptr(), b.as_ptr(), len),
+                a.iter().zip(b).position(|(a, b)| !a.bitwise_eq(b))
+            );
+            assert_eq!(
+                match_position(a.as_ptr(), b.as_ptr(), len),
+                a.iter().zip(b).position(|(a, b)| a.bitwise_eq(b))
+            );
+            for value in b.iter().chain(a.first()) {
+                assert_eq!(
+                    position(a.as_ptr(), *value, len),
+                    a.iter().position(|a| a.bitwise_eq(value))
+                );
+                assert_eq!(
+                    position_not(a.as_ptr(), *value, len),
+                    a.iter().position(|a| !a.bitwise_eq(value))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes() {
+        // all combinations of a few values that provoke borrows between lanes
+        let values = [0_u8, 1, 0x80];
+        for pattern in 0..values.len().pow(9) {
+            let a = (0..9)
+                .map(|i| values[pattern / values.len().pow(i) % values.len()])
+                .collect::<Vec<_>>();
+            check(&a, &[1; 9]);
+            check(&a, &[0; 9]);
+            check(&a[1..], &a[..8]);
+        }
+    }
+
+    #[test]
+    fn test_wide() {
+        fn narrow<T>(src: &[u64], f: fn(u64) -> T) -> Vec<T> {
+            src.iter().map(|&x| f(x)).collect()
+        }
+
+        let a = (0..100_u64)
+            .map(|i| i * 0x0101_0001_0000_0100)
+            .collect::<Vec<_>>();
+        let mut b = a.clone();
+        for i in [0, 3, 50, 99] {
+            b[i] ^= 1 << 16;
+            check(&a, &b);
+            check(&narrow(&a, |x| x as u32), &narrow(&b, |x| x as u32));
+            check(&narrow(&a, |x| x as u16), &narrow(&b, |x| x as u16));
+            check(&narrow(&a, |x| x as u128), &narrow(&b, |x| x as u128));
+        }
+        check(&[1.0_f32, -0.0, 0.0, f32::NAN], &[0.0, 0.0, -0.0, f32::NAN]);
+        check::<u64>(&[], &[]);
+        check(&[0_u16, 0, 1], &[0, 1, 1]);
+    }
+}
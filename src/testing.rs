@@ -0,0 +1,116 @@
+//! Helpers for testing code that calls the unsafe primitives of this crate.
+
+pub mod corpus;
+
+use crate::{RegisterType, SliceExt};
+
+/// Buffer surrounded by guard regions filled with a poison value.
+///
+/// Operations are run on the inner buffer using [`GuardedBuf::run`], which afterwards verifies
+/// that the guard regions were not modified. This detects writes that overrun the start or end
+/// of the buffer, for example because of an incorrect length passed to [`rep_movs`](crate::rep_movs).
+#[derive(Debug, Clone)]
+pub struct GuardedBuf<T> {
+    storage: Vec<T>,
+    guard_len: usize,
+    len: usize,
+    poison: T,
+}
+
+impl<T: RegisterType> GuardedBuf<T> {
+    /// Default number of guard elements before and after the buffer.
+    pub const DEFAULT_GUARD_LEN: usize = 64;
+
+    /// Create a buffer of `len` elements initialized to `value`, with guard regions of the
+    /// default length filled with `poison`.
+    pub fn new(len: usize, value: T, poison: T) -> Self {
+        Self::with_guard_len(len, value, poison, Self::DEFAULT_GUARD_LEN)
+    }
+
+    /// Create a buffer of `len` elements initialized to `value`, with `guard_len` elements
+    /// of `poison` before and after it.
+    pub fn with_guard_len(len: usize, value: T, poison: T, guard_len: usize) -> Self {
+        let mut storage = vec![poison; len + 2 * guard_len];
+        storage[guard_len..guard_len + len].fill(value);
+        Self {
+            storage,
+            guard_len,
+            len,
+            poison,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.storage[self.guard_len..self.guard_len + self.len]
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.storage[self.guard_len..self.guard_len + self.len]
+    }
+
+    /// Return the offset of the first modified guard element relative to the start of the buffer.
+    ///
+    /// Offsets in the leading guard region are negative, offsets in the trailing guard region
+    /// are greater or equal to the length of the buffer.
+    pub fn guard_violation(&self) -> Option<isize> {
+        let (head, rest) = self.storage.split_at(self.guard_len);
+        let tail = &rest[self.len..];
+        if let Some(index) = head.inline_mismatch_value(self.poison) {
+            return Some(index as isize - self.guard_len as isize);
+        }
+        tail.inline_mismatch_value(self.poison)
+            .map(|index| (self.len + index) as isize)
+    }
+
+    /// Panic if any of the guard elements was modified.
+    pub fn verify(&self) {
+        if let Some(offset) = self.guard_violation() {
+            panic!(
+                "guard overwritten at offset {offset} of buffer with length {}",
+                self.len
+            );
+        }
+    }
+
+    /// Run `op` on the inner buffer and verify the guard regions afterwards.
+    pub fn run<R>(&mut self, op: impl FnOnce(&mut [T]) -> R) -> R {
+        let result = op(self.as_mut_slice());
+        self.verify();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rep_stos;
+
+    #[test]
+    fn test_guarded_buf() {
+        let mut buf = GuardedBuf::with_guard_len(4, 0_u16, 0xDEAD, 8);
+        assert_eq!(buf.as_slice(), &[0; 4]);
+        buf.run(|dst| dst.fill(1));
+        assert_eq!(buf.as_slice(), &[1; 4]);
+        assert_eq!(buf.guard_violation(), None);
+    }
+
+    #[test]
+    fn test_guard_violation() {
+        let mut buf = GuardedBuf::with_guard_len(4, 0_u8, 0xA5, 8);
+        unsafe { rep_stos(1_u8, buf.storage.as_mut_ptr().add(8 + 2), 3) };
+        assert_eq!(buf.guard_violation(), Some(4));
+
+        let mut buf = GuardedBuf::with_guard_len(4, 0_u8, 0xA5, 8);
+        unsafe { rep_stos(1_u8, buf.storage.as_mut_ptr().add(8 - 2), 1) };
+        assert_eq!(buf.guard_violation(), Some(-2));
+    }
+
+    #[test]
+    #[should_panic(expected = "guard overwritten at offset 5 of buffer with length 5")]
+    fn test_verify_panic() {
+        let mut buf = GuardedBuf::new(5, 0_u64, u64::MAX);
+        let offset = GuardedBuf::<u64>::DEFAULT_GUARD_LEN;
+        unsafe { rep_stos(1_u64, buf.storage.as_mut_ptr().add(offset), 6) };
+        buf.verify();
+    }
+}
@@ -0,0 +1,206 @@
+//! Text format of the regression corpus of minimized fuzz findings and edge cases.
+//!
+//! Each case is stored in a `.case` file consisting of `key: value` lines, lines starting with
+//! `#` are comments:
+//!
+//! ```text
+//! # rep cmpsb with a mismatch in the last byte of an unaligned buffer
+//! op: mismatch
+//! align: 7
+//! a: 00*4095 01
+//! b: 00*4096
+//! ```
+//!
+//! `op` is one of `copy`, `fill`, `position` and `mismatch`. Byte sequences are written as hex
+//! bytes, `XX*N` repeats a byte `N` times. `a` is the source or haystack, `b` the second input of
+//! `mismatch` and `value` the byte of `fill` and `position`. `align` is the offset of the inputs
+//! from a 64 byte boundary.
+//!
+//! Fuzz targets can record a finding using [`Case::save`], the crate replays the cases in its
+//! `tests/corpus/` directory against every backend.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Operation exercised by a [`Case`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Copy,
+    Fill,
+    Position,
+    Mismatch,
+}
+
+impl Op {
+    fn name(self) -> &'static str {
+        match self {
+            Op::Copy => "copy",
+            Op::Fill => "fill",
+            Op::Position => "position",
+            Op::Mismatch => "mismatch",
+        }
+    }
+}
+
+/// A single recorded case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub comment: String,
+    pub op: Op,
+    pub align: usize,
+    pub a: Vec<u8>,
+    pub b: Vec<u8>,
+    pub value: u8,
+}
+
+impl Case {
+    pub fn new(op: Op, a: &[u8]) -> Self {
+        Self {
+            comment: String::new(),
+            op,
+            align: 0,
+            a: a.to_vec(),
+            b: Vec::new(),
+            value: 0,
+        }
+    }
+
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.comment = comment.to_string();
+        self
+    }
+
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_b(mut self, b: &[u8]) -> Self {
+        self.b = b.to_vec();
+        self
+    }
+
+    pub fn with_value(mut self, value: u8) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Parse the contents of a corpus file.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut comment = Vec::new();
+        let mut case = Case::new(Op::Copy, &[]);
+        let mut op = None;
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(text) = line.strip_prefix('#') {
+                comment.push(text.trim());
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("expected `key: value`, got `{line}`"))?;
+            let value = value.trim();
+            match key.trim() {
+                "op" => {
+                    op = Some(match value {
+                        "copy" => Op::Copy,
+                        "fill" => Op::Fill,
+                        "position" => Op::Position,
+                        "mismatch" => Op::Mismatch,
+                        _ => return Err(format!("unknown op `{value}`")),
+                    })
+                }
+                "align" => case.align = value.parse().map_err(|e| format!("align: {e}"))?,
+                "value" => {
+                    case.value = u8::from_str_radix(value, 16).map_err(|e| format!("value: {e}"))?
+                }
+                "a" => case.a = parse_bytes(value)?,
+                "b" => case.b = parse_bytes(value)?,
+                key => return Err(format!("unknown key `{key}`")),
+            }
+        }
+        case.op = op.ok_or("missing op")?;
+        case.comment = comment.join("\n");
+        Ok(case)
+    }
+
+    /// Format the case in the corpus file format.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for line in self.comment.lines() {
+            writeln!(text, "# {line}").unwrap();
+        }
+        writeln!(text, "op: {}", self.op.name()).unwrap();
+        writeln!(text, "align: {}", self.align).unwrap();
+        match self.op {
+            Op::Fill | Op::Position => writeln!(text, "value: {:02x}", self.value).unwrap(),
+            Op::Copy | Op::Mismatch => {}
+        }
+        writeln!(text, "a: {}", format_bytes(&self.a)).unwrap();
+        if self.op == Op::Mismatch {
+            writeln!(text, "b: {}", format_bytes(&self.b)).unwrap();
+        }
+        text
+    }
+
+    /// Write the case to `<name>.case` in the corpus directory `dir` and return its path.
+    pub fn save(&self, dir: &Path, name: &str) -> std::io::Result<PathBuf> {
+        let path = dir.join(format!("{name}.case"));
+        std::fs::write(&path, self.to_text())?;
+        Ok(path)
+    }
+}
+
+fn parse_bytes(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for token in text.split_whitespace() {
+        let (byte, count) = token.split_once('*').unwrap_or((token, "1"));
+        let byte = u8::from_str_radix(byte, 16).map_err(|e| format!("`{token}`: {e}"))?;
+        let count = count.parse().map_err(|e| format!("`{token}`: {e}"))?;
+        bytes.extend(std::iter::repeat_n(byte, count));
+    }
+    Ok(bytes)
+}
+
+fn format_bytes(bytes: &[u8]) -> String {
+    let mut tokens = Vec::new();
+    for run in bytes.chunk_by(|a, b| a == b) {
+        match run.len() {
+            1 => tokens.push(format!("{:02x}", run[0])),
+            len => tokens.push(format!("{:02x}*{len}", run[0])),
+        }
+    }
+    tokens.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_roundtrip() {
+        let case = Case::new(Op::Mismatch, &[0, 0, 0, 1, 2])
+            .with_comment("found by fuzzing\nsecond line")
+            .with_align(3)
+            .with_b(&[0, 0, 0, 1, 3]);
+        let text = case.to_text();
+        assert!(text.contains("a: 00*3 01 02\n"));
+        assert_eq!(Case::parse(&text), Ok(case));
+
+        let case = Case::new(Op::Fill, &[]).with_value(0xAB);
+        assert_eq!(Case::parse(&case.to_text()), Ok(case));
+        assert!(Case::parse("op: frobnicate").is_err());
+        assert!(Case::parse("a: 00").is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "writes to the file system")]
+    fn test_save() {
+        let case = Case::new(Op::Position, &[1, 2, 3]).with_value(3);
+        let path = case
+            .save(&std::env::temp_dir(), "x86_strings_ops_test_save")
+            .unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Case::parse(&text), Ok(case));
+    }
+}
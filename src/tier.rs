@@ -0,0 +1,358 @@
+//! Copy and fill functions for a statically known size class.
+//!
+//! Each tier uses a single code path without any runtime dispatch on the length or on processor
+//! features, which makes them suitable for code generators that already know the size class of
+//! an operation:
+//!
+//!  - small, up to [`SMALL_MAX`] bytes: straight-line overlapping loads and stores of up to 16
+//!    bytes, which compile to unaligned SSE moves
+//!  - medium: a single `rep movsb` or `rep stosb` instruction
+//!  - large, from about [`LARGE_MIN`] bytes: non-temporal stores that bypass the cache
+//!
+//! All tiers produce the correct result for any length within their documented range, using a
+//! tier outside of its recommended range only affects performance.
+
+use crate::{copy_nontemporal, fill_nontemporal, rep_movs, rep_stos, Error};
+
+/// Maximum length in bytes supported by [`try_copy_small`] and [`try_fill_small`].
+pub const SMALL_MAX: usize = 32;
+
+/// Length in bytes from which on [`try_copy_large`] and [`fill_large`] are expected to be faster
+/// than the medium tier, roughly the size of the last level cache share of a single core.
+pub const LARGE_MIN: usize = 1 << 20;
+
+/// Unsigned integer used as the unit of the overlapping small copies.
+trait Word: Copy {
+    fn splat(value: u8) -> Self;
+}
+
+macro_rules! impl_word {
+    ($($ty:ty),*) => {
+        $(impl Word for $ty {
+            #[inline(always)]
+            fn splat(value: u8) -> Self {
+                <$ty>::from_ne_bytes([value; core::mem::size_of::<$ty>()])
+            }
+        })*
+    };
+}
+
+impl_word!(u16, u32, u64, u128);
+
+/// Copy `len` bytes using one load and store of `W` at the start and one at the end, which
+/// overlap if `len` is less than twice the size of `W`.
+///
+/// # Safety
+///
+/// `src` and `dst` need to be valid for `len` bytes and `size_of::<W>() <= len`.
+#[inline(always)]
+unsafe fn copy_head_tail<W: Word>(src: *const u8, dst: *mut u8, len: usize) {
+    let last = len - core::mem::size_of::<W>();
+    let head = src.cast::<W>().read_unaligned();
+    let tail = src.add(last).cast::<W>().read_unaligned();
+    dst.cast::<W>().write_unaligned(head);
+    dst.add(last).cast::<W>().write_unaligned(tail);
+}
+
+/// Fill `len` bytes using one store of `W` at the start and one at the end.
+///
+/// # Safety
+///
+/// `dst` needs to be valid for `len` bytes and `size_of::<W>() <= len`.
+#[inline(always)]
+unsafe fn fill_head_tail<W: Word>(value: u8, dst: *mut u8, len: usize) {
+    let pattern = W::splat(value);
+    dst.cast::<W>().write_unaligned(pattern);
+    dst.add(len - core::mem::size_of::<W>())
+        .cast::<W>()
+        .write_unaligned(pattern);
+}
+
+/// Copy `len` bytes from `src` to `dst` using straight-line code.
+///
+/// # Safety
+///
+/// `src` and `dst` need to be valid for `len` bytes, which must be at most [`SMALL_MAX`], and
+/// must not overlap.
+#[inline(always)]
+pub(crate) unsafe fn copy_small_raw(src: *const u8, dst: *mut u8, len: usize) {
+    debug_assert!(len <= SMALL_MAX);
+    match len {
+        16.. => copy_head_tail::<u128>(src, dst, len),
+        8.. => copy_head_tail::<u64>(src, dst, len),
+        4.. => copy_head_tail::<u32>(src, dst, len),
+        2.. => copy_head_tail::<u16>(src, dst, len),
+        1 => dst.write(src.read()),
+        0 => {}
+    }
+}
+
+/// Check that `src` and `dst` have the same length and return it.
+#[inline(always)]
+fn same_len(src: &[u8], dst: &[u8]) -> Result<usize, Error> {
+    if src.len() != dst.len() {
+        return Err(Error::LenMismatch {
+            src_len: src.len(),
+            dst_len: dst.len(),
+        });
+    }
+    Ok(dst.len())
+}
+
+/// Check that `len` is within the small tier.
+#[inline(always)]
+fn small_len(len: usize) -> Result<usize, Error> {
+    if len > SMALL_MAX {
+        return Err(Error::LimitExceeded {
+            len,
+            limit: SMALL_MAX,
+        });
+    }
+    Ok(len)
+}
+
+/// Panic with the message matching an error of the tier functions.
+#[cfg(not(feature = "no-panic"))]
+#[cold]
+fn tier_panic(err: Error) -> ! {
+    match err {
+        Error::LenMismatch { .. } => panic!("length mismatch"),
+        _ => panic!("length exceeds small tier"),
+    }
+}
+
+/// Copy `src` into `dst` using straight-line code.
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths or are longer than [`SMALL_MAX`] bytes.
+#[cfg(not(feature = "no-panic"))]
+#[inline(always)]
+pub fn copy_small(src: &[u8], dst: &mut [u8]) {
+    if let Err(err) = try_copy_small(src, dst) {
+        tier_panic(err)
+    }
+}
+
+/// Same as `copy_small`, but returns [`Error::LenMismatch`] if `src` and `dst` have different
+/// lengths and [`Error::LimitExceeded`] if they are longer than [`SMALL_MAX`] bytes.
+#[inline(always)]
+pub fn try_copy_small(src: &[u8], dst: &mut [u8]) -> Result<(), Error> {
+    let len = small_len(same_len(src, dst)?)?;
+    unsafe { copy_small_raw(src.as_ptr(), dst.as_mut_ptr(), len) }
+    Ok(())
+}
+
+/// Fill `dst` with `value` using straight-line code.
+///
+/// # Panics
+///
+/// Panics if `dst` is longer than [`SMALL_MAX`] bytes.
+#[cfg(not(feature = "no-panic"))]
+#[inline(always)]
+pub fn fill_small(dst: &mut [u8], value: u8) {
+    if let Err(err) = try_fill_small(dst, value) {
+        tier_panic(err)
+    }
+}
+
+/// Same as `fill_small`, but returns [`Error::LimitExceeded`] instead of panicking if `dst` is
+/// longer than [`SMALL_MAX`] bytes.
+#[inline(always)]
+pub fn try_fill_small(dst: &mut [u8], value: u8) -> Result<(), Error> {
+    let len = small_len(dst.len())?;
+    let dst = dst.as_mut_ptr();
+    unsafe {
+        match len {
+            16.. => fill_head_tail::<u128>(value, dst, len),
+            8.. => fill_head_tail::<u64>(value, dst, len),
+            4.. => fill_head_tail::<u32>(value, dst, len),
+            2.. => fill_head_tail::<u16>(value, dst, len),
+            1 => dst.write(value),
+            0 => {}
+        }
+    }
+    Ok(())
+}
+
+/// Copy `src` into `dst` using a single `rep movsb` instruction.
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+#[inline(always)]
+pub fn copy_medium(src: &[u8], dst: &mut [u8]) {
+    if let Err(err) = try_copy_medium(src, dst) {
+        tier_panic(err)
+    }
+}
+
+/// Same as `copy_medium`, but returns [`Error::LenMismatch`] instead of panicking if `src` and
+/// `dst` have different lengths.
+#[inline(always)]
+pub fn try_copy_medium(src: &[u8], dst: &mut [u8]) -> Result<(), Error> {
+    let len = same_len(src, dst)?;
+    unsafe { rep_movs(src.as_ptr(), dst.as_mut_ptr(), len) }
+    Ok(())
+}
+
+/// Fill `dst` with `value` using a single `rep stosb` instruction.
+#[inline(always)]
+pub fn fill_medium(dst: &mut [u8], value: u8) {
+    unsafe { rep_stos(value, dst.as_mut_ptr(), dst.len()) }
+}
+
+/// Copy `src` into `dst` using non-temporal stores, see [`copy_nontemporal`].
+///
+/// # Panics
+///
+/// Panics if `src` and `dst` have different lengths.
+#[cfg(not(feature = "no-panic"))]
+#[inline]
+pub fn copy_large(src: &[u8], dst: &mut [u8]) {
+    if let Err(err) = try_copy_large(src, dst) {
+        tier_panic(err)
+    }
+}
+
+/// Same as `copy_large`, but returns [`Error::LenMismatch`] instead of panicking if `src` and
+/// `dst` have different lengths.
+#[inline]
+pub fn try_copy_large(src: &[u8], dst: &mut [u8]) -> Result<(), Error> {
+    let len = same_len(src, dst)?;
+    unsafe { copy_nontemporal(src.as_ptr(), dst.as_mut_ptr(), len) }
+    Ok(())
+}
+
+/// Fill `dst` with `value` using non-temporal stores, see [`fill_nontemporal`].
+#[inline]
+pub fn fill_large(dst: &mut [u8], value: u8) {
+    unsafe { fill_nontemporal(value, dst.as_mut_ptr(), dst.len()) }
+}
+
+/// Copy up to `N` bytes of `src` into a zero initialized array and return it together with the
+/// number of copied bytes.
+///
+/// Longer inputs are truncated. Arrays of up to [`SMALL_MAX`] bytes are filled using the
+/// straight-line code of the small tier, larger ones using a single `rep movsb` instruction like
+/// in the medium tier, the choice is made at compile time.
+#[inline]
+pub fn copy_to_stack<const N: usize>(src: &[u8]) -> ([u8; N], usize) {
+    let mut buffer = [0; N];
+    let len = src.len().min(N);
+    let (src, dst) = (src.as_ptr(), buffer.as_mut_ptr());
+    if N <= SMALL_MAX {
+        unsafe { copy_small_raw(src, dst, len) }
+    } else {
+        unsafe { rep_movs(src, dst, len) }
+    }
+    (buffer, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(feature = "no-panic"))]
+    use crate::testing::GuardedBuf;
+
+    #[cfg(not(feature = "no-panic"))]
+    type CopyFn = fn(&[u8], &mut [u8]);
+    #[cfg(not(feature = "no-panic"))]
+    type FillFn = fn(&mut [u8], u8);
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_tiers() {
+        let src = (0..=255_u8).collect::<Vec<_>>();
+        let tiers: [(CopyFn, usize); 3] = [
+            (copy_small, SMALL_MAX),
+            (copy_medium, 256),
+            (copy_large, 256),
+        ];
+        for (copy, max) in tiers {
+            for len in 0..=max {
+                let mut buf = GuardedBuf::new(len, 0_u8, 0xEE);
+                buf.run(|dst| copy(&src[..len], dst));
+                assert_eq!(buf.as_slice(), &src[..len]);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_fill_tiers() {
+        let tiers: [(FillFn, usize); 3] = [
+            (fill_small, SMALL_MAX),
+            (fill_medium, 256),
+            (fill_large, 256),
+        ];
+        for (fill, max) in tiers {
+            for len in 0..=max {
+                let mut buf = GuardedBuf::new(len, 0_u8, 0xEE);
+                buf.run(|dst| fill(dst, 0x42));
+                assert!(buf.as_slice().iter().all(|&b| b == 0x42));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "length exceeds small tier")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_copy_small_panic() {
+        copy_small(&[0; SMALL_MAX + 1], &mut [0; SMALL_MAX + 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "length exceeds small tier")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_fill_small_panic() {
+        fill_small(&mut [0; SMALL_MAX + 1], 1);
+    }
+
+    #[test]
+    fn test_copy_to_stack() {
+        let (buffer, len) = copy_to_stack::<8>(b"key");
+        assert_eq!((&buffer, len), (b"key\0\0\0\0\0", 3));
+        let (buffer, len) = copy_to_stack::<4>(b"truncated");
+        assert_eq!((&buffer, len), (b"trun", 4));
+        let (buffer, len) = copy_to_stack::<0>(b"abc");
+        assert_eq!((buffer, len), ([], 0));
+
+        let src = (0..100_u8).collect::<Vec<_>>();
+        let (buffer, len) = copy_to_stack::<64>(&src);
+        assert_eq!((&buffer[..], len), (&src[..64], 64));
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let mut dst = [0; SMALL_MAX + 1];
+        assert_eq!(
+            try_copy_small(&[1; SMALL_MAX + 1], &mut dst),
+            Err(Error::LimitExceeded {
+                len: SMALL_MAX + 1,
+                limit: SMALL_MAX
+            })
+        );
+        assert_eq!(
+            try_fill_small(&mut dst, 1),
+            Err(Error::LimitExceeded {
+                len: SMALL_MAX + 1,
+                limit: SMALL_MAX
+            })
+        );
+        let mismatch = Err(Error::LenMismatch {
+            src_len: 2,
+            dst_len: 3,
+        });
+        assert_eq!(try_copy_small(&[1; 2], &mut dst[..3]), mismatch);
+        assert_eq!(try_copy_medium(&[1; 2], &mut dst[..3]), mismatch);
+        assert_eq!(try_copy_large(&[1; 2], &mut dst[..3]), mismatch);
+        assert_eq!(dst, [0; SMALL_MAX + 1]);
+
+        assert_eq!(try_fill_small(&mut dst[..SMALL_MAX], 1), Ok(()));
+        assert_eq!(try_copy_medium(&[2; 4], &mut dst[..4]), Ok(()));
+        assert_eq!(try_copy_large(&[3; 2], &mut dst[..2]), Ok(()));
+        assert_eq!(dst[..6], [3, 3, 2, 2, 1, 1]);
+    }
+}
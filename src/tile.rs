@@ -0,0 +1,287 @@
+use crate::{rep_movs, Error};
+
+/// Rectangle within a row-major matrix, in units of elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRect {
+    pub row: usize,
+    pub col: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl TileRect {
+    /// Validate that this rectangle lies within a matrix of `len` elements with `cols` columns
+    /// and return the index of its first element.
+    ///
+    /// Returns [`Error::LimitExceeded`] with the end column or row of the tile and the number of
+    /// columns or rows of the matrix otherwise.
+    #[inline]
+    fn start(&self, len: usize, cols: usize) -> Result<usize, Error> {
+        let end_col = self.col.saturating_add(self.width);
+        if end_col > cols {
+            return Err(Error::LimitExceeded {
+                len: end_col,
+                limit: cols,
+            });
+        }
+        if self.width == 0 {
+            // empty tiles do not access any rows
+            return Ok(0);
+        }
+        let rows = len.checked_div(cols).unwrap_or(0);
+        let end_row = self.row.saturating_add(self.height);
+        if end_row > rows {
+            return Err(Error::LimitExceeded {
+                len: end_row,
+                limit: rows,
+            });
+        }
+        Ok(self.row * cols + self.col)
+    }
+
+    /// Number of elements in this rectangle, saturating at `usize::MAX`.
+    #[inline]
+    fn len(&self) -> usize {
+        self.width.saturating_mul(self.height)
+    }
+}
+
+/// Panic with the message matching an error of the tile functions.
+#[cfg(not(feature = "no-panic"))]
+#[cold]
+fn tile_panic(err: Error, rect: TileRect, cols: usize) -> ! {
+    match err {
+        Error::LenMismatch { .. } => panic!("length mismatch"),
+        _ if rect.col.saturating_add(rect.width) > cols => panic!("tile exceeds matrix columns"),
+        _ => panic!("tile exceeds matrix rows"),
+    }
+}
+
+/// Copy the elements of `rect` out of the row-major matrix `src` with `cols` columns into the
+/// contiguous `dst`.
+///
+/// Every row of the tile is copied using one `rep movs` instruction, if the tile spans all
+/// columns the whole tile is copied using a single instruction.
+///
+/// # Panics
+///
+/// Panics if `rect` is not contained in the matrix or if `dst` does not have a length of exactly
+/// `rect.width * rect.height`.
+#[cfg(not(feature = "no-panic"))]
+pub fn extract_tile<T: Copy>(src: &[T], cols: usize, rect: TileRect, dst: &mut [T]) {
+    if let Err(err) = try_extract_tile(src, cols, rect, dst) {
+        tile_panic(err, rect, cols)
+    }
+}
+
+/// Same as `extract_tile`, but returns an error instead of panicking.
+///
+/// Returns [`Error::LimitExceeded`] if `rect` is not contained in the matrix, and
+/// [`Error::LenMismatch`] if `dst` does not have a length of exactly `rect.width * rect.height`.
+#[inline]
+pub fn try_extract_tile<T: Copy>(
+    src: &[T],
+    cols: usize,
+    rect: TileRect,
+    dst: &mut [T],
+) -> Result<(), Error> {
+    let start = rect.start(src.len(), cols)?;
+    if dst.len() != rect.len() {
+        return Err(Error::LenMismatch {
+            src_len: rect.len(),
+            dst_len: dst.len(),
+        });
+    }
+    let (src, dst) = (src.as_ptr(), dst.as_mut_ptr());
+    if rect.width == cols {
+        unsafe { rep_movs(src.add(start), dst, rect.len()) }
+    } else if rect.width > 0 {
+        for i in 0..rect.height {
+            unsafe {
+                rep_movs(
+                    src.add(start + i * cols),
+                    dst.add(i * rect.width),
+                    rect.width,
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copy the contiguous `src` into the elements of `rect` in the row-major matrix `dst` with
+/// `cols` columns.
+///
+/// This is the inverse operation of [`extract_tile`].
+///
+/// # Panics
+///
+/// Panics if `rect` is not contained in the matrix or if `src` does not have a length of exactly
+/// `rect.width * rect.height`.
+#[cfg(not(feature = "no-panic"))]
+pub fn insert_tile<T: Copy>(src: &[T], dst: &mut [T], cols: usize, rect: TileRect) {
+    if let Err(err) = try_insert_tile(src, dst, cols, rect) {
+        tile_panic(err, rect, cols)
+    }
+}
+
+/// Same as `insert_tile`, but returns an error instead of panicking.
+///
+/// Returns [`Error::LimitExceeded`] if `rect` is not contained in the matrix, and
+/// [`Error::LenMismatch`] if `src` does not have a length of exactly `rect.width * rect.height`.
+#[inline]
+pub fn try_insert_tile<T: Copy>(
+    src: &[T],
+    dst: &mut [T],
+    cols: usize,
+    rect: TileRect,
+) -> Result<(), Error> {
+    let start = rect.start(dst.len(), cols)?;
+    if src.len() != rect.len() {
+        return Err(Error::LenMismatch {
+            src_len: src.len(),
+            dst_len: rect.len(),
+        });
+    }
+    let (src, dst) = (src.as_ptr(), dst.as_mut_ptr());
+    if rect.width == cols {
+        unsafe { rep_movs(src, dst.add(start), rect.len()) }
+    } else if rect.width > 0 {
+        for i in 0..rect.height {
+            unsafe {
+                rep_movs(
+                    src.add(i * rect.width),
+                    dst.add(start + i * cols),
+                    rect.width,
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rustfmt::skip]
+    const MATRIX: [f32; 12] = [
+        0.0, 1.0, 2.0, 3.0,
+        4.0, 5.0, 6.0, 7.0,
+        8.0, 9.0, 10.0, 11.0,
+    ];
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_extract_tile() {
+        let mut tile = [0_f32; 4];
+        let rect = TileRect {
+            row: 1,
+            col: 1,
+            width: 2,
+            height: 2,
+        };
+        extract_tile(&MATRIX, 4, rect, &mut tile);
+        assert_eq!(tile, [5.0, 6.0, 9.0, 10.0]);
+
+        let mut tile = [0_f32; 8];
+        let rect = TileRect {
+            row: 1,
+            col: 0,
+            width: 4,
+            height: 2,
+        };
+        extract_tile(&MATRIX, 4, rect, &mut tile);
+        assert_eq!(tile, MATRIX[4..]);
+
+        let rect = TileRect {
+            row: 3,
+            col: 4,
+            width: 0,
+            height: 0,
+        };
+        extract_tile(&MATRIX, 4, rect, &mut []);
+
+        let rect = TileRect {
+            row: usize::MAX,
+            col: 1,
+            width: 0,
+            height: 5,
+        };
+        extract_tile(&MATRIX, 4, rect, &mut []);
+        insert_tile(&[], &mut [0_u8; 0], 0, TileRect { col: 0, ..rect });
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_insert_tile() {
+        let mut matrix = MATRIX;
+        let rect = TileRect {
+            row: 0,
+            col: 2,
+            width: 2,
+            height: 3,
+        };
+        insert_tile(&[-1.0; 6], &mut matrix, 4, rect);
+        assert_eq!(
+            matrix,
+            [0.0, 1.0, -1.0, -1.0, 4.0, 5.0, -1.0, -1.0, 8.0, 9.0, -1.0, -1.0]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "tile exceeds matrix rows")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_extract_tile_rows_panic() {
+        let rect = TileRect {
+            row: 2,
+            col: 0,
+            width: 1,
+            height: 2,
+        };
+        extract_tile(&MATRIX, 4, rect, &mut [0.0; 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "tile exceeds matrix columns")]
+    #[cfg(not(feature = "no-panic"))]
+    fn test_insert_tile_cols_panic() {
+        let rect = TileRect {
+            row: 0,
+            col: 3,
+            width: 2,
+            height: 1,
+        };
+        insert_tile(&[0.0; 2], &mut MATRIX.clone(), 4, rect);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let rect = TileRect {
+            row: 2,
+            col: 0,
+            width: 1,
+            height: 2,
+        };
+        assert_eq!(
+            try_extract_tile(&MATRIX, 4, rect, &mut [0.0; 2]),
+            Err(Error::LimitExceeded { len: 4, limit: 3 })
+        );
+        let rect = TileRect { col: 3, ..rect };
+        assert_eq!(
+            try_insert_tile(&[0.0; 2], &mut MATRIX.clone(), 3, rect),
+            Err(Error::LimitExceeded { len: 4, limit: 3 })
+        );
+        let rect = TileRect { row: 0, ..rect };
+        assert_eq!(
+            try_insert_tile(&[0.0; 3], &mut MATRIX.clone(), 4, rect),
+            Err(Error::LenMismatch {
+                src_len: 3,
+                dst_len: 2
+            })
+        );
+        let mut tile = [0.0; 2];
+        assert_eq!(try_extract_tile(&MATRIX, 4, rect, &mut tile), Ok(()));
+        assert_eq!(tile, [3.0, 7.0]);
+    }
+}
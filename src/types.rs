@@ -15,67 +15,190 @@ mod private {
     impl Sealed for f64 {}
 }
 
+/// Operand size form of the string instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AsmArm {
+    /// `movsb`, `stosb`, `cmpsb` and `scasb`, operating on 1 byte.
+    Byte,
+    /// `movsw`, `stosw`, `cmpsw` and `scasw`, operating on 2 bytes.
+    Word,
+    /// `movsd`, `stosd`, `cmpsd` and `scasd`, operating on 4 bytes.
+    Dword,
+    /// `movsq`, `stosq`, `cmpsq` and `scasq`, operating on 8 bytes.
+    Qword,
+}
+
+impl AsmArm {
+    /// Number of bytes processed per element.
+    pub const fn width(self) -> usize {
+        match self {
+            AsmArm::Byte => 1,
+            AsmArm::Word => 2,
+            AsmArm::Dword => 4,
+            AsmArm::Qword => 8,
+        }
+    }
+}
+
 pub trait RegisterType: private::Sealed + Copy + PartialEq {
+    /// Size of the type in bytes.
+    const WIDTH: usize;
+    /// Form of the string instructions used for this type, or `None` if there is no form of
+    /// this width and the scan and compare functions use a portable loop instead.
+    const ASM_ARM: Option<AsmArm>;
+
     fn bitwise_eq(&self, other: &Self) -> bool;
 }
 
 impl RegisterType for i8 {
+    const WIDTH: usize = 1;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Byte);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for u8 {
+    const WIDTH: usize = 1;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Byte);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for i16 {
+    const WIDTH: usize = 2;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Word);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for u16 {
+    const WIDTH: usize = 2;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Word);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for i32 {
+    const WIDTH: usize = 4;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Dword);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for u32 {
+    const WIDTH: usize = 4;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Dword);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for i64 {
+    const WIDTH: usize = 8;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Qword);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for u64 {
+    const WIDTH: usize = 8;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Qword);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for i128 {
+    const WIDTH: usize = 16;
+    const ASM_ARM: Option<AsmArm> = None;
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for u128 {
+    const WIDTH: usize = 16;
+    const ASM_ARM: Option<AsmArm> = None;
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self == other
     }
 }
 impl RegisterType for f32 {
+    const WIDTH: usize = 4;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Dword);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self.to_bits() == other.to_bits()
     }
 }
 impl RegisterType for f64 {
+    const WIDTH: usize = 8;
+    const ASM_ARM: Option<AsmArm> = Some(AsmArm::Qword);
+
     fn bitwise_eq(&self, other: &Self) -> bool {
         self.to_bits() == other.to_bits()
     }
 }
+
+/// Integer types supporting arithmetic in addition to the register operations.
+pub trait IntegerType: RegisterType + Eq + Ord {
+    fn wrapping_add(self, other: Self) -> Self;
+}
+
+impl IntegerType for i8 {
+    fn wrapping_add(self, other: Self) -> Self {
+        i8::wrapping_add(self, other)
+    }
+}
+impl IntegerType for u8 {
+    fn wrapping_add(self, other: Self) -> Self {
+        u8::wrapping_add(self, other)
+    }
+}
+impl IntegerType for i16 {
+    fn wrapping_add(self, other: Self) -> Self {
+        i16::wrapping_add(self, other)
+    }
+}
+impl IntegerType for u16 {
+    fn wrapping_add(self, other: Self) -> Self {
+        u16::wrapping_add(self, other)
+    }
+}
+impl IntegerType for i32 {
+    fn wrapping_add(self, other: Self) -> Self {
+        i32::wrapping_add(self, other)
+    }
+}
+impl IntegerType for u32 {
+    fn wrapping_add(self, other: Self) -> Self {
+        u32::wrapping_add(self, other)
+    }
+}
+impl IntegerType for i64 {
+    fn wrapping_add(self, other: Self) -> Self {
+        i64::wrapping_add(self, other)
+    }
+}
+impl IntegerType for u64 {
+    fn wrapping_add(self, other: Self) -> Self {
+        u64::wrapping_add(self, other)
+    }
+}
+impl IntegerType for i128 {
+    fn wrapping_add(self, other: Self) -> Self {
+        i128::wrapping_add(self, other)
+    }
+}
+impl IntegerType for u128 {
+    fn wrapping_add(self, other: Self) -> Self {
+        u128::wrapping_add(self, other)
+    }
+}
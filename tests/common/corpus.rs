@@ -0,0 +1,27 @@
+//! Regression corpus of minimized fuzz findings and edge cases, stored as text files in
+//! `tests/corpus/`, see `x86_strings_ops::testing::corpus` for the file format.
+
+use std::path::{Path, PathBuf};
+use x86_strings_ops::testing::corpus::Case;
+
+/// Directory containing the corpus files.
+pub fn dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus")
+}
+
+/// Load all cases of the corpus directory, together with their file names, sorted by name.
+pub fn load() -> Vec<(String, Case)> {
+    let mut cases = std::fs::read_dir(dir())
+        .expect("corpus directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "case"))
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let text = std::fs::read_to_string(&path).unwrap();
+            let case = Case::parse(&text).unwrap_or_else(|e| panic!("{name}: {e}"));
+            (name, case)
+        })
+        .collect::<Vec<_>>();
+    cases.sort_by(|a, b| a.0.cmp(&b.0));
+    cases
+}
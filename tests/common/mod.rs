@@ -0,0 +1,53 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+#[cfg(feature = "testing")]
+pub mod corpus;
+pub mod oracle;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Number of random cases per test, reduced under Miri which is several orders of magnitude
+/// slower than native execution.
+pub fn iterations() -> usize {
+    if cfg!(miri) {
+        20
+    } else {
+        2000
+    }
+}
+
+/// Random number generator with a fixed seed, so that failures are reproducible.
+pub fn rng() -> StdRng {
+    StdRng::seed_from_u64(0x5EED_1234_ABCD_0042)
+}
+
+/// Random vector of up to `max_len` elements drawn from a small alphabet, so that searches
+/// find matches and compared slices share long prefixes.
+pub fn random_vec<T: TryFrom<u8>>(rng: &mut StdRng, max_len: usize) -> Vec<T>
+where
+    T::Error: core::fmt::Debug,
+{
+    let len = rng.gen_range(0..=max_len);
+    let alphabet = rng.gen_range(1..=4_u8);
+    (0..len)
+        .map(|_| T::try_from(rng.gen_range(0..alphabet)).unwrap())
+        .collect()
+}
+
+/// Copy of `src` with a few randomly changed elements.
+pub fn mutate<T: Copy + TryFrom<u8>>(rng: &mut StdRng, src: &[T]) -> Vec<T>
+where
+    T::Error: core::fmt::Debug,
+{
+    let mut dst = src.to_vec();
+    if !dst.is_empty() {
+        for _ in 0..rng.gen_range(0..3) {
+            let index = rng.gen_range(0..dst.len());
+            dst[index] = T::try_from(rng.gen_range(4..8)).unwrap();
+        }
+    }
+    dst
+}
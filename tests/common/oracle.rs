@@ -0,0 +1,86 @@
+//! Reference implementations of the crate's operations using only the standard library.
+//!
+//! Results of both the inline assembly and the fallback implementations, which are what Miri
+//! executes, are checked against these functions, so that both stay semantically in sync.
+
+pub fn position<T: PartialEq>(haystack: &[T], value: T) -> Option<usize> {
+    haystack.iter().position(|x| *x == value)
+}
+
+pub fn position_nth<T: PartialEq>(haystack: &[T], value: T, n: usize) -> Option<usize> {
+    haystack
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| **x == value)
+        .nth(n)
+        .map(|(index, _)| index)
+}
+
+pub fn rposition_nth<T: PartialEq>(haystack: &[T], value: T, n: usize) -> Option<usize> {
+    haystack
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, x)| **x == value)
+        .nth(n)
+        .map(|(index, _)| index)
+}
+
+pub fn position_not<T: PartialEq>(haystack: &[T], value: T) -> Option<usize> {
+    haystack.iter().position(|x| *x != value)
+}
+
+pub fn position_not_any<T: PartialEq>(haystack: &[T], values: &[T]) -> Option<usize> {
+    haystack.iter().position(|x| !values.contains(x))
+}
+
+pub fn mismatch<T: PartialEq>(a: &[T], b: &[T]) -> Option<usize> {
+    a.iter().zip(b).position(|(a, b)| a != b)
+}
+
+pub fn match_position<T: PartialEq>(a: &[T], b: &[T]) -> Option<usize> {
+    a.iter().zip(b).position(|(a, b)| a == b)
+}
+
+pub fn find<T: PartialEq>(haystack: &[T], needle: &[T]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+pub fn rfind<T: PartialEq>(haystack: &[T], needle: &[T]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(haystack.len());
+    }
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+pub fn common_prefix_len<T: PartialEq>(slices: &[&[T]]) -> usize {
+    let Some((first, rest)) = slices.split_first() else {
+        return 0;
+    };
+    rest.iter().fold(first.len(), |len, slice| {
+        mismatch(
+            &first[..len.min(slice.len())],
+            &slice[..len.min(slice.len())],
+        )
+        .unwrap_or(len.min(slice.len()))
+    })
+}
+
+pub fn dedup<T: PartialEq + Copy>(src: &[T]) -> Vec<T> {
+    let mut result = src.to_vec();
+    result.dedup();
+    result
+}
+
+pub fn merge<T: Ord + Copy>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut result = [a, b].concat();
+    result.sort();
+    result
+}
+
+pub fn partition<T: PartialOrd + Copy>(src: &[T], pivot: T) -> (Vec<T>, Vec<T>) {
+    src.iter().partition(|x| **x < pivot)
+}
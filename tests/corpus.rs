@@ -0,0 +1,93 @@
+//! Replay of the regression corpus in `tests/corpus/` against every backend, see
+//! `x86_strings_ops::testing::corpus` for the file format.
+
+mod common;
+
+use common::corpus;
+use common::oracle;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use x86_strings_ops::backend::{self, Backend, RepBackend, ScalarBackend};
+use x86_strings_ops::testing::corpus::{Case, Op};
+
+/// Buffer with its data starting `align` bytes after a 64 byte boundary.
+struct Placed {
+    storage: Vec<u64>,
+    align: usize,
+    len: usize,
+}
+
+impl Placed {
+    fn new(data: &[u8], align: usize) -> Self {
+        let storage = vec![0_u64; (data.len() + 128).div_ceil(8)];
+        let offset = storage.as_ptr().cast::<u8>().align_offset(64) + align % 64;
+        let mut placed = Self {
+            storage,
+            align: offset,
+            len: data.len(),
+        };
+        placed.as_mut_slice().copy_from_slice(data);
+        placed
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        let bytes = self.storage.as_ptr().cast::<u8>();
+        unsafe { core::slice::from_raw_parts(bytes.add(self.align), self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        let bytes = self.storage.as_mut_ptr().cast::<u8>();
+        unsafe { core::slice::from_raw_parts_mut(bytes.add(self.align), self.len) }
+    }
+}
+
+fn replay(case: &Case, backend: &dyn Backend) {
+    let a = Placed::new(&case.a, case.align);
+    match case.op {
+        Op::Copy => {
+            let mut dst = Placed::new(&vec![0xCC; case.a.len()], case.align);
+            let dst = dst.as_mut_slice();
+            unsafe { backend.copy(a.as_slice().as_ptr(), dst.as_mut_ptr(), dst.len()) };
+            assert_eq!(dst, &case.a[..]);
+        }
+        Op::Fill => {
+            let mut dst = Placed::new(&case.a, case.align);
+            let dst = dst.as_mut_slice();
+            unsafe { backend.fill(case.value, dst.as_mut_ptr(), dst.len()) };
+            assert!(dst.iter().all(|&x| x == case.value));
+        }
+        Op::Position => {
+            let src = a.as_slice();
+            let position = unsafe { backend.position(src.as_ptr(), case.value, src.len()) };
+            assert_eq!(position, oracle::position(&case.a, case.value));
+        }
+        Op::Mismatch => {
+            assert_eq!(
+                case.a.len(),
+                case.b.len(),
+                "inputs of mismatch need equal lengths"
+            );
+            let b = Placed::new(&case.b, case.align);
+            let (a, b) = (a.as_slice(), b.as_slice());
+            let position = unsafe { backend.mismatch(a.as_ptr(), b.as_ptr(), a.len()) };
+            assert_eq!(position, oracle::mismatch(&case.a, &case.b));
+        }
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "reads the corpus from the file system")]
+fn test_replay_corpus() {
+    let cases = corpus::load();
+    assert!(!cases.is_empty());
+    let backends: [&dyn Backend; 3] = [&RepBackend, &ScalarBackend, backend::current()];
+    for (name, case) in &cases {
+        for backend in backends {
+            let result = catch_unwind(AssertUnwindSafe(|| replay(case, backend)));
+            assert!(
+                result.is_ok(),
+                "{name} failed on backend {}",
+                backend.name()
+            );
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Link time checks that the `try_` functions do not contain any panic paths.
+//!
+//! Each checked call is wrapped in a guard whose destructor references an undefined symbol. The
+//! destructor only runs when unwinding out of the call, so linking succeeds only if the optimizer
+//! removed every panic path. The checks therefore only run in release builds:
+//!
+//! ```text
+//! cargo test --release --features no-panic --test no_panic
+//! ```
+#![cfg(all(feature = "no-panic", not(debug_assertions)))]
+
+use std::hint::black_box;
+use x86_strings_ops::{BoundedSearch, Error, SliceExt, TileRect};
+
+struct PanicGuard;
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        extern "C" {
+            fn x86_strings_ops_function_may_panic() -> !;
+        }
+        unsafe { x86_strings_ops_function_may_panic() }
+    }
+}
+
+#[inline(always)]
+fn assert_no_panic<R>(f: impl FnOnce() -> R) -> R {
+    let guard = PanicGuard;
+    let result = f();
+    core::mem::forget(guard);
+    result
+}
+
+#[test]
+fn test_try_functions_do_not_panic() {
+    let src = black_box([1_u8, 2, 3, 4]);
+    let mut dst = black_box([0_u8; 4]);
+    let (src, dst) = (black_box(&src[..]), black_box(&mut dst[..]));
+
+    assert_no_panic(|| dst.inline_fill(7));
+    assert_eq!(assert_no_panic(|| dst.inline_position(7)), Some(0));
+    assert_eq!(assert_no_panic(|| dst.try_inline_copy_from(src)), Ok(()));
+    assert_eq!(assert_no_panic(|| dst.try_inline_mismatch(src)), Ok(None));
+    assert_eq!(
+        assert_no_panic(|| dst.try_inline_mismatch_within(src, 2)),
+        Ok(BoundedSearch::NotFoundWithinLimit)
+    );
+    assert!(assert_no_panic(|| x86_strings_ops::try_copy(src, &mut *dst)).is_ok());
+    assert_eq!(
+        assert_no_panic(|| dst.try_inline_copy_within(1..3, 0)),
+        Ok(())
+    );
+    assert!(!assert_no_panic(|| src.inline_eq(dst)));
+    assert!(!assert_no_panic(|| src.inline_starts_with(dst)));
+    assert!(!assert_no_panic(|| src.inline_ends_with(dst)));
+    assert_eq!(assert_no_panic(|| src.inline_count_leading(1)), 1);
+
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::bits::try_fill_range(dst, 3..13, true)),
+        Ok(())
+    );
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::bits::try_copy_range(src, dst, 1..31)),
+        Ok(())
+    );
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::bits::try_count_ones_range(src, 0..40)),
+        Err(Error::LimitExceeded { len: 40, limit: 32 })
+    );
+
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::try_fill_where(dst, &[0b1001], 5)),
+        Ok(())
+    );
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::try_copy_strided(src, 2, &mut *dst, 2, 1)),
+        Err(Error::LenMismatch {
+            src_len: 2,
+            dst_len: 4
+        })
+    );
+    let rect = TileRect {
+        row: 1,
+        col: 0,
+        width: 2,
+        height: 1,
+    };
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::try_extract_tile(src, 2, rect, &mut dst[..2])),
+        Ok(())
+    );
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::try_copy_bytes_between(src, &mut *dst)),
+        Ok(())
+    );
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::try_copy_small(src, &mut *dst)),
+        Ok(())
+    );
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::try_fill_small(&mut *dst, 9)),
+        Ok(())
+    );
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::try_copy_medium(&src[1..], &mut *dst)),
+        Err(Error::LenMismatch {
+            src_len: 3,
+            dst_len: 4
+        })
+    );
+    assert_eq!(
+        assert_no_panic(|| x86_strings_ops::try_repack_rows(src, 2, &mut *dst, 2, 1, 2)),
+        Ok(())
+    );
+}
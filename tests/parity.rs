@@ -0,0 +1,172 @@
+//! Randomized parity checks of the public API against the reference implementations in
+//! `common::oracle`.
+//!
+//! Natively these exercise the inline assembly, under Miri the portable fallbacks:
+//!
+//! ```text
+//! cargo test --test parity
+//! cargo +nightly miri test --test parity
+//! ```
+
+mod common;
+
+use common::{iterations, mutate, oracle, random_vec, rng};
+use rand::Rng;
+use x86_strings_ops::{
+    backend, common_prefix_len, rep_cmps, rep_movs, rep_scas, rep_stos, repe_scas, repne_cmps,
+    try_dedup_consecutive_into, try_diff_summary, try_merge_sorted_runs, try_partition_copy,
+    SliceExt,
+};
+
+macro_rules! parity_primitives {
+    ($($name:ident: $ty:ty),*) => {
+        $(#[test]
+        fn $name() {
+            let mut rng = rng();
+            for _ in 0..iterations() {
+                let a = random_vec::<$ty>(&mut rng, 100);
+                let b = mutate(&mut rng, &a);
+                let value = rng.gen_range(0..4) as $ty;
+                let len = a.len();
+                unsafe {
+                    assert_eq!(rep_cmps(a.as_ptr(), b.as_ptr(), len), oracle::mismatch(&a, &b));
+                    assert_eq!(
+                        repne_cmps(a.as_ptr(), b.as_ptr(), len),
+                        oracle::match_position(&a, &b)
+                    );
+                    assert_eq!(rep_scas(a.as_ptr(), value, len), oracle::position(&a, value));
+                    assert_eq!(repe_scas(a.as_ptr(), value, len), oracle::position_not(&a, value));
+
+                    let mut dst = vec![<$ty>::MAX; len];
+                    rep_movs(a.as_ptr(), dst.as_mut_ptr(), len);
+                    assert_eq!(dst, a);
+                    rep_stos(value, dst.as_mut_ptr(), len);
+                    assert!(dst.iter().all(|x| *x == value));
+                }
+            }
+        })*
+    };
+}
+
+parity_primitives!(
+    test_primitives_u8: u8,
+    test_primitives_u16: u16,
+    test_primitives_u32: u32,
+    test_primitives_u64: u64
+);
+
+#[test]
+fn test_slice_ext() {
+    let mut rng = rng();
+    for _ in 0..iterations() {
+        let haystack = random_vec::<u32>(&mut rng, 100);
+        let needle = random_vec::<u32>(&mut rng, 4);
+        let value = rng.gen_range(0..4);
+        let n = rng.gen_range(0..4);
+        assert_eq!(
+            haystack.inline_position(value),
+            oracle::position(&haystack, value)
+        );
+        assert_eq!(
+            haystack.inline_position_nth(value, n),
+            oracle::position_nth(&haystack, value, n)
+        );
+        assert_eq!(
+            haystack.inline_rposition_nth(value, n),
+            oracle::rposition_nth(&haystack, value, n)
+        );
+        assert_eq!(
+            haystack.inline_position_not_any(&needle),
+            oracle::position_not_any(&haystack, &needle)
+        );
+        let bytes = haystack.iter().map(|&x| x as u8).collect::<Vec<_>>();
+        let set = needle.iter().map(|&x| x as u8).collect::<Vec<_>>();
+        assert_eq!(
+            bytes.inline_position_not_any(&set),
+            oracle::position_not_any(&bytes, &set)
+        );
+        assert_eq!(
+            haystack.inline_find_subslice(&needle),
+            oracle::find(&haystack, &needle)
+        );
+        assert_eq!(
+            haystack.inline_rfind_subslice(&needle),
+            oracle::rfind(&haystack, &needle)
+        );
+
+        let other = mutate(&mut rng, &haystack);
+        assert_eq!(
+            haystack.try_inline_mismatch(&other),
+            Ok(oracle::mismatch(&haystack, &other))
+        );
+        assert_eq!(
+            haystack.inline_match_position(&needle),
+            oracle::match_position(&haystack, &needle)
+        );
+        let slices = [&haystack[..], &other, &needle];
+        assert_eq!(
+            common_prefix_len(&slices),
+            oracle::common_prefix_len(&slices)
+        );
+    }
+}
+
+#[test]
+fn test_backend() {
+    let mut rng = rng();
+    for _ in 0..iterations() {
+        let a = random_vec::<u8>(&mut rng, 100);
+        let b = mutate(&mut rng, &a);
+        let value = rng.gen_range(0..4);
+        assert_eq!(backend::position(&a, value), oracle::position(&a, value));
+        assert_eq!(
+            backend::try_mismatch(&a, &b).unwrap(),
+            oracle::mismatch(&a, &b)
+        );
+        let mut dst = vec![0xFF; a.len()];
+        backend::try_copy(&a, &mut dst).unwrap();
+        assert_eq!(dst, a);
+    }
+}
+
+#[test]
+fn test_diff_summary() {
+    let mut rng = rng();
+    for _ in 0..iterations() {
+        let a = random_vec::<u8>(&mut rng, 100);
+        let b = mutate(&mut rng, &a);
+        let summary = try_diff_summary(&a, &b).unwrap();
+        let diffs = (0..a.len()).filter(|&i| a[i] != b[i]).collect::<Vec<_>>();
+        assert_eq!(summary.first_diff, diffs.first().copied());
+        assert_eq!(summary.last_diff, diffs.last().copied());
+        assert_eq!(summary.equal_bytes, a.len() - diffs.len());
+    }
+}
+
+#[test]
+fn test_runs() {
+    let mut rng = rng();
+    for _ in 0..iterations() {
+        let src = random_vec::<u16>(&mut rng, 100);
+        let mut dst = vec![0; src.len()];
+        let written = try_dedup_consecutive_into(&src, &mut dst).unwrap();
+        assert_eq!(dst[..written], oracle::dedup(&src));
+
+        let pivot = rng.gen_range(0..4);
+        let (mut lt, mut ge) = (vec![0; src.len()], vec![0; src.len()]);
+        let (lt_len, ge_len) = try_partition_copy(&src, pivot, &mut lt, &mut ge).unwrap();
+        let (expected_lt, expected_ge) = oracle::partition(&src, pivot);
+        assert_eq!(
+            (&lt[..lt_len], &ge[..ge_len]),
+            (&expected_lt[..], &expected_ge[..])
+        );
+
+        let mut a = random_vec::<u16>(&mut rng, 50);
+        let mut b = random_vec::<u16>(&mut rng, 50);
+        a.sort();
+        b.sort();
+        let mut merged = vec![0; a.len() + b.len()];
+        try_merge_sorted_runs(&a, &b, &mut merged).unwrap();
+        assert_eq!(merged, oracle::merge(&a, &b));
+    }
+}
@@ -0,0 +1,55 @@
+//! Registration of a custom backend, in its own test binary because a backend can only be
+//! registered once per process.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use x86_strings_ops::backend::{
+    current, fill, position, register, try_copy, try_mismatch, Backend, RepBackend, ScalarBackend,
+};
+
+/// Backend forwarding to [`RepBackend`] and counting its calls.
+struct CountingBackend(AtomicUsize);
+
+impl Backend for CountingBackend {
+    fn name(&self) -> &'static str {
+        "counting"
+    }
+
+    unsafe fn copy(&self, src: *const u8, dst: *mut u8, len: usize) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        RepBackend.copy(src, dst, len)
+    }
+
+    unsafe fn fill(&self, value: u8, dst: *mut u8, len: usize) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        RepBackend.fill(value, dst, len)
+    }
+
+    unsafe fn position(&self, src: *const u8, value: u8, len: usize) -> Option<usize> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        RepBackend.position(src, value, len)
+    }
+
+    unsafe fn mismatch(&self, a: *const u8, b: *const u8, len: usize) -> Option<usize> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        RepBackend.mismatch(a, b, len)
+    }
+}
+
+static COUNTING: CountingBackend = CountingBackend(AtomicUsize::new(0));
+
+#[test]
+fn test_register() {
+    assert!(register(&COUNTING).is_ok());
+    assert_eq!(register(&ScalarBackend).unwrap_err().name(), "counting");
+    assert_eq!(current().name(), "counting");
+
+    // longer than any of the detected default thresholds
+    let src = (0..1024).map(|i| i as u8 | 1).collect::<Vec<_>>();
+    let mut dst = vec![0_u8; 1024];
+    try_copy(&src, &mut dst).unwrap();
+    fill(&mut dst[512..], 0);
+    assert_eq!(dst[..512], src[..512]);
+    assert_eq!(position(&dst, 0), Some(512));
+    assert_eq!(try_mismatch(&dst, &src), Ok(Some(512)));
+    assert!(COUNTING.0.load(Ordering::Relaxed) >= 4);
+}
@@ -0,0 +1,137 @@
+//! End to end workload resembling an HTTP/1.1 style parser, exercising the public API of several
+//! modules together and asserting byte exact results against a straightforward implementation
+//! using only the standard library.
+//!
+//! The stream is received in MTU sized frames, messages are framed by scanning for delimiters,
+//! headers are compared ignoring case, and the bodies are copied into a packet arena and into
+//! records padded to a multiple of 16 bytes. In release mode this also serves as a realistic
+//! benchmark target:
+//!
+//! ```text
+//! cargo test --release --test workload
+//! ```
+
+use x86_strings_ops::{
+    deep_eq, eq_ignore_ascii_case_inline, normalize_ascii_lowercase_trimmed, try_chunked_copy_iter,
+    try_copy, try_copy_packets, try_diff_summary, Finder, SliceExt,
+};
+
+const MESSAGES: usize = 200;
+const MTU: usize = 1500;
+const RECORD_ALIGN: usize = 16;
+
+static HEADER_END: Finder<'static> = Finder::new(b"\r\n\r\n");
+
+/// Deterministic stream of messages with varying header case, header order and body lengths.
+fn stream() -> (Vec<u8>, Vec<Vec<u8>>) {
+    let mut stream = Vec::new();
+    let mut bodies = Vec::new();
+    for i in 0..MESSAGES {
+        let body = (0..(i * 37) % 700)
+            .map(|j| b"abcdefghij\r\n:"[(i + j) % 13])
+            .collect::<Vec<u8>>();
+        let length_header = ["Content-Length", "content-length", "CONTENT-LENGTH"][i % 3];
+        stream.extend_from_slice(format!("POST /items/{i} HTTP/1.1\r\n").as_bytes());
+        if i % 2 == 0 {
+            stream.extend_from_slice(b"Host: example.com\r\n");
+        }
+        stream.extend_from_slice(format!("{length_header}:  {}\r\n", body.len()).as_bytes());
+        stream.extend_from_slice(format!("X-Trace: {:08x}\r\n\r\n", i * 7919).as_bytes());
+        stream.extend_from_slice(&body);
+        bodies.push(body);
+    }
+    (stream, bodies)
+}
+
+/// Receive `stream` in frames of at most `MTU` bytes.
+fn receive(stream: &[u8]) -> Vec<u8> {
+    let mut received = Vec::with_capacity(stream.len());
+    let mut frames = try_chunked_copy_iter(stream, MTU).unwrap();
+    let mut frame = [0_u8; MTU];
+    while let Some(len) = frames.try_copy_next(&mut frame).unwrap() {
+        received.extend_from_slice(&frame[..len]);
+    }
+    received
+}
+
+/// Split `received` into messages and return their bodies.
+fn parse(received: &[u8]) -> Vec<&[u8]> {
+    let mut bodies = Vec::new();
+    let mut rest = received;
+    while !rest.is_empty() {
+        let header_len = HEADER_END.find(rest).expect("header terminator");
+        let mut headers = &rest[..header_len + 2];
+        let mut content_length = None;
+        // skip the request line
+        headers = &headers[headers.inline_position(b'\n').unwrap() + 1..];
+        while let Some(end) = headers.inline_position(b'\n') {
+            let line = &headers[..end - 1];
+            let colon = line.inline_position(b':').expect("header separator");
+            let (name, value) = (&line[..colon], &line[colon + 1..]);
+            if eq_ignore_ascii_case_inline(name, b"content-length") {
+                let value = normalize_ascii_lowercase_trimmed(value);
+                content_length = Some(std::str::from_utf8(&value).unwrap().parse().unwrap());
+            }
+            headers = &headers[end + 1..];
+        }
+        let content_length: usize = content_length.expect("content length");
+        let body_start = header_len + 4;
+        bodies.push(&rest[body_start..body_start + content_length]);
+        rest = &rest[body_start + content_length..];
+    }
+    bodies
+}
+
+/// Copy `bodies` into records that are padded with zeros to a multiple of `RECORD_ALIGN` bytes.
+fn pad_records(bodies: &[&[u8]]) -> Vec<u8> {
+    let total = bodies
+        .iter()
+        .map(|body| body.len().next_multiple_of(RECORD_ALIGN))
+        .sum();
+    let mut records = vec![0xEE_u8; total];
+    let mut offset = 0;
+    for body in bodies {
+        let padded = body.len().next_multiple_of(RECORD_ALIGN);
+        let record = &mut records[offset..offset + padded];
+        let (data, padding) = record.split_at_mut(body.len());
+        try_copy(*body, data).unwrap();
+        padding.inline_fill(0);
+        offset += padded;
+    }
+    records
+}
+
+#[test]
+fn test_parser_workload() {
+    let (stream, expected_bodies) = stream();
+    let received = receive(&stream);
+    assert_eq!(
+        try_diff_summary(&received, &stream).unwrap().first_diff,
+        None
+    );
+
+    let bodies = parse(&received);
+    let expected = expected_bodies
+        .iter()
+        .map(Vec::as_slice)
+        .collect::<Vec<_>>();
+    assert!(deep_eq(&bodies, &expected));
+
+    let mut arena = vec![0_u8; stream.len()];
+    let mut offsets = vec![0; bodies.len()];
+    assert_eq!(
+        try_copy_packets(&bodies, &mut arena, &mut offsets),
+        Ok(bodies.len())
+    );
+    let arena_len = offsets.last().unwrap() + bodies.last().unwrap().len();
+    assert_eq!(&arena[..arena_len], expected_bodies.concat());
+
+    let records = pad_records(&bodies);
+    let mut expected_records = Vec::new();
+    for body in &expected_bodies {
+        let mut record = body.clone();
+        record.resize(body.len().next_multiple_of(RECORD_ALIGN), 0);
+        expected_records.extend_from_slice(&record);
+    }
+    assert_eq!(records, expected_records);
+}